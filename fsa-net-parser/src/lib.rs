@@ -4,15 +4,45 @@ lalrpop_mod!(fsa_net_lang);
 
 pub mod syntax_tree;
 
+pub use lalrpop_util::ParseError;
 
 
+
+/**
+ * Reserved words of the language: they can be used
+ * as names only when quoted.
+ */
+pub const KEYWORDS: &[&str] = &[
+    "network", "events", "automata", "rel", "obs", "state", "begin", "link", "request", "trans",
+    "space", "linspace", "src", "dst", "input", "output", "diagnosis", "save", "load",
+    "diagnoser", "closures", "diagnosability",
+];
+
+/**
+ * Keywords that have a meaning only at the start
+ * of their clause: elsewhere they are plain names.
+ */
+pub const CONTEXTUAL_KEYWORDS: &[&str] = &[
+    "import", "template", "capacity", "strategy", "check", "witness", "likely", "prob",
+    "cost", "cheapest", "final", "init",
+];
+
 pub type Code<'a> = Vec<syntax_tree::Block<'a>>;
-pub type SyntaxError<'a> = lalrpop_util::ParseError<usize, lalrpop_util::lexer::Token<'a>, syntax_tree::TransitionFactoryError>;
+pub type SyntaxError<'a> = lalrpop_util::ParseError<usize, lalrpop_util::lexer::Token<'a>, syntax_tree::UserError>;
 
 pub fn parse<'a>(code: &'a str) -> Result<Code<'a>, SyntaxError<'a>>{
+    parse_with_offset(code, 0)
+}
 
+/**
+ * Parse code whose first byte is at position `offset`.
+ * All the locations, in the syntax tree and in the errors,
+ * are shifted by `offset`: this allows to parse multiple files
+ * keeping each location unique.
+ */
+pub fn parse_with_offset<'a>(code: &'a str, offset: usize) -> Result<Code<'a>, SyntaxError<'a>>{
     let parser = fsa_net_lang::FsacodeParser::new();
-    parser.parse(code)
+    parser.parse(offset, code).map_err(|err| err.map_location(|loc| loc + offset))
 }
 
 
@@ -36,10 +66,330 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_offset() {
+        let code = "network N {\n    link L A B\n}";
+        let ast = parse_with_offset(code, 100).unwrap();
+        match &ast[0] {
+            syntax_tree::Block::Network(net) => assert_eq!(net.get_location(), (100, 128)),
+            _ => panic!("expected a network"),
+        }
+
+        let err = parse_with_offset("network", 100).err().unwrap();
+        match err {
+            ParseError::UnrecognizedEOF { location, .. } => assert_eq!(location, 107),
+            _ => panic!("expected UnrecognizedEOF, found {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_link_capacity() {
+        let code = "network N {\n    link L1 A B\n    link L2 A B capacity 3\n}";
+        let ast = parse(code).unwrap();
+        let capacities: Vec<Option<usize>> = match &ast[0] {
+            syntax_tree::Block::Network(net) => net
+                .params
+                .iter()
+                .filter_map(|param| match &param.param {
+                    syntax_tree::NetworkParameter::Link(link) => Some(link.capacity),
+                    _ => None,
+                })
+                .collect(),
+            _ => panic!("expected a network"),
+        };
+        assert_eq!(capacities, vec![None, Some(3)]);
+
+        let invalid = [
+            "network N {\n link L A B capacity 0\n}",
+            "network N {\n link L A B capacity x\n}",
+        ];
+        for code in &invalid {
+            match parse(code).err().unwrap() {
+                ParseError::User {
+                    error: syntax_tree::UserError::Declaration(error),
+                } => assert_eq!(error.get_location(), (24, 34)),
+                err => panic!("expected a Declaration error, found {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_obs_steps() {
+        let code = "request N {\n    diagnosis o1, {o2 | 'o 3'}, ?\n}";
+        let ast = parse(code).unwrap();
+        let steps = match &ast[0] {
+            syntax_tree::Block::Request(req) => match &req.list[0].cmd {
+                syntax_tree::Command::Diagnosis(syntax_tree::DiagnosisCommand::Fresh(cmd)) => {
+                    cmd.obs_list.clone()
+                }
+                _ => panic!("expected a diagnosis"),
+            },
+            _ => panic!("expected a request"),
+        };
+        let expected = vec![
+            syntax_tree::ObsStep::Label("o1"),
+            syntax_tree::ObsStep::Set(vec!["o2", "o 3"]),
+            syntax_tree::ObsStep::Any,
+        ];
+        assert_eq!(steps, expected);
+
+        let code = "request N {\n    linspace o1, o2*, (o3 | ?, o1)+\n}";
+        let steps = match &parse(code).unwrap()[0] {
+            syntax_tree::Block::Request(req) => match &req.list[0].cmd {
+                syntax_tree::Command::Linspace(cmd) => cmd.obs_list.clone(),
+                _ => panic!("expected a linspace"),
+            },
+            _ => panic!("expected a request"),
+        };
+        let group = syntax_tree::ObsStep::Group(vec![
+            vec![syntax_tree::ObsStep::Label("o3")],
+            vec![syntax_tree::ObsStep::Any, syntax_tree::ObsStep::Label("o1")],
+        ]);
+        let expected = vec![
+            syntax_tree::ObsStep::Label("o1"),
+            syntax_tree::ObsStep::Star(Box::new(syntax_tree::ObsStep::Label("o2"))),
+            syntax_tree::ObsStep::Plus(Box::new(group)),
+        ];
+        assert_eq!(steps, expected);
+
+        assert!(parse("request N {\n    linspace {}\n}").is_err());
+        assert!(parse("request N {\n    linspace {o1|}\n}").is_err());
+        assert!(parse("request N {\n    linspace o1**\n}").is_err());
+    }
+
+    #[test]
+    fn test_diagnosis_strategy() {
+        let code = "request N {\n    diagnosis o1 strategy size\n    diagnosis load f\n}";
+        let orders: Vec<Option<syntax_tree::EliminationOrder>> = match &parse(code).unwrap()[0] {
+            syntax_tree::Block::Request(req) => req
+                .list
+                .iter()
+                .map(|cmd| match &cmd.cmd {
+                    syntax_tree::Command::Diagnosis(syntax_tree::DiagnosisCommand::Fresh(cmd)) => {
+                        cmd.order
+                    }
+                    syntax_tree::Command::Diagnosis(syntax_tree::DiagnosisCommand::Load(cmd)) => {
+                        cmd.order
+                    }
+                    _ => panic!("expected a diagnosis"),
+                })
+                .collect(),
+            _ => panic!("expected a request"),
+        };
+        assert_eq!(orders, vec![Some(syntax_tree::EliminationOrder::MinSize), None]);
+
+        let code = "request N {\n diagnosis o1 strategy fast\n}";
+        match parse(code).err().unwrap() {
+            ParseError::User { error } => assert_eq!(error.get_location(), (26, 39)),
+            err => panic!("expected a User error, found {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let code = "request N {\n    diagnosis o1 strategy size check [r, 'f 1'], []\n}";
+        let cmd = match &parse(code).unwrap()[0] {
+            syntax_tree::Block::Request(req) => match &req.list[0].cmd {
+                syntax_tree::Command::Check(cmd) => cmd.clone(),
+                _ => panic!("expected a check"),
+            },
+            _ => panic!("expected a request"),
+        };
+        assert_eq!(cmd.strings, vec![vec!["r", "f 1"], vec![]]);
+        assert_eq!(cmd.get_location(), (16, 63));
+        match cmd.diagnosis {
+            syntax_tree::DiagnosisCommand::Fresh(diagnosis) => {
+                assert_eq!(diagnosis.order, Some(syntax_tree::EliminationOrder::MinSize))
+            }
+            _ => panic!("expected a fresh diagnosis"),
+        }
+
+        assert!(parse("request N {\n    diagnosis o1 check\n}").is_err());
+        assert!(parse("request N {\n    diagnosis o1 check [r,]\n}").is_err());
+    }
+
+    #[test]
+    fn test_witness() {
+        let code = "request N {\n    diagnosis o1 witness 2\n    diagnosis o1 witness\n}";
+        let lengths: Vec<Option<usize>> = match &parse(code).unwrap()[0] {
+            syntax_tree::Block::Request(req) => req
+                .list
+                .iter()
+                .map(|cmd| match &cmd.cmd {
+                    syntax_tree::Command::Witness(cmd) => cmd.length,
+                    _ => panic!("expected a witness"),
+                })
+                .collect(),
+            _ => panic!("expected a request"),
+        };
+        assert_eq!(lengths, vec![Some(2), None]);
+
+        assert!(parse("request N {\n    diagnosis load f witness 2\n}").is_err());
+        match parse("request N {\n diagnosis o1 witness x\n}").err().unwrap() {
+            ParseError::User { error } => assert_eq!(error.get_location(), (34, 35)),
+            err => panic!("expected a User error, found {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_probability() {
+        let code = "network N {\n automata A {\n  trans t {\n   src a\n   dst b\n   prob .5\n  }\n  trans u {\n   src a\n   dst a\n   prob 2\n  }\n }\n}";
+        let probs: Vec<Option<f64>> = match &parse(code).unwrap()[0] {
+            syntax_tree::Block::Network(net) => net
+                .params
+                .iter()
+                .flat_map(|param| match &param.param {
+                    syntax_tree::NetworkParameter::Automata(automata) => automata
+                        .params
+                        .iter()
+                        .filter_map(|param| match &param.param {
+                            syntax_tree::AutomataParameter::Transition(trans) => Some(trans.prob),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => vec![],
+                })
+                .collect(),
+            _ => panic!("expected a network"),
+        };
+        assert_eq!(probs, vec![Some(0.5), Some(2.0)]);
+
+        let invalid = [
+            "network N {\n automata A {\n  trans t {\n   src a\n   dst b\n   prob 0\n  }\n }\n}",
+            "network N {\n automata A {\n  trans t {\n   src a\n   dst b\n   prob x\n  }\n }\n}",
+        ];
+        for code in &invalid {
+            match parse(code).err().unwrap() {
+                ParseError::User { error } => assert_eq!(error.get_location(), (59, 65)),
+                err => panic!("expected a User error, found {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_likely() {
+        let code = "request N {\n    diagnosis o1 likely 2\n    diagnosis o1 likely\n}";
+        let counts: Vec<Option<usize>> = match &parse(code).unwrap()[0] {
+            syntax_tree::Block::Request(req) => req
+                .list
+                .iter()
+                .map(|cmd| match &cmd.cmd {
+                    syntax_tree::Command::Likely(cmd) => cmd.count,
+                    _ => panic!("expected a likely diagnosis"),
+                })
+                .collect(),
+            _ => panic!("expected a request"),
+        };
+        assert_eq!(counts, vec![Some(2), None]);
+
+        match parse("request N {\n diagnosis o1 likely 0\n}").err().unwrap() {
+            ParseError::User { error } => assert_eq!(error.get_location(), (33, 34)),
+            err => panic!("expected a User error, found {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_cost() {
+        let code = "network N {\n automata A {\n  trans t {\n   src a\n   dst b\n   cost 3\n  }\n }\n}\nrequest N {\n diagnosis o1 cheapest 2\n}";
+        let ast = parse(code).unwrap();
+        let cost = match &ast[0] {
+            syntax_tree::Block::Network(net) => match &net.params[0].param {
+                syntax_tree::NetworkParameter::Automata(automata) => {
+                    match &automata.params[0].param {
+                        syntax_tree::AutomataParameter::Transition(trans) => trans.cost,
+                        _ => panic!("expected a transition"),
+                    }
+                }
+                _ => panic!("expected an automata"),
+            },
+            _ => panic!("expected a network"),
+        };
+        assert_eq!(cost, Some(3));
+        match &ast[1] {
+            syntax_tree::Block::Request(req) => match &req.list[0].cmd {
+                syntax_tree::Command::Cheapest(cmd) => assert_eq!(cmd.count, Some(2)),
+                _ => panic!("expected a cheapest diagnosis"),
+            },
+            _ => panic!("expected a request"),
+        }
+
+        let code = "network N {\n automata A {\n  trans t {\n   src a\n   dst b\n   cost 1.5\n  }\n }\n}";
+        assert!(parse(code).is_err());
+    }
+
+    #[test]
+    fn test_final() {
+        let code = "network N {\n final both\n automata A {\n  begin a\n  state b\n  final a, b\n }\n}";
+        let ast = parse(code).unwrap();
+        let params = match &ast[0] {
+            syntax_tree::Block::Network(net) => &net.params,
+            _ => panic!("expected a network"),
+        };
+        match &params[0].param {
+            syntax_tree::NetworkParameter::Final(criterion) => {
+                assert_eq!(*criterion, syntax_tree::FinalCriterion::Both)
+            }
+            _ => panic!("expected a final criterion"),
+        }
+        match &params[1].param {
+            syntax_tree::NetworkParameter::Automata(automata) => match &automata.params[2].param {
+                syntax_tree::AutomataParameter::Final(states) => assert_eq!(states, &vec!["a", "b"]),
+                _ => panic!("expected final states"),
+            },
+            _ => panic!("expected an automata"),
+        }
+
+        match parse("network N {\n final all\n}").err().unwrap() {
+            ParseError::User { error } => assert_eq!(error.get_location(), (13, 22)),
+            err => panic!("expected a User error, found {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_link_init() {
+        let code = "network N {\n    link L1 A B init e1\n    link L2 A B capacity 2 init 'e 2'\n    link L3 A B\n}";
+        let ast = parse(code).unwrap();
+        let inits: Vec<(Option<usize>, Option<&str>)> = match &ast[0] {
+            syntax_tree::Block::Network(net) => net
+                .params
+                .iter()
+                .filter_map(|param| match &param.param {
+                    syntax_tree::NetworkParameter::Link(link) => Some((link.capacity, link.init)),
+                    _ => None,
+                })
+                .collect(),
+            _ => panic!("expected a network"),
+        };
+        assert_eq!(inits, vec![(None, Some("e1")), (Some(2), Some("e 2")), (None, None)]);
+
+        assert!(parse("network N {\n link L A B init\n}").is_err());
+    }
+
+    #[test]
+    fn test_contextual_keywords() {
+        let code = "network import {\n    events import\n}";
+        let ast = parse(code).unwrap();
+        match &ast[0] {
+            syntax_tree::Block::Network(net) => {
+                assert_eq!(net.name, "import");
+                match &net.params[0].param {
+                    syntax_tree::NetworkParameter::Events(events) => {
+                        assert_eq!(events, &vec!["import"])
+                    }
+                    _ => panic!("expected the events"),
+                }
+            }
+            _ => panic!("expected a network"),
+        }
+        for keyword in CONTEXTUAL_KEYWORDS {
+            assert!(!KEYWORDS.contains(keyword));
+        }
+    }
+
     fn try_syntax(file: &PathBuf) -> Result<bool, std::io::Error> {
         let code = load_file(file)?;
         let parser = fsa_net_lang::FsacodeParser::new();
-        let result = parser.parse(&code);
+        let result = parser.parse(0, &code);
         result.unwrap();
         Ok(true)
     }
@@ -1,12 +1,21 @@
 use item_location_derive::{add_location, DefaultBuilder};
 
+#[derive(Clone)]
 pub enum Block<'a> {
     Network(Network<'a>),
     Request(Request<'a>),
+    Import(Import<'a>),
+    Template(AutomataTemplate<'a>),
 }
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
+pub struct Import<'a> {
+    pub path: &'a str,
+}
+
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
 pub struct Network<'a> {
     pub name: &'a str,
     pub params: Vec<NetworkParameterDecl<'a>>,
@@ -14,46 +23,109 @@ pub struct Network<'a> {
 
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct NetworkParameterDecl<'a> {
     pub param : NetworkParameter<'a>
 }
 
+#[derive(Clone)]
 pub enum NetworkParameter<'a> {
     Automata(Automata<'a>),
+    Template(AutomataTemplate<'a>),
+    Instance(AutomataInstance<'a>),
     Link(Link<'a>),
     Events(Vec<&'a str>),
     ObserveLabels(Vec<&'a str>),
     RelevanceLabels(Vec<&'a str>),
+    Final(FinalCriterion),
+}
+
+/*
+    Criterion chosen by the `final` declaration of a network
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FinalCriterion {
+    EmptyLinks,
+    FinalStates,
+    Both,
+    Any,
+}
+
+impl FinalCriterion {
+    pub const NAMES: [&'static str; 4] = ["links", "states", "both", "any"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "links" => Some(Self::EmptyLinks),
+            "states" => Some(Self::FinalStates),
+            "both" => Some(Self::Both),
+            "any" => Some(Self::Any),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::EmptyLinks => "links",
+            Self::FinalStates => "states",
+            Self::Both => "both",
+            Self::Any => "any",
+        }
+    }
 }
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct Automata<'a> {
     pub name: &'a str,
     pub params: Vec<AutomataParameterDecl<'a>>,
 }
 
+/**
+ * An automata whose links are parameters:
+ * each instance replaces the parameters with
+ * actual link names.
+ */
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
+pub struct AutomataTemplate<'a> {
+    pub name: &'a str,
+    pub args: Vec<&'a str>,
+    pub params: Vec<AutomataParameterDecl<'a>>,
+}
+
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
+pub struct AutomataInstance<'a> {
+    pub name: &'a str,
+    pub template: &'a str,
+    pub args: Vec<&'a str>,
+}
+
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
 pub struct AutomataParameterDecl<'a> {
     pub param: AutomataParameter<'a>
 }
 
+#[derive(Clone)]
 pub enum AutomataParameter<'a> {
     StateDecl(StateDeclaration<'a>),
     Transition(TransitionDeclaration<'a>),
+    // references to states declared elsewhere
+    Final(Vec<&'a str>),
 }
 
 
 
+#[derive(Clone)]
 pub enum StateDeclaration<'a> {
     State(&'a str),
     Begin(&'a str),
 }
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct TransitionDeclaration<'a> {
     pub name: &'a str,
     pub source: &'a str,
@@ -62,11 +134,27 @@ pub struct TransitionDeclaration<'a> {
     pub output: Option<Vec<Event<'a>>>,
     pub rel_label: Option<&'a str>,
     pub obs_label: Option<&'a str>,
+    // relative weight among the transitions leaving a state
+    pub prob: Option<f64>,
+    pub cost: Option<usize>,
+    // keys of a complex declaration with their location, in source order
+    pub keys: Vec<(TransitionKeyName, (usize, usize))>,
 }
 
 impl<'a> TransitionDeclaration<'a> {
     pub fn simple_decl(name: &'a str, source: &'a str, destination: &'a str) -> Self {
-        Self::new(name, source, destination, None, None, None, None)
+        Self::new(
+            name,
+            source,
+            destination,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        )
     }
 }
 
@@ -93,12 +181,138 @@ impl TransitionFactoryError {
             error_type: TransitionFactoryErrorType::MissingSourceOrDestination,
         }
     }
+
+    pub(crate) fn new_invalid_probability(begin: usize, end: usize) -> Self {
+        Self {
+            begin,
+            end,
+            error_type: TransitionFactoryErrorType::InvalidProbability,
+        }
+    }
+
+    pub(crate) fn new_invalid_cost(begin: usize, end: usize) -> Self {
+        Self {
+            begin,
+            end,
+            error_type: TransitionFactoryErrorType::InvalidCost,
+        }
+    }
+
+    pub fn get_location(&self) -> (usize, usize) {
+        (self.begin, self.end)
+    }
+
+    pub fn get_error_type(&self) -> &TransitionFactoryErrorType {
+        &self.error_type
+    }
 }
 
 #[derive(Debug)]
 pub enum TransitionFactoryErrorType {
     MissingSourceOrDestination,
     DuplicatedKey,
+    InvalidProbability,
+    InvalidCost,
+}
+
+/**
+ * Invalid value in a declaration
+ * other than a transition one
+ */
+#[derive(Debug)]
+pub struct DeclarationError {
+    begin: usize,
+    end: usize,
+    error_type: DeclarationErrorType,
+}
+
+impl DeclarationError {
+    pub(crate) fn new_invalid_capacity(begin: usize, end: usize) -> Self {
+        Self {
+            begin,
+            end,
+            error_type: DeclarationErrorType::InvalidCapacity,
+        }
+    }
+
+    pub(crate) fn new_invalid_strategy(begin: usize, end: usize) -> Self {
+        Self {
+            begin,
+            end,
+            error_type: DeclarationErrorType::InvalidStrategy,
+        }
+    }
+
+    pub(crate) fn new_invalid_length(begin: usize, end: usize) -> Self {
+        Self {
+            begin,
+            end,
+            error_type: DeclarationErrorType::InvalidLength,
+        }
+    }
+
+    pub(crate) fn new_invalid_count(begin: usize, end: usize) -> Self {
+        Self {
+            begin,
+            end,
+            error_type: DeclarationErrorType::InvalidCount,
+        }
+    }
+
+    pub(crate) fn new_invalid_criterion(begin: usize, end: usize) -> Self {
+        Self {
+            begin,
+            end,
+            error_type: DeclarationErrorType::InvalidCriterion,
+        }
+    }
+
+    pub fn get_location(&self) -> (usize, usize) {
+        (self.begin, self.end)
+    }
+
+    pub fn get_error_type(&self) -> &DeclarationErrorType {
+        &self.error_type
+    }
+}
+
+#[derive(Debug)]
+pub enum DeclarationErrorType {
+    InvalidCapacity,
+    InvalidStrategy,
+    InvalidLength,
+    InvalidCount,
+    InvalidCriterion,
+}
+
+/**
+ * Errors reported by the parser actions
+ */
+#[derive(Debug)]
+pub enum UserError {
+    Transition(TransitionFactoryError),
+    Declaration(DeclarationError),
+}
+
+impl UserError {
+    pub fn get_location(&self) -> (usize, usize) {
+        match self {
+            Self::Transition(err) => err.get_location(),
+            Self::Declaration(err) => err.get_location(),
+        }
+    }
+}
+
+impl From<TransitionFactoryError> for UserError {
+    fn from(err: TransitionFactoryError) -> Self {
+        Self::Transition(err)
+    }
+}
+
+impl From<DeclarationError> for UserError {
+    fn from(err: DeclarationError) -> Self {
+        Self::Declaration(err)
+    }
 }
 
 #[add_location]
@@ -146,6 +360,9 @@ pub struct ComplexTransactionFactory<'a> {
     output: TransitionParameterFactory<Vec<Event<'a>>>,
     rel: TransitionParameterFactory<&'a str>,
     obs: TransitionParameterFactory<&'a str>,
+    prob: TransitionParameterFactory<f64>,
+    cost: TransitionParameterFactory<usize>,
+    keys: Vec<(TransitionKeyName, (usize, usize))>,
     begin: usize,
     end: usize,
 }
@@ -174,6 +391,9 @@ impl<'a> ComplexTransactionFactory<'a> {
                 self.output.get_param(),
                 self.rel.get_param(),
                 self.obs.get_param(),
+                self.prob.get_param(),
+                self.cost.get_param(),
+                self.keys,
             );
             Ok(output)
         } else {
@@ -185,6 +405,7 @@ impl<'a> ComplexTransactionFactory<'a> {
 
     pub fn set_parameter(mut self, key: TransitionKey<'a>) -> Result<Self, TransitionFactoryError> {
         let loc = key.get_location();
+        self.keys.push((key.key.name(), loc));
         match key.key {
             TransitionKeys::Src(param) => self.src = self.src.set_value(param, loc)?,
             TransitionKeys::Dst(param) => self.dst = self.dst.set_value(param, loc)?,
@@ -192,6 +413,8 @@ impl<'a> ComplexTransactionFactory<'a> {
             TransitionKeys::Output(param) => self.output = self.output.set_value(param, loc)?,
             TransitionKeys::Rel(param) => self.rel = self.rel.set_value(param, loc)?,
             TransitionKeys::Obs(param) => self.obs = self.obs.set_value(param, loc)?,
+            TransitionKeys::Prob(param) => self.prob = self.prob.set_value(param, loc)?,
+            TransitionKeys::Cost(param) => self.cost = self.cost.set_value(param, loc)?,
         }
         Ok(self)
     }
@@ -210,6 +433,35 @@ pub enum TransitionKeys<'a> {
     Output(Vec<Event<'a>>),
     Rel(&'a str),
     Obs(&'a str),
+    Prob(f64),
+    Cost(usize),
+}
+
+impl<'a> TransitionKeys<'a> {
+    pub fn name(&self) -> TransitionKeyName {
+        match self {
+            Self::Src(_) => TransitionKeyName::Src,
+            Self::Dst(_) => TransitionKeyName::Dst,
+            Self::Input(_) => TransitionKeyName::Input,
+            Self::Output(_) => TransitionKeyName::Output,
+            Self::Rel(_) => TransitionKeyName::Rel,
+            Self::Obs(_) => TransitionKeyName::Obs,
+            Self::Prob(_) => TransitionKeyName::Prob,
+            Self::Cost(_) => TransitionKeyName::Cost,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionKeyName {
+    Src,
+    Dst,
+    Input,
+    Output,
+    Rel,
+    Obs,
+    Prob,
+    Cost,
 }
 
 pub fn remove_quotes<'a>(quoted_str: &'a str) -> &'a str {
@@ -218,46 +470,86 @@ pub fn remove_quotes<'a>(quoted_str: &'a str) -> &'a str {
 }
 
 #[add_location]
-#[derive(DefaultBuilder, Default)]
+#[derive(DefaultBuilder, Default, Clone)]
 pub struct Event<'a> {
     pub name: &'a str,
     pub link: &'a str,
 }
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct Link<'a> {
     pub name: &'a str,
     pub source: &'a str,
     pub destination: &'a str,
+    pub capacity: Option<usize>,
+    // event pending on the link in the initial state
+    pub init: Option<&'a str>,
 }
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct Request<'a> {
     pub name: &'a str,
     pub list: Vec<CommandDecl<'a>>,
 }
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct CommandDecl<'a> {
     pub cmd : Command<'a>,
 }
 
+#[derive(Clone)]
 pub enum Command<'a> {
     Space,
+    Closures,
+    Diagnosability,
     Linspace(LinspaceCommand<'a>),
     Diagnosis(DiagnosisCommand<'a>),
+    Diagnoser(DiagnoserCommand<'a>),
+    Check(CheckCommand<'a>),
+    Witness(WitnessCommand<'a>),
+    Likely(LikelyCommand<'a>),
+    Cheapest(CheapestCommand<'a>),
 }
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct LinspaceCommand<'a> {
-    pub name_list: Vec<&'a str>,
+    pub obs_list: Vec<ObsStep<'a>>,
     pub save_file: Option<&'a str>
 }
 
+/*
+    A step of an observation pattern: a single label,
+    a set of possible labels, any label at all or
+    a group of alternative chains, possibly repeated.
+    A plain list of steps is a chain.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObsStep<'a> {
+    Label(&'a str),
+    Set(Vec<&'a str>),
+    Any,
+    Group(Vec<Vec<ObsStep<'a>>>),
+    Star(Box<ObsStep<'a>>),
+    Plus(Box<ObsStep<'a>>)
+}
+
+impl<'a> ObsStep<'a> {
+    pub fn names(&self) -> Vec<&'a str> {
+        match self {
+            Self::Label(name) => vec![name],
+            Self::Set(names) => names.clone(),
+            Self::Any => vec![],
+            Self::Group(alts) => alts.iter().flatten().flat_map(|s| s.names()).collect(),
+            Self::Star(step) | Self::Plus(step) => step.names()
+        }
+    }
+}
+
+#[derive(Clone)]
 pub enum DiagnosisCommand<'a> {
     Fresh(FreshDiagnosisCommand<'a>),
     Load(LoadDiagnosisCommand<'a>)
@@ -265,12 +557,118 @@ pub enum DiagnosisCommand<'a> {
 
 
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct FreshDiagnosisCommand<'a> {
-    pub name_list: Vec<&'a str>,
+    pub obs_list: Vec<ObsStep<'a>>,
+    pub order: Option<EliminationOrder>,
 }
 #[add_location]
-#[derive(DefaultBuilder)]
+#[derive(DefaultBuilder, Clone)]
 pub struct LoadDiagnosisCommand<'a> {
     pub file: &'a str,
+    pub order: Option<EliminationOrder>,
+}
+
+/*
+    Heuristic choosing the next node removed
+    by state elimination during the diagnosis
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EliminationOrder {
+    MaxDegree,
+    MinProduct,
+    MinSize,
+    FewestLoops,
+    Topological,
+}
+
+impl EliminationOrder {
+    pub const NAMES: [&'static str; 5] = ["degree", "product", "size", "loops", "topological"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "degree" => Some(Self::MaxDegree),
+            "product" => Some(Self::MinProduct),
+            "size" => Some(Self::MinSize),
+            "loops" => Some(Self::FewestLoops),
+            "topological" => Some(Self::Topological),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MaxDegree => "degree",
+            Self::MinProduct => "product",
+            Self::MinSize => "size",
+            Self::FewestLoops => "loops",
+            Self::Topological => "topological",
+        }
+    }
+}
+
+/*
+    Test each string of relevance labels
+    against the result of the diagnosis
+*/
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
+pub struct CheckCommand<'a> {
+    pub diagnosis: DiagnosisCommand<'a>,
+    pub strings: Vec<Vec<&'a str>>,
+}
+
+/*
+    Shortest trajectories explaining the diagnosis:
+    one for each relevance string up to `length` labels,
+    one for each set of relevance labels without it
+*/
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
+pub struct WitnessCommand<'a> {
+    pub diagnosis: FreshDiagnosisCommand<'a>,
+    pub length: Option<usize>,
+}
+
+/*
+    The `count` most likely relevance strings
+    explaining the observation, one when not given
+*/
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
+pub struct LikelyCommand<'a> {
+    pub diagnosis: FreshDiagnosisCommand<'a>,
+    pub count: Option<usize>,
+}
+
+/*
+    The `count` relevance strings explaining the
+    observation with the lowest total transition cost,
+    one when not given
+*/
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
+pub struct CheapestCommand<'a> {
+    pub diagnosis: FreshDiagnosisCommand<'a>,
+    pub count: Option<usize>,
+}
+
+#[derive(Clone)]
+pub enum DiagnoserCommand<'a> {
+    Build(BuildDiagnoserCommand<'a>),
+    Load(LoadDiagnoserCommand<'a>)
+}
+
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
+pub struct BuildDiagnoserCommand<'a> {
+    pub name_list: Vec<&'a str>,
+    pub save_file: Option<&'a str>
+}
+
+#[add_location]
+#[derive(DefaultBuilder, Clone)]
+pub struct LoadDiagnoserCommand<'a> {
+    pub file: &'a str,
+    pub name_list: Vec<&'a str>,
 }
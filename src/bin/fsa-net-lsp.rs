@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(err) = fsa_net::lsp::run() {
+        eprintln!("fsa-net-lsp: {}", err);
+        std::process::exit(1);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::engine::EliminationOrder;
+
 #[derive(Debug)]
 pub struct Requests {
     pub commands: Vec<Command>,
@@ -12,12 +14,35 @@ impl Requests {
 #[derive(Debug)]
 pub enum Command {
     FullSpace,
-    Linspace((Vec<usize>, Option<usize>)),
+    Closures,
+    Diagnosability,
+    Linspace((Vec<ObsStep>, Option<usize>)),
     Diagnosis(DiagnosisCommand),
+    Diagnoser(DiagnoserCommand),
+    Check((DiagnosisCommand, Vec<Vec<usize>>)),
+    Witness((Vec<ObsStep>, Option<EliminationOrder>, Option<usize>)),
+    Likely((Vec<ObsStep>, Option<EliminationOrder>, Option<usize>)),
+    Cheapest((Vec<ObsStep>, Option<EliminationOrder>, Option<usize>)),
 }
 
 #[derive(Debug)]
 pub enum DiagnosisCommand {
-    Fresh(Vec<usize>),
-    Load(usize),
+    Fresh((Vec<ObsStep>, Option<EliminationOrder>)),
+    Load((usize, Option<EliminationOrder>)),
+}
+
+#[derive(Debug)]
+pub enum DiagnoserCommand {
+    Build((Vec<usize>, Option<usize>)),
+    Load((usize, Vec<usize>)),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObsStep {
+    Label(usize),
+    Set(Vec<usize>),
+    Any,
+    Group(Vec<Vec<ObsStep>>),
+    Star(Box<ObsStep>),
+    Plus(Box<ObsStep>),
 }
@@ -32,6 +32,14 @@ impl<'a> GraphBuilder<'a> {
         self
     }
 
+    /**
+     * A graph can be built only
+     * when the begin node is known
+     */
+    pub fn has_begin(&self) -> bool {
+        self.node_indexs.contains_key(self.begin)
+    }
+
     pub fn build_graph(self) -> Graph<'a> {
         let root = self.node_indexs[self.begin];
         let adjacent_list = self.make_adjacent_list();
@@ -45,17 +53,22 @@ impl<'a> GraphBuilder<'a> {
     fn make_adjacent_list(&self) -> Vec<Vec<usize>> {
         let mut output: Vec<Vec<usize>> = (0..self.nodes.len()).map(|_| vec![]).collect();
         for edge in &self.edges {
-            let (from, to) = self.get_indexes(edge);
-            output[from].push(to);
+            if let Some((from, to)) = self.get_indexes(edge) {
+                output[from].push(to);
+            }
         }
         output
     }
 
-    fn get_indexes(&self, edge: &(&'a str, &'a str)) -> (usize, usize) {
+    /*
+        Edges with undeclared nodes are
+        reported by the name table: skip them
+    */
+    fn get_indexes(&self, edge: &(&'a str, &'a str)) -> Option<(usize, usize)> {
         let (from, to) = edge;
-        let from = self.node_indexs[from];
-        let to = self.node_indexs[to];
-        (from, to)
+        let from = self.node_indexs.get(from)?;
+        let to = self.node_indexs.get(to)?;
+        Some((*from, *to))
     }
 }
 
@@ -66,7 +79,7 @@ pub struct Graph<'a> {
 }
 
 impl<'a> Graph<'a> {
-    pub fn breadth_first_search(mut self) -> Result<(), super::GraphError<'a>> {
+    pub fn breadth_first_search(mut self) -> Result<(), Vec<&'a str>> {
         let mut queue = VecDeque::new();
         self.nodes[self.root].status = NodeStatus::Discovered;
         let mut missing_nodes = self.nodes.len() - 1; // root is already seen
@@ -1,48 +1,90 @@
-use super::GraphBuilder;
+use super::super::compiler_utils::is_network;
+use super::{GraphBuilder, GraphError, Loc};
+use ahash::AHashMap;
 use fsa_net_parser::syntax_tree::*;
 use fsa_net_parser::Code;
 
-type GraphResult<'a> = Result<(), super::GraphError<'a>>;
-pub fn check_connection<'a>(code: &Code<'a>) -> GraphResult<'a> {
-    code.iter().try_fold((), |_, curr| match curr {
-        Block::Network(net) => check_network(net),
-        Block::Request(_) => Ok(()),
-    })
-}
-
-fn check_network<'a>(network: &Network<'a>) -> GraphResult<'a> {
-    network
-        .params
+/**
+ * Check that every state is reachable in
+ * every automata. All the errors are collected.
+ */
+pub fn check_connection<'a>(code: &Code<'a>) -> Result<(), Vec<GraphError<'a>>> {
+    let errors: Vec<GraphError<'a>> = code
         .iter()
-        .try_fold((), |_, param| check_parameters(param))
+        .filter_map(is_network)
+        .flat_map(|net| net.params.iter())
+        .filter_map(check_parameters)
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
 }
 
-fn check_parameters<'a>(param: &NetworkParameterDecl<'a>) -> GraphResult<'a> {
+fn check_parameters<'a>(param: &NetworkParameterDecl<'a>) -> Option<GraphError<'a>> {
     match &param.param {
         NetworkParameter::Automata(automata) => check_automata(automata),
-        _ => Ok(()),
+        _ => None,
     }
 }
 
-fn check_automata<'a>(automata: &Automata<'a>) -> GraphResult<'a> {
+/*
+    Automata without a begin state and
+    duplicated states are reported by
+    the name table: skip them
+*/
+fn check_automata<'a>(automata: &Automata<'a>) -> Option<GraphError<'a>> {
+    let mut state_locs = AHashMap::new();
     let graph_builder = automata
         .params
         .iter()
-        .fold(GraphBuilder::new(), chain_graph_builder);
-    graph_builder.build_graph().breadth_first_search()
+        .fold(GraphBuilder::new(), |builder, param| {
+            chain_graph_builder(builder, param, &mut state_locs)
+        });
+    if !graph_builder.has_begin() {
+        return None;
+    }
+
+    let states = graph_builder.build_graph().breadth_first_search().err()?;
+    let states = states
+        .into_iter()
+        .map(|name| (name, state_locs[name]))
+        .collect();
+    Some(GraphError {
+        automata: automata.name,
+        loc: automata.get_location(),
+        states,
+    })
 }
 
 fn chain_graph_builder<'a>(
     builder: GraphBuilder<'a>,
     param: &AutomataParameterDecl<'a>,
+    state_locs: &mut AHashMap<&'a str, Loc>,
 ) -> GraphBuilder<'a> {
     match &param.param {
-        AutomataParameter::StateDecl(state) => add_node(builder, state),
+        AutomataParameter::StateDecl(state) => {
+            add_node(builder, state, param.get_location(), state_locs)
+        }
         AutomataParameter::Transition(trans) => builder.add_link(trans.source, trans.destination),
+        AutomataParameter::Final(_) => builder,
     }
 }
 
-fn add_node<'a>(builder: GraphBuilder<'a>, state: &StateDeclaration<'a>) -> GraphBuilder<'a> {
+fn add_node<'a>(
+    builder: GraphBuilder<'a>,
+    state: &StateDeclaration<'a>,
+    loc: Loc,
+    state_locs: &mut AHashMap<&'a str, Loc>,
+) -> GraphBuilder<'a> {
+    let name = match state {
+        StateDeclaration::Begin(name) | StateDeclaration::State(name) => *name,
+    };
+    if state_locs.contains_key(name) {
+        return builder;
+    }
+    state_locs.insert(name, loc);
     match state {
         StateDeclaration::Begin(name) => builder.add_node(name).set_begin(name),
         StateDeclaration::State(name) => builder.add_node(name),
@@ -1,6 +1,18 @@
 mod adjacent_list_graph;
 mod check_connection;
-pub type GraphError<'a> = Vec<&'a str>;
+
+type Loc = (usize, usize);
+
+/**
+ * States of `automata` not reachable
+ * from the begin state
+ */
+#[derive(Debug, PartialEq)]
+pub struct GraphError<'a> {
+    pub automata: &'a str,
+    pub loc: Loc,
+    pub states: Vec<(&'a str, Loc)>,
+}
 
 pub use adjacent_list_graph::GraphBuilder;
 pub use check_connection::check_connection;
@@ -3,14 +3,30 @@ use super::error;
 use super::link_connection;
 use super::name_table;
 use super::net_compiler;
+use super::template_expansion;
 use super::CompileResult;
 
 use fsa_net_parser::Code;
 
-pub fn compile<'a>(code: &'a Code<'a>) -> Result<CompileResult, error::CompileError<'a>> {
-    let table = name_table::build_name_table(code)?;
-    automata_connection::check_connection(code)?;
-    link_connection::link_check(code)?;
+/**
+ * Expand templates and run every semantic check before
+ * compiling: the errors found by each check are collected together
+ */
+pub fn compile<'a>(code: &Code<'a>) -> Result<CompileResult<'a>, Vec<error::CompileError<'a>>> {
+    let mut errors = vec![];
+    let code = match collect_errors(template_expansion::expand_templates(code), &mut errors) {
+        Some(code) => code,
+        None => return Err(errors),
+    };
+    let code = &code;
+    let table = collect_errors(name_table::build_name_table(code), &mut errors);
+    collect_errors(automata_connection::check_connection(code), &mut errors);
+    collect_errors(link_connection::link_check(code), &mut errors);
+
+    let table = match table {
+        Some(table) if errors.is_empty() => table,
+        _ => return Err(errors),
+    };
     let comp_res = net_compiler::compile_networks(code, &table);
 
     let output = CompileResult {
@@ -21,6 +37,22 @@ pub fn compile<'a>(code: &'a Code<'a>) -> Result<CompileResult, error::CompileEr
     Ok(output)
 }
 
+fn collect_errors<'a, T, E>(
+    res: Result<T, Vec<E>>,
+    errors: &mut Vec<error::CompileError<'a>>,
+) -> Option<T>
+where
+    E: Into<error::CompileError<'a>>,
+{
+    match res {
+        Ok(val) => Some(val),
+        Err(errs) => {
+            errors.extend(errs.into_iter().map(|e| e.into()));
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -44,8 +76,14 @@ mod test {
         let ast = parse(&code).expect("`unconnected-automata` should be syntactically correcy");
         let res = compile(&ast);
         let err = res.unwrap_err();
+        assert_eq!(err.len(), 1, "{:?}", err);
+        let err = err.into_iter().next().unwrap();
         match err {
-            error::CompileError::GraphError(err) => assert_eq!(err, vec!["a4"]),
+            error::CompileError::GraphError(err) => {
+                assert_eq!(err.automata, "A");
+                let states: Vec<&str> = err.states.iter().map(|(name, _)| *name).collect();
+                assert_eq!(states, vec!["a4"]);
+            }
             err => panic!("Expected GraphError, found: {:?}", err),
         }
     }
@@ -56,6 +94,8 @@ mod test {
         let ast = parse(&code).expect("`link_not_in_input.fnl` should be syntactically correcy");
         let res = compile(&ast);
         let err = res.unwrap_err();
+        assert_eq!(err.len(), 1, "{:?}", err);
+        let err = err.into_iter().next().unwrap();
         match err {
             error::CompileError::LinkError(err) => match err {
                 LinkError::NotInput(err) => {
@@ -73,6 +113,8 @@ mod test {
         let ast = parse(&code).expect("`multiple_link_usage.fnl` should be syntactically correcy");
         let res = compile(&ast);
         let err = res.unwrap_err();
+        assert_eq!(err.len(), 1, "{:?}", err);
+        let err = err.into_iter().next().unwrap();
         match err {
             error::CompileError::LinkError(err) => match err {
                 LinkError::MultipleLinkUse(err) => {
@@ -88,6 +130,24 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_link_init_not_consumed() {
+        let code = load_code_from_file("link-init-not-consumed");
+        let ast = parse(&code).expect("`link-init-not-consumed` should be syntactically correct");
+        let res = compile(&ast);
+        let err = res.unwrap_err();
+        assert_eq!(err.len(), 1, "{:?}", err);
+        let err = err.into_iter().next().unwrap();
+        match err {
+            error::CompileError::LinkError(LinkError::InitNotConsumed(err)) => {
+                assert_eq!(err.automata, "B");
+                assert_eq!(err.link, "L1");
+                assert_eq!(err.event, "e2");
+            }
+            err => panic!("Expected InitNotConsumed, found: {:?}", err),
+        }
+    }
+
     #[test]
     fn test_compile() {
         let src_code = load_code_from_file("simple-network");
@@ -96,6 +156,18 @@ mod test {
         assert_eq!(comp_res.compile_network.len(), 1);
     }
 
+    #[test]
+    fn test_compile_templates() {
+        let src_code = load_code_from_file("template-network");
+        let code = parse(&src_code).expect("`template-network` should be syntactically correct");
+        let comp_res = compile(&code).expect("`template-network` should be semantically correct");
+        assert_eq!(comp_res.compile_network.len(), 1);
+
+        let net_index = comp_res.index_table.get_network_table(0);
+        assert_eq!(net_index.get_automata_names(0).get_name(), "B1");
+        assert_eq!(net_index.get_automata_names(1).get_name(), "B2");
+    }
+
     #[test]
     fn test_index_table_build() {
         let src_code = load_code_from_file("simple-network");
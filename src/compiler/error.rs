@@ -1,12 +1,14 @@
-use super::automata_connection::GraphError;
-use super::link_connection::LinkError;
-use super::name_table::NameError;
+pub use super::automata_connection::GraphError;
+pub use super::link_connection::{LinkConnectionError, LinkCountError, LinkError, LinkInitError};
+pub use super::name_table::*;
+pub use super::template_expansion::*;
 
 #[derive(Debug)]
 pub enum CompileError<'a> {
     NameError(NameError<'a>),
     GraphError(GraphError<'a>),
     LinkError(LinkError<'a>),
+    TemplateError(TemplateError<'a>),
 }
 
 #[macro_export]
@@ -23,3 +25,4 @@ macro_rules! into_compile_error {
 into_compile_error! {NameError}
 into_compile_error! {GraphError}
 into_compile_error! {LinkError}
+into_compile_error! {TemplateError}
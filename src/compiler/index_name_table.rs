@@ -50,10 +50,18 @@ impl<'a> NetNames<'a> {
         &self.rel_names[index]
     }
 
+    pub fn rel_count(&self) -> usize {
+        self.rel_names.len()
+    }
+
     pub fn get_obs_name(&self, index: usize) -> &str {
         &self.obs_names[index]
     }
 
+    pub fn get_obs_index(&self, name: &str) -> Option<usize> {
+        self.obs_names.iter().position(|n| *n == name)
+    }
+
     pub fn get_ev_name(&self, index: usize) -> &str {
         &self.ev_names[index]
     }
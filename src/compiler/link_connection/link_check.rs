@@ -1,13 +1,26 @@
 use super::super::compiler_utils::is_network;
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use fsa_net_parser::syntax_tree::*;
 use fsa_net_parser::Code;
 
-pub fn link_check<'a>(code: &'a Code<'a>) -> Result<(), LinkError<'a>> {
-    code.iter()
+type Loc = (usize, usize);
+
+/**
+ * Check link usage in every network.
+ * All the errors are collected.
+ */
+pub fn link_check<'a>(code: &Code<'a>) -> Result<(), Vec<LinkError<'a>>> {
+    let errors: Vec<LinkError<'a>> = code
+        .iter()
         .filter_map(is_network)
         .map(CheckLinkFactory::new)
-        .try_fold((), |_, curr| validate_link(curr))
+        .flat_map(validate_link)
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
 }
 
 #[derive(Debug)]
@@ -15,15 +28,16 @@ pub enum LinkError<'a> {
     NotInput(LinkConnectionError<'a>),
     NotOutput(LinkConnectionError<'a>),
     MultipleLinkUse(Vec<LinkCountError<'a>>),
+    InitNotConsumed(LinkInitError<'a>),
 }
 
 impl<'a> LinkError<'a> {
-    fn new_not_input_error(automata: &'a str, link: &'a str) -> Self {
-        Self::NotInput(LinkConnectionError { automata, link })
+    fn new_not_input_error(trans: &LinkUsage<'a>, link: &LinkInfo<'a>) -> Self {
+        Self::NotInput(LinkConnectionError::new(trans, link))
     }
 
-    fn new_not_output_error(automata: &'a str, link: &'a str) -> Self {
-        Self::NotOutput(LinkConnectionError { automata, link })
+    fn new_not_output_error(trans: &LinkUsage<'a>, link: &LinkInfo<'a>) -> Self {
+        Self::NotOutput(LinkConnectionError::new(trans, link))
     }
 }
 
@@ -32,68 +46,142 @@ pub struct LinkCountError<'a> {
     pub automata: &'a str,
     pub link: &'a str,
     pub count: usize,
+    pub loc: Loc,
 }
 
 impl<'a> LinkCountError<'a> {
-    fn new(automata: &'a str, link: &'a str, count: usize) -> Self {
+    fn new(automata: &'a str, link: &'a str, count: usize, loc: Loc) -> Self {
         Self {
             automata,
             link,
             count,
+            loc,
+        }
+    }
+}
+
+/**
+ * The initial event of a link that no transition
+ * of its destination takes as input: loc is the
+ * location of the link declaration
+ */
+#[derive(Debug)]
+pub struct LinkInitError<'a> {
+    pub automata: &'a str,
+    pub link: &'a str,
+    pub event: &'a str,
+    pub loc: Loc,
+}
+
+impl<'a> LinkInitError<'a> {
+    fn new(link: &LinkInfo<'a>, event: &'a str) -> Self {
+        Self {
+            automata: link.dst,
+            link: link.name,
+            event,
+            loc: link.loc,
         }
     }
 }
 
+/**
+ * loc is the location of the transition
+ * using the link, link_loc the location
+ * of the link declaration
+ */
 #[derive(Debug)]
 pub struct LinkConnectionError<'a> {
     pub automata: &'a str,
     pub link: &'a str,
+    pub loc: Loc,
+    pub link_loc: Loc,
 }
 
-fn validate_link<'a>(factory: CheckLinkFactory<'a>) -> Result<(), LinkError<'a>> {
+impl<'a> LinkConnectionError<'a> {
+    fn new(trans: &LinkUsage<'a>, link: &LinkInfo<'a>) -> Self {
+        Self {
+            automata: trans.automata,
+            link: link.name,
+            loc: trans.loc,
+            link_loc: link.loc,
+        }
+    }
+}
+
+/*
+    Undefined links, automata and events are already
+    reported by the name table, so they are ignored here.
+*/
+fn validate_link<'a>(factory: CheckLinkFactory<'a>) -> Vec<LinkError<'a>> {
+    let mut errors = vec![];
     let mut usage_counter = LinkUsageCounter::default();
     for trans in &factory.links_use {
         usage_counter.count(trans);
-        let link = factory.links_def.get(trans.link).unwrap();
+        let link = if let Some(link) = factory.links_def.get(trans.link) {
+            link
+        } else {
+            continue;
+        };
         match trans.usage {
             LinkUsageType::Input => {
                 if link.dst != trans.automata {
-                    return Err(LinkError::new_not_input_error(trans.automata, link.name));
+                    errors.push(LinkError::new_not_input_error(trans, link));
                 }
             }
             LinkUsageType::Output => {
                 if link.src != trans.automata {
-                    return Err(LinkError::new_not_output_error(trans.automata, link.name));
+                    errors.push(LinkError::new_not_output_error(trans, link));
                 }
             }
         }
     }
 
     if let Some(multiple_use_err) = usage_counter.collect_error() {
-        Err(LinkError::MultipleLinkUse(multiple_use_err))
-    } else {
-        Ok(())
+        errors.push(LinkError::MultipleLinkUse(multiple_use_err));
     }
+    errors.extend(validate_init(&factory));
+    errors
+}
+
+fn validate_init<'a>(factory: &CheckLinkFactory<'a>) -> Vec<LinkError<'a>> {
+    let consumed: AHashSet<(&str, &str, &str)> = factory
+        .links_use
+        .iter()
+        .filter(|trans| matches!(trans.usage, LinkUsageType::Input))
+        .map(|trans| (trans.automata, trans.link, trans.event))
+        .collect();
+    factory
+        .links_def
+        .values()
+        .filter(|link| factory.automata.contains(link.dst))
+        .filter_map(|link| link.init.map(|init| (link, init)))
+        .filter(|(_, init)| factory.events.contains(init))
+        .filter(|(link, init)| !consumed.contains(&(link.dst, link.name, *init)))
+        .map(|(link, init)| LinkError::InitNotConsumed(LinkInitError::new(link, init)))
+        .collect()
 }
 
 #[derive(Default)]
 struct CheckLinkFactory<'a> {
     links_def: AHashMap<&'a str, LinkInfo<'a>>,
     links_use: Vec<LinkUsage<'a>>,
+    automata: AHashSet<&'a str>,
+    events: AHashSet<&'a str>,
 }
 
 impl<'a> CheckLinkFactory<'a> {
-    fn new(net: &'a Network<'a>) -> Self {
+    fn new(net: &Network<'a>) -> Self {
         let output = Self::default();
         net.params
             .iter()
             .fold(output, |acc, curr| acc.insert_network_param(curr))
     }
 
-    fn insert_network_param(self, param: &'a NetworkParameterDecl<'a>) -> Self {
+    fn insert_network_param(self, param: &NetworkParameterDecl<'a>) -> Self {
         match &param.param {
             NetworkParameter::Automata(auto) => self.insert_automata(auto),
             NetworkParameter::Link(link) => self.insert_link(link),
+            NetworkParameter::Events(events) => self.insert_events(events),
             _ => self,
         }
     }
@@ -104,23 +192,30 @@ impl<'a> CheckLinkFactory<'a> {
         self
     }
 
-    fn insert_automata(self, auto: &'a Automata<'a>) -> Self {
+    fn insert_events(mut self, events: &[&'a str]) -> Self {
+        self.events.extend(events);
+        self
+    }
+
+    fn insert_automata(mut self, auto: &Automata<'a>) -> Self {
+        self.automata.insert(auto.name);
         auto.params
             .iter()
             .filter_map(is_transaction)
-            .fold(self, |acc, curr| acc.insert_transition(curr, &auto.name))
+            .fold(self, |acc, curr| acc.insert_transition(curr, auto.name))
     }
 
     fn insert_transition(mut self, trans: &TransitionDeclaration<'a>, auto_name: &'a str) -> Self {
+        let loc = trans.get_location();
         if let Some(input) = &trans.input {
-            let info = LinkUsage::new(auto_name, input.link, trans.name, LinkUsageType::Input);
+            let info = LinkUsage::new(auto_name, input, trans.name, loc, LinkUsageType::Input);
             self.links_use.push(info);
         }
 
         if let Some(outputs) = &trans.output {
             for output in outputs {
                 let info =
-                    LinkUsage::new(auto_name, output.link, trans.name, LinkUsageType::Output);
+                    LinkUsage::new(auto_name, output, trans.name, loc, LinkUsageType::Output);
                 self.links_use.push(info);
             }
         }
@@ -129,11 +224,11 @@ impl<'a> CheckLinkFactory<'a> {
     }
 }
 
-fn is_transaction<'a, 'b: 'a>(
+fn is_transaction<'a, 'b>(
     param: &'b AutomataParameterDecl<'a>,
 ) -> Option<&'b TransitionDeclaration<'a>> {
     match &param.param {
-        AutomataParameter::StateDecl(_) => None,
+        AutomataParameter::StateDecl(_) | AutomataParameter::Final(_) => None,
         AutomataParameter::Transition(trans) => Some(trans),
     }
 }
@@ -141,21 +236,26 @@ fn is_transaction<'a, 'b: 'a>(
 struct LinkUsage<'a> {
     automata: &'a str,
     link: &'a str,
+    event: &'a str,
     trans: &'a str,
+    loc: Loc,
     usage: LinkUsageType,
 }
 
 impl<'a> LinkUsage<'a> {
     fn new(
         automata_name: &'a str,
-        link_name: &'a str,
+        event: &Event<'a>,
         trans: &'a str,
+        loc: Loc,
         usage: LinkUsageType,
     ) -> Self {
         Self {
             automata: automata_name,
-            link: link_name,
+            link: event.link,
+            event: event.name,
             trans,
+            loc,
             usage,
         }
     }
@@ -170,6 +270,8 @@ struct LinkInfo<'a> {
     name: &'a str,
     src: &'a str,
     dst: &'a str,
+    init: Option<&'a str>,
+    loc: Loc,
 }
 impl<'a> LinkInfo<'a> {
     fn new(lk: &Link<'a>) -> Self {
@@ -177,22 +279,24 @@ impl<'a> LinkInfo<'a> {
             name: lk.name,
             src: lk.source,
             dst: lk.destination,
+            init: lk.init,
+            loc: lk.get_location(),
         }
     }
 }
 
 #[derive(Default)]
 struct LinkUsageCounter<'a> {
-    counter: AHashMap<LinkUsageKey<'a>, usize>,
+    counter: AHashMap<LinkUsageKey<'a>, (usize, Loc)>,
 }
 
 impl<'a> LinkUsageCounter<'a> {
     fn count(&mut self, info: &LinkUsage<'a>) {
         let key = info.into();
-        if let Some(count) = self.counter.get_mut(&key) {
+        if let Some((count, _)) = self.counter.get_mut(&key) {
             *count += 1;
         } else {
-            self.counter.insert(key, 1);
+            self.counter.insert(key, (1, info.loc));
         }
     }
 
@@ -200,8 +304,8 @@ impl<'a> LinkUsageCounter<'a> {
         let count_err: Vec<LinkCountError<'a>> = self
             .counter
             .into_iter()
-            .filter(|(_, v)| *v > 1)
-            .map(|(k, v)| LinkCountError::new(k.automata, k.link, v))
+            .filter(|(_, (v, _))| *v > 1)
+            .map(|(k, (v, loc))| LinkCountError::new(k.automata, k.link, v, loc))
             .collect();
         if count_err.len() > 0 {
             Some(count_err)
@@ -1,4 +1,4 @@
 mod link_check;
 
 pub use link_check::link_check;
-pub use link_check::LinkError;
+pub use link_check::{LinkConnectionError, LinkCountError, LinkError, LinkInitError};
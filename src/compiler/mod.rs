@@ -1,11 +1,12 @@
 mod automata_connection;
 mod compiler;
 mod compiler_utils;
-mod error;
+pub mod error;
 mod index_name_table;
 mod link_connection;
 mod name_table;
 mod net_compiler;
+mod template_expansion;
 
 use super::command;
 use super::network;
@@ -14,3 +14,20 @@ pub enum NameClass {
     State,
     Transition,
 }
+
+impl std::fmt::Display for NameClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Network => "network",
+            Self::Request => "request",
+            Self::Automata => "automata",
+            Self::Link => "link",
+            Self::Event => "event",
+            Self::ObsLabel => "observation label",
+            Self::RelLabel => "relevance label",
+            Self::State => "state",
+            Self::Transition => "transition",
+        };
+        write!(f, "{}", name)
+    }
+}
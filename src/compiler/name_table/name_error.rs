@@ -31,6 +31,7 @@ into_name_error! {MismatchedType}
 pub struct UndefinedLabel<'a> {
     pub name: &'a str,
     pub class: NameClass,
+    pub loc: Loc,
 }
 
 #[derive(Debug)]
@@ -38,6 +39,7 @@ pub struct MismatchedType<'a> {
     pub name: &'a str,
     pub orig: NameClass,
     pub curr: NameClass,
+    pub loc: Loc,
 }
 
 #[derive(Debug)]
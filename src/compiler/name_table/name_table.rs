@@ -35,6 +35,12 @@ pub struct GlobalNameTable<'a> {
     net_index: usize,
 }
 
+impl<'a> Default for GlobalNameTable<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /*
     declare_ methods are used when a name is
     declare, i.e. automata declaration
@@ -60,12 +66,13 @@ impl<'a> GlobalNameTable<'a> {
         }
     }
 
-    pub fn declare_network(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn declare_network(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         self.check_name(name, loc, NameClass::Network, &NameStatus::Defined)?;
-        Ok(self.insert_new_network(name, loc, NameStatus::Defined))
+        self.insert_new_network(name, loc, NameStatus::Defined);
+        Ok(())
     }
 
-    pub fn declare_automata(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn declare_automata(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         if let CollectionStatus::Network(net_name) = self.status {
             self.insert_new_automata(name, net_name, loc, NameStatus::Defined)
         } else {
@@ -78,102 +85,99 @@ impl<'a> GlobalNameTable<'a> {
         a convenience wrapper around the actual
         name insertion.
     */
-    pub fn declare_link(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
-        let output = self.insert_network_name(name, NetworkName::Link, loc, NameStatus::Defined)?;
-        Ok(output.set_network_index(name))
+    pub fn declare_link(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
+        self.insert_network_name(name, NetworkName::Link, loc, NameStatus::Defined)?;
+        self.set_network_index(name);
+        Ok(())
     }
 
-    pub fn declare_rel_label(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
-        let output =
-            self.insert_network_name(name, NetworkName::RelLabel, loc, NameStatus::Defined)?;
-        Ok(output.set_network_index(name))
+    pub fn declare_rel_label(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
+        self.insert_network_name(name, NetworkName::RelLabel, loc, NameStatus::Defined)?;
+        self.set_network_index(name);
+        Ok(())
     }
 
-    pub fn declare_obs_label(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
-        let output =
-            self.insert_network_name(name, NetworkName::ObsLabel, loc, NameStatus::Defined)?;
-        Ok(output.set_network_index(name))
+    pub fn declare_obs_label(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
+        self.insert_network_name(name, NetworkName::ObsLabel, loc, NameStatus::Defined)?;
+        self.set_network_index(name);
+        Ok(())
     }
 
-    pub fn declare_event(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
-        let output =
-            self.insert_network_name(name, NetworkName::Event, loc, NameStatus::Defined)?;
-        Ok(output.set_network_index(name))
+    pub fn declare_event(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
+        self.insert_network_name(name, NetworkName::Event, loc, NameStatus::Defined)?;
+        self.set_network_index(name);
+        Ok(())
     }
 
-    fn set_network_index(mut self, name: &'a str) -> Self {
+    fn set_network_index(&mut self, name: &'a str) {
         if let CollectionStatus::Network(net_name) = self.status {
             let net_table = self.networks.get_mut(net_name).unwrap();
-            let mut item = net_table.names.get_mut(name).unwrap();
+            let item = net_table.names.get_mut(name).unwrap();
             let cls = item.class;
             let index = net_table.counter.get_count(cls);
             item.index = index;
-            self
         } else {
             panic!("cannot call `add_automata` outside netword")
         }
     }
 
-    pub fn declare_begin(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
-        let output =
-            self.insert_automata_name(name, loc, AutomataName::Begin, NameStatus::Defined)?;
-        Ok(output.set_automata_index(name))
+    pub fn declare_begin(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
+        self.insert_automata_name(name, loc, AutomataName::Begin, NameStatus::Defined)?;
+        self.set_automata_index(name);
+        Ok(())
     }
 
-    pub fn declare_state(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
-        let output =
-            self.insert_automata_name(name, loc, AutomataName::State, NameStatus::Defined)?;
-        Ok(output.set_automata_index(name))
+    pub fn declare_state(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
+        self.insert_automata_name(name, loc, AutomataName::State, NameStatus::Defined)?;
+        self.set_automata_index(name);
+        Ok(())
     }
 
-    pub fn declare_transition(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
-        let output =
-            self.insert_automata_name(name, loc, AutomataName::Transition, NameStatus::Defined)?;
-        Ok(output.set_automata_index(name))
+    pub fn declare_transition(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
+        self.insert_automata_name(name, loc, AutomataName::Transition, NameStatus::Defined)?;
+        self.set_automata_index(name);
+        Ok(())
     }
 
-    fn set_automata_index(mut self, name: &'a str) -> Self {
+    fn set_automata_index(&mut self, name: &'a str) {
         if let CollectionStatus::Automata { net, automata } = self.status {
             let net_table = self.networks.get_mut(net).unwrap();
             let auto_table = net_table.automata.get_mut(automata).unwrap();
             auto_table.set_index(name);
-            self
         } else {
             panic!("call `set_automata_index` outside automata block");
         }
     }
 
-    pub fn insert_request(mut self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn insert_request(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         if let Some(prev) = self.requests.get(name) {
             new_name_error! {name, NameClass::Request, NameClass::Request, prev.get_location(), loc}
         } else {
             self.requests.insert(name, RequestTable::new(loc));
             self.status = CollectionStatus::Request(name);
-            Ok(self)
+            Ok(())
         }
     }
 
-    pub fn add_request(mut self, req: Request<'a>) -> GlobalNameResult<'a> {
+    pub fn add_request(&mut self, req: Request<'a>) {
         if let CollectionStatus::Request(net_name) = self.status {
             let req_table = self.requests.get_mut(net_name).unwrap();
             req_table.add_request(req);
-            Ok(self)
         } else {
             panic!("call `add_request` in wrong status")
         }
     }
 
-    pub fn exit_request(mut self) -> Self {
+    pub fn exit_request(&mut self) {
         self.status = CollectionStatus::Global;
-        self
     }
 
-    pub fn add_automata(mut self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn add_automata(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         if let CollectionStatus::Network(net_name) = self.status {
             let net_table = self.networks.get_mut(net_name).unwrap();
             if let Some(_) = net_table.automata.get(name) {
                 net_table.names.remove(name);
-                Ok(self)
+                Ok(())
             } else {
                 self.check_name(name, loc, NameClass::Automata, &NameStatus::Undefined)?;
                 self.insert_network_name(
@@ -192,42 +196,51 @@ impl<'a> GlobalNameTable<'a> {
         Most of the add_ method are gust convenience
         wrapper around the actual name insertion method
     */
-    pub fn add_link(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn add_link(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         self.insert_network_name(name, NetworkName::Link, loc, NameStatus::Undefined)
     }
 
-    pub fn add_rel_label(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn add_rel_label(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         self.insert_network_name(name, NetworkName::RelLabel, loc, NameStatus::Undefined)
     }
 
-    pub fn add_obs_label(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn add_obs_label(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         self.insert_network_name(name, NetworkName::ObsLabel, loc, NameStatus::Undefined)
     }
 
-    pub fn add_event(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn add_event(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         self.insert_network_name(name, NetworkName::Event, loc, NameStatus::Undefined)
     }
 
-    pub fn add_state(self, name: &'a str, loc: Loc) -> GlobalNameResult<'a> {
+    pub fn add_state(&mut self, name: &'a str, loc: Loc) -> NameResult<'a> {
         self.insert_automata_name(name, loc, AutomataName::State, NameStatus::Undefined)
     }
 
-    pub fn exit_automata(mut self) -> Self {
+    pub fn exit_automata(&mut self) {
         if let CollectionStatus::Automata { net, automata: _ } = self.status {
             self.status = CollectionStatus::Network(net);
-            self
         } else {
             panic!()
         }
     }
 
-    pub fn exit_network(mut self) -> Self {
+    pub fn exit_network(&mut self) {
         self.status = CollectionStatus::Global;
-        self
     }
 
-    pub fn validate(self) -> GlobalNameResult<'a> {
-        self.validate_network()?.validate_requests()
+    /*
+        Validation does not stop at the first
+        error: all the errors are collected and
+        returned together.
+    */
+    pub fn validate(&self) -> Result<(), Vec<NameError<'a>>> {
+        let mut errors = self.validate_network();
+        errors.append(&mut self.validate_requests());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 
     pub fn get_network_name_index(&self, net_name: &str, item_name: &str) -> usize {
@@ -274,22 +287,23 @@ impl<'a> GlobalNameTable<'a> {
         factory.build()
     }
 
-    fn validate_network(self) -> GlobalNameResult<'a> {
+    fn validate_network(&self) -> Vec<NameError<'a>> {
+        let mut errors = Vec::new();
         for (net_name, table) in self.networks.iter() {
-            table.validate(net_name)?;
+            table.validate(net_name, &mut errors);
         }
-
-        Ok(self)
+        errors
     }
 
-    fn validate_requests(self) -> GlobalNameResult<'a> {
+    fn validate_requests(&self) -> Vec<NameError<'a>> {
+        let mut errors = Vec::new();
         let names = self.get_undefined_network_names();
         if names.len() > 0 {
             let err = UndefinedNetwork { names };
-            Err(NameError::UndefinedNetwork(err))
-        } else {
-            self.validate_request_labels()
+            errors.push(NameError::UndefinedNetwork(err));
         }
+        self.validate_request_labels(&mut errors);
+        errors
     }
 
     fn get_undefined_network_names(&self) -> Vec<(&'a str, Loc)> {
@@ -300,22 +314,44 @@ impl<'a> GlobalNameTable<'a> {
             .collect()
     }
 
-    fn validate_request_labels(self) -> GlobalNameResult<'a> {
+    fn validate_request_labels(&self, errors: &mut Vec<NameError<'a>>) {
         for (net_name, req) in self.requests.iter() {
-            let net_table = self.networks.get(net_name).unwrap();
-            validate_labels(net_table, req.get_linspace_labels(), NameClass::ObsLabel)?;
-            validate_labels(net_table, req.get_diagnosis_labels(), NameClass::ObsLabel)?;
+            if let Some(net_table) = self.networks.get(net_name) {
+                validate_labels(
+                    net_table,
+                    req.get_linspace_labels(),
+                    NameClass::ObsLabel,
+                    errors,
+                );
+                validate_labels(
+                    net_table,
+                    req.get_diagnosis_labels(),
+                    NameClass::ObsLabel,
+                    errors,
+                );
+                validate_labels(
+                    net_table,
+                    req.get_diagnoser_labels(),
+                    NameClass::ObsLabel,
+                    errors,
+                );
+                validate_labels(
+                    net_table,
+                    req.get_check_labels(),
+                    NameClass::RelLabel,
+                    errors,
+                );
+            }
         }
-        Ok(self)
     }
 
     fn insert_automata_name(
-        mut self,
+        &mut self,
         name: &'a str,
         loc: Loc,
         automata_cls: AutomataName,
         stat: NameStatus,
-    ) -> GlobalNameResult<'a> {
+    ) -> NameResult<'a> {
         if let CollectionStatus::Automata { net, automata } = self.status {
             let res = self.check_name(name, loc, &automata_cls, &stat)?;
             let net_table = self.networks.get_mut(net).unwrap();
@@ -323,27 +359,26 @@ impl<'a> GlobalNameTable<'a> {
             let (stat, class) = next_stat_and_class(res, stat, automata_cls);
 
             automata_table.insert_name(name, loc, class, stat);
-            Ok(self)
+            Ok(())
         } else {
             panic!("call insert_automata_name outside automata")
         }
     }
 
-    fn insert_new_network(mut self, name: &'a str, loc: Loc, stat: NameStatus) -> Self {
+    fn insert_new_network(&mut self, name: &'a str, loc: Loc, stat: NameStatus) {
         self.status = CollectionStatus::Network(name);
         self.networks
             .insert(name, NetworkNameTable::new(loc, stat, self.net_index));
         self.net_index += 1;
-        self
     }
 
     fn insert_new_automata(
-        mut self,
+        &mut self,
         automata_name: &'a str,
         net_name: &'a str,
         loc: Loc,
         stat: NameStatus,
-    ) -> GlobalNameResult<'a> {
+    ) -> NameResult<'a> {
         self.check_name(automata_name, loc, NameClass::Automata, &stat)?;
         let net_table = self.networks.get_mut(net_name).unwrap();
         if let Some(_) = net_table.names.get(automata_name) {
@@ -357,16 +392,16 @@ impl<'a> GlobalNameTable<'a> {
             net: net_name,
             automata: automata_name,
         };
-        Ok(self)
+        Ok(())
     }
 
     fn insert_network_name(
-        mut self,
+        &mut self,
         name: &'a str,
         class: NetworkName,
         loc: Loc,
         stat: NameStatus,
-    ) -> GlobalNameResult<'a> {
+    ) -> NameResult<'a> {
         match &self.status {
             CollectionStatus::Automata { net, automata: _ } | CollectionStatus::Network(net) => {
                 let net_class: NameClass = (&class).into();
@@ -386,7 +421,7 @@ impl<'a> GlobalNameTable<'a> {
                     };
                     net_table.names.insert(name, info);
                 }
-                Ok(self)
+                Ok(())
             }
             _ => panic!("Call add_automata in state: {:?}", self.status),
         }
@@ -480,28 +515,32 @@ impl<'a> GlobalNameTable<'a> {
     }
 }
 
-fn validate_labels<'b, 'a: 'b>(
+fn validate_labels<'a>(
     table: &NetworkNameTable<'a>,
-    labels: impl Iterator<Item = &'b Vec<&'a str>>,
+    labels: impl Iterator<Item = (Loc, &'a str)>,
     class: NameClass,
-) -> Result<(), NameError<'a>> {
-    for lbls in labels {
-        for lbl in lbls {
-            if let Some(cls) = table.get_name_class(lbl) {
-                if cls != class {
-                    return Err(MismatchedType {
-                        name: lbl,
-                        orig: cls,
-                        curr: class,
-                    })?;
-                }
-            } else {
-                return Err(UndefinedLabel { name: lbl, class })?;
+    errors: &mut Vec<NameError<'a>>,
+) {
+    for (loc, lbl) in labels {
+        if let Some(cls) = table.get_name_class(lbl) {
+            if cls != class {
+                let err = MismatchedType {
+                    name: lbl,
+                    orig: cls,
+                    curr: class,
+                    loc,
+                };
+                errors.push(err.into());
             }
+        } else {
+            let err = UndefinedLabel {
+                name: lbl,
+                class,
+                loc,
+            };
+            errors.push(err.into());
         }
     }
-
-    Ok(())
 }
 
 fn next_stat_and_class(
@@ -533,7 +572,7 @@ impl CheckNameResult {
     }
 }
 
-pub type GlobalNameResult<'a> = Result<GlobalNameTable<'a>, NameError<'a>>;
+pub type NameResult<'a> = Result<(), NameError<'a>>;
 
 #[derive(Debug)]
 enum CollectionStatus<'a> {
@@ -688,16 +727,18 @@ impl<'a> NetworkNameTable<'a> {
         }
     }
 
-    fn validate(&self, net_name: &'a str) -> Result<(), NameError<'a>> {
-        self.stat.validate(net_name, self.loc)?;
+    fn validate(&self, net_name: &'a str, errors: &mut Vec<NameError<'a>>) {
+        if let Err(err) = self.stat.validate(net_name, self.loc) {
+            errors.push(err.into());
+        }
         for (name, item) in self.names.iter() {
-            item.validate(name)?;
+            if let Err(err) = item.validate(name) {
+                errors.push(err.into());
+            }
         }
         for automata in self.automata.values() {
-            automata.validate()?;
+            automata.validate(errors);
         }
-
-        Ok(())
     }
 }
 
@@ -826,17 +867,19 @@ impl<'a> AutomataNameTable<'a> {
         }
     }
 
-    fn validate(&self) -> Result<(), NameError<'a>> {
-        self.validate_definitions()?;
-        self.validate_begin_state()?;
-        Ok(())
+    fn validate(&self, errors: &mut Vec<NameError<'a>>) {
+        self.validate_definitions(errors);
+        if let Err(err) = self.validate_begin_state() {
+            errors.push(err.into());
+        }
     }
 
-    fn validate_definitions(&self) -> Result<(), UndefinedNameError<'a>> {
+    fn validate_definitions(&self, errors: &mut Vec<NameError<'a>>) {
         for (name, item) in self.names.iter() {
-            item.stat.validate(name, item.loc)?
+            if let Err(err) = item.stat.validate(name, item.loc) {
+                errors.push(err.into());
+            }
         }
-        Ok(())
     }
 
     fn validate_begin_state(&self) -> Result<(), BeginStateError<'a>> {
@@ -997,16 +1040,16 @@ mod test {
 
     #[test]
     fn test_correct_name() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("netname", (0, 1)).unwrap();
-        let name_table = name_table.declare_event("ev", (4, 5)).unwrap();
-        let name_table = name_table.declare_link("lk", (6, 7)).unwrap();
-        let name_table = name_table.declare_automata("at", (10, 12)).unwrap();
-        let name_table = name_table.declare_begin("s1", (13, 15)).unwrap();
-        let name_table = name_table.declare_state("s2", (45, 35)).unwrap();
-        let name_table = name_table.exit_automata();
-        let name_table = name_table.exit_network();
-        let name_table = name_table.insert_request("netname", (45, 123)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("netname", (0, 1)).unwrap();
+        name_table.declare_event("ev", (4, 5)).unwrap();
+        name_table.declare_link("lk", (6, 7)).unwrap();
+        name_table.declare_automata("at", (10, 12)).unwrap();
+        name_table.declare_begin("s1", (13, 15)).unwrap();
+        name_table.declare_state("s2", (45, 35)).unwrap();
+        name_table.exit_automata();
+        name_table.exit_network();
+        name_table.insert_request("netname", (45, 123)).unwrap();
 
         name_table
             .validate()
@@ -1015,11 +1058,13 @@ mod test {
 
     #[test]
     fn test_missing_network() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.insert_request("net", (0, 1)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.insert_request("net", (0, 1)).unwrap();
         let err = name_table
             .validate()
             .expect_err("`net` is not a defined network");
+        assert_eq!(err.len(), 1);
+        let err = err.into_iter().next().unwrap();
         match err {
             NameError::UndefinedNetwork(err) => {
                 assert_eq!(err.names, vec![("net", (0, 1))])
@@ -1030,12 +1075,12 @@ mod test {
 
     #[test]
     fn test_ridefined_name() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("netname", (0, 1)).unwrap();
-        let name_table = name_table.declare_event("ev", (4, 5)).unwrap();
-        let name_table = name_table.declare_link("lk", (6, 7)).unwrap();
-        let name_table = name_table.declare_automata("at", (10, 12)).unwrap();
-        let name_table = name_table.exit_automata();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("netname", (0, 1)).unwrap();
+        name_table.declare_event("ev", (4, 5)).unwrap();
+        name_table.declare_link("lk", (6, 7)).unwrap();
+        name_table.declare_automata("at", (10, 12)).unwrap();
+        name_table.exit_automata();
         let err = name_table
             .declare_link("at", (15, 18))
             .expect_err("`at` is defined twice");
@@ -1051,20 +1096,22 @@ mod test {
 
     #[test]
     fn test_multiple_begin_state() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("netname", (0, 1)).unwrap();
-        let name_table = name_table.declare_event("ev", (4, 5)).unwrap();
-        let name_table = name_table.declare_link("lk", (6, 7)).unwrap();
-        let name_table = name_table.declare_automata("at", (10, 12)).unwrap();
-        let name_table = name_table.declare_begin("s0", (45, 12)).unwrap();
-        let name_table = name_table.declare_begin("s1", (56, 142)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("netname", (0, 1)).unwrap();
+        name_table.declare_event("ev", (4, 5)).unwrap();
+        name_table.declare_link("lk", (6, 7)).unwrap();
+        name_table.declare_automata("at", (10, 12)).unwrap();
+        name_table.declare_begin("s0", (45, 12)).unwrap();
+        name_table.declare_begin("s1", (56, 142)).unwrap();
 
-        let name_table = name_table.exit_automata();
-        let name_table = name_table.exit_network();
+        name_table.exit_automata();
+        name_table.exit_network();
 
         let err = name_table
             .validate()
             .expect_err("There are two begin states");
+        assert_eq!(err.len(), 1);
+        let err = err.into_iter().next().unwrap();
 
         match err {
             NameError::BeginStateError(err) => match err.class {
@@ -1081,18 +1128,20 @@ mod test {
 
     #[test]
     fn test_no_begin_state() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("netname", (0, 1)).unwrap();
-        let name_table = name_table.declare_event("ev", (4, 5)).unwrap();
-        let name_table = name_table.declare_link("lk", (6, 7)).unwrap();
-        let name_table = name_table.declare_automata("at", (10, 12)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("netname", (0, 1)).unwrap();
+        name_table.declare_event("ev", (4, 5)).unwrap();
+        name_table.declare_link("lk", (6, 7)).unwrap();
+        name_table.declare_automata("at", (10, 12)).unwrap();
 
-        let name_table = name_table.exit_automata();
-        let name_table = name_table.exit_network();
+        name_table.exit_automata();
+        name_table.exit_network();
 
         let err = name_table
             .validate()
             .expect_err("There aren't begin states");
+        assert_eq!(err.len(), 1);
+        let err = err.into_iter().next().unwrap();
 
         match err {
             NameError::BeginStateError(err) => match err.class {
@@ -1105,13 +1154,13 @@ mod test {
 
     #[test]
     fn test_state_ridefinition() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("netname", (0, 1)).unwrap();
-        let name_table = name_table.declare_event("ev", (4, 5)).unwrap();
-        let name_table = name_table.declare_link("lk", (6, 7)).unwrap();
-        let name_table = name_table.declare_automata("at", (10, 12)).unwrap();
-        let name_table = name_table.declare_begin("s0", (45, 12)).unwrap();
-        let name_table = name_table.declare_state("s1", (56, 142)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("netname", (0, 1)).unwrap();
+        name_table.declare_event("ev", (4, 5)).unwrap();
+        name_table.declare_link("lk", (6, 7)).unwrap();
+        name_table.declare_automata("at", (10, 12)).unwrap();
+        name_table.declare_begin("s0", (45, 12)).unwrap();
+        name_table.declare_state("s1", (56, 142)).unwrap();
 
         let err = name_table
             .declare_state("s1", (67, 132))
@@ -1129,20 +1178,26 @@ mod test {
 
     #[test]
     fn test_undefined_automata() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("net", (0, 1)).unwrap();
-        let name_table = name_table.declare_link("L1", (0, 1)).unwrap();
-        let name_table = name_table.add_automata("A", (10, 10)).unwrap();
-        let name_table = name_table.add_automata("B", (10, 10)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("net", (0, 1)).unwrap();
+        name_table.declare_link("L1", (0, 1)).unwrap();
+        name_table.add_automata("A", (10, 10)).unwrap();
+        name_table.add_automata("B", (10, 10)).unwrap();
 
-        let name_table = name_table.declare_automata("A", (14, 15)).unwrap();
+        name_table.declare_automata("A", (14, 15)).unwrap();
 
-        let name_table = name_table.exit_automata();
-        let name_table = name_table.exit_network();
+        name_table.exit_automata();
+        name_table.exit_network();
 
         let err = name_table
             .validate()
             .expect_err("Automata `b` is not defined");
+        // automata `A` has no begin state
+        assert_eq!(err.len(), 2);
+        let err = err
+            .into_iter()
+            .find(|e| matches!(e, NameError::UndefinedNameError(_)))
+            .unwrap();
         match err {
             NameError::UndefinedNameError(err) => {
                 assert_eq!(err.name, "B");
@@ -1153,10 +1208,10 @@ mod test {
 
     #[test]
     fn test_event_name_riusage() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("net", (0, 1)).unwrap();
-        let name_table = name_table.declare_link("L1", (0, 1)).unwrap();
-        let name_table = name_table.add_event("A", (67, 23)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("net", (0, 1)).unwrap();
+        name_table.declare_link("L1", (0, 1)).unwrap();
+        name_table.add_event("A", (67, 23)).unwrap();
         let err = name_table
             .add_automata("A", (10, 10))
             .expect_err("Name `A` is found as Event");
@@ -1172,10 +1227,10 @@ mod test {
 
     #[test]
     fn test_event_name_ridefinition() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("net", (0, 1)).unwrap();
-        let name_table = name_table.declare_link("L1", (0, 1)).unwrap();
-        let name_table = name_table.add_event("A", (67, 23)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("net", (0, 1)).unwrap();
+        name_table.declare_link("L1", (0, 1)).unwrap();
+        name_table.add_event("A", (67, 23)).unwrap();
         let err = name_table
             .declare_automata("A", (10, 10))
             .expect_err("Name `A` is found as Event");
@@ -1191,10 +1246,10 @@ mod test {
 
     #[test]
     fn test_automata_name_riusage() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("net", (0, 1)).unwrap();
-        let name_table = name_table.declare_link("L1", (0, 1)).unwrap();
-        let name_table = name_table.add_automata("A", (67, 12)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("net", (0, 1)).unwrap();
+        name_table.declare_link("L1", (0, 1)).unwrap();
+        name_table.add_automata("A", (67, 12)).unwrap();
         let err = name_table
             .add_event("A", (67, 23))
             .expect_err("Name `A` is found as Automata");
@@ -1211,10 +1266,10 @@ mod test {
 
     #[test]
     fn test_automata_name_ridefinition() {
-        let name_table = GlobalNameTable::new();
-        let name_table = name_table.declare_network("net", (0, 1)).unwrap();
-        let name_table = name_table.declare_link("L1", (0, 1)).unwrap();
-        let name_table = name_table.declare_automata("A", (67, 12)).unwrap();
+        let mut name_table = GlobalNameTable::new();
+        name_table.declare_network("net", (0, 1)).unwrap();
+        name_table.declare_link("L1", (0, 1)).unwrap();
+        name_table.declare_automata("A", (67, 12)).unwrap();
         let err = name_table
             .add_event("A", (67, 23))
             .expect_err("Name `A` is Declared as Automata");
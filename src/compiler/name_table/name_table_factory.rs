@@ -7,130 +7,196 @@ use fsa_net_parser::Code;
 
 /**
  * Build the name table.
- * This operation stops when the first name error is found
+ * This operation does not stop at the first name error:
+ * every error found is collected and returned.
+ * When a network, automata or request declaration
+ * is invalid its body is skipped.
  */
-pub fn build_name_table<'a>(code: &Code<'a>) -> Result<GlobalNameTable<'a>, NameError<'a>> {
-    let name_table = GlobalNameTable::new();
-    let name_table = code.iter().try_fold(name_table, |nt, curr| match curr {
-        Block::Network(net) => collect_network(nt, net),
-        Block::Request(req) => collect_request(nt, req),
-    })?;
-
-    name_table.validate()
-}
+pub fn build_name_table<'a>(code: &Code<'a>) -> Result<GlobalNameTable<'a>, Vec<NameError<'a>>> {
+    let mut name_table = GlobalNameTable::new();
+    let mut errors = Vec::new();
+    for block in code {
+        match block {
+            Block::Network(net) => collect_network(&mut name_table, &mut errors, net),
+            Block::Request(req) => collect_request(&mut name_table, &mut errors, req),
+            Block::Import(_) | Block::Template(_) => {}
+        }
+    }
 
-fn collect_request<'a>(nt: GlobalNameTable<'a>, req: &Request<'a>) -> GlobalNameResult<'a> {
-    let nt = nt.insert_request(req.name, req.get_location())?;
-    let nt = req.list.iter().try_fold(nt, collect_command)?;
-    Ok(nt.exit_request())
+    if let Err(mut err) = name_table.validate() {
+        errors.append(&mut err);
+    }
+
+    if errors.is_empty() {
+        Ok(name_table)
+    } else {
+        Err(errors)
+    }
 }
 
-fn collect_command<'a>(nt: GlobalNameTable<'a>, cmd: &CommandDecl<'a>) -> GlobalNameResult<'a> {
-    let req = convert_command(cmd);
-    nt.add_request(req)
+fn collect_request<'a>(
+    nt: &mut GlobalNameTable<'a>,
+    errors: &mut Vec<NameError<'a>>,
+    req: &Request<'a>,
+) {
+    if push_error(errors, nt.insert_request(req.name, req.get_location())) {
+        for cmd in &req.list {
+            nt.add_request(convert_command(cmd));
+        }
+        nt.exit_request();
+    }
 }
 
-fn collect_network<'a>(nt: GlobalNameTable<'a>, net: &Network<'a>) -> GlobalNameResult<'a> {
-    let nt = nt.declare_network(net.name, net.get_location())?;
-    let nt = net.params.iter().try_fold(nt, collect_net_param)?;
-    Ok(nt.exit_network())
+fn collect_network<'a>(
+    nt: &mut GlobalNameTable<'a>,
+    errors: &mut Vec<NameError<'a>>,
+    net: &Network<'a>,
+) {
+    if push_error(errors, nt.declare_network(net.name, net.get_location())) {
+        for param in &net.params {
+            collect_net_param(nt, errors, param);
+        }
+        nt.exit_network();
+    }
 }
 
 fn collect_net_param<'a>(
-    nt: GlobalNameTable<'a>,
+    nt: &mut GlobalNameTable<'a>,
+    errors: &mut Vec<NameError<'a>>,
     param: &NetworkParameterDecl<'a>,
-) -> GlobalNameResult<'a> {
+) {
     let loc = param.get_location();
     match &param.param {
-        NetworkParameter::Automata(automata) => collect_automata(nt, automata),
-        NetworkParameter::Events(events) => events
-            .iter()
-            .try_fold(nt, |nt, ev| nt.declare_event(ev, loc)),
-        NetworkParameter::ObserveLabels(labels) => labels
-            .iter()
-            .try_fold(nt, |nt, lbl| nt.declare_obs_label(lbl, loc)),
-        NetworkParameter::RelevanceLabels(labels) => labels
-            .iter()
-            .try_fold(nt, |nt, lbl| nt.declare_rel_label(lbl, loc)),
-        NetworkParameter::Link(link) => collect_link(nt, link),
+        NetworkParameter::Automata(automata) => collect_automata(nt, errors, automata),
+        NetworkParameter::Events(events) => {
+            for ev in events {
+                push_error(errors, nt.declare_event(ev, loc));
+            }
+        }
+        NetworkParameter::ObserveLabels(labels) => {
+            for lbl in labels {
+                push_error(errors, nt.declare_obs_label(lbl, loc));
+            }
+        }
+        NetworkParameter::RelevanceLabels(labels) => {
+            for lbl in labels {
+                push_error(errors, nt.declare_rel_label(lbl, loc));
+            }
+        }
+        NetworkParameter::Link(link) => collect_link(nt, errors, link),
+        NetworkParameter::Final(_) => {}
+        // already replaced by template expansion
+        NetworkParameter::Template(_) | NetworkParameter::Instance(_) => {}
     }
 }
 
-fn collect_link<'a>(nt: GlobalNameTable<'a>, link: &Link<'a>) -> GlobalNameResult<'a> {
+fn collect_link<'a>(
+    nt: &mut GlobalNameTable<'a>,
+    errors: &mut Vec<NameError<'a>>,
+    link: &Link<'a>,
+) {
     let loc = link.get_location();
-    let nt = nt.declare_link(link.name, loc)?;
-
-    let nt = nt.add_automata(link.source, loc)?;
-    nt.add_automata(link.destination, loc)
+    push_error(errors, nt.declare_link(link.name, loc));
+    push_error(errors, nt.add_automata(link.source, loc));
+    push_error(errors, nt.add_automata(link.destination, loc));
+    if let Some(init) = link.init {
+        push_error(errors, nt.add_event(init, loc));
+    }
 }
 
-fn collect_automata<'a>(nt: GlobalNameTable<'a>, automata: &Automata<'a>) -> GlobalNameResult<'a> {
-    let nt = nt.declare_automata(automata.name, automata.get_location())?;
-    let nt = automata
-        .params
-        .iter()
-        .try_fold(nt, collect_automata_param)?;
-    Ok(nt.exit_automata())
+fn collect_automata<'a>(
+    nt: &mut GlobalNameTable<'a>,
+    errors: &mut Vec<NameError<'a>>,
+    automata: &Automata<'a>,
+) {
+    if push_error(
+        errors,
+        nt.declare_automata(automata.name, automata.get_location()),
+    ) {
+        for param in &automata.params {
+            collect_automata_param(nt, errors, param);
+        }
+        nt.exit_automata();
+    }
 }
 
 fn collect_automata_param<'a>(
-    nt: GlobalNameTable<'a>,
+    nt: &mut GlobalNameTable<'a>,
+    errors: &mut Vec<NameError<'a>>,
     param: &AutomataParameterDecl<'a>,
-) -> GlobalNameResult<'a> {
+) {
     let loc = param.get_location();
-    match &param.param {
+    let res = match &param.param {
         AutomataParameter::StateDecl(state) => match state {
             StateDeclaration::Begin(state) => nt.declare_begin(state, loc),
             StateDeclaration::State(state) => nt.declare_state(state, loc),
         },
-        AutomataParameter::Transition(trans) => collect_transition(nt, trans),
-    }
+        AutomataParameter::Transition(trans) => {
+            collect_transition(nt, errors, trans);
+            Ok(())
+        }
+        AutomataParameter::Final(states) => {
+            for state in states {
+                push_error(errors, nt.add_state(state, loc));
+            }
+            Ok(())
+        }
+    };
+    push_error(errors, res);
 }
 
 fn collect_transition<'a>(
-    nt: GlobalNameTable<'a>,
+    nt: &mut GlobalNameTable<'a>,
+    errors: &mut Vec<NameError<'a>>,
     trans: &TransitionDeclaration<'a>,
-) -> GlobalNameResult<'a> {
+) {
     let loc = trans.get_location();
-    let nt = nt.declare_transition(trans.name, loc)?;
+    push_error(errors, nt.declare_transition(trans.name, loc));
 
-    let nt = nt.add_state(trans.source, loc)?;
-    let nt = nt.add_state(trans.destination, loc)?;
+    push_error(errors, nt.add_state(trans.source, loc));
+    push_error(errors, nt.add_state(trans.destination, loc));
 
-    let nt = if let Some(input_event) = &trans.input {
-        collect_event(nt, input_event, loc)?
-    } else {
-        nt
-    };
+    if let Some(input_event) = &trans.input {
+        collect_event(nt, errors, input_event, loc);
+    }
 
-    let nt = if let Some(obs_label) = &trans.obs_label {
-        nt.add_obs_label(obs_label, loc)?
-    } else {
-        nt
-    };
+    if let Some(obs_label) = &trans.obs_label {
+        push_error(errors, nt.add_obs_label(obs_label, loc));
+    }
 
-    let nt = if let Some(rel_label) = &trans.rel_label {
-        nt.add_rel_label(rel_label, loc)?
-    } else {
-        nt
-    };
+    if let Some(rel_label) = &trans.rel_label {
+        push_error(errors, nt.add_rel_label(rel_label, loc));
+    }
 
     if let Some(output_events) = &trans.output {
-        output_events
-            .iter()
-            .try_fold(nt, |nt, ev| collect_event(nt, ev, loc))
-    } else {
-        Ok(nt)
+        for ev in output_events {
+            collect_event(nt, errors, ev, loc);
+        }
     }
 }
 
 fn collect_event<'a>(
-    nt: GlobalNameTable<'a>,
+    nt: &mut GlobalNameTable<'a>,
+    errors: &mut Vec<NameError<'a>>,
     event: &Event<'a>,
     loc: (usize, usize),
-) -> GlobalNameResult<'a> {
-    let nt = nt.add_event(&event.name, loc)?;
-    nt.add_link(&event.link, loc)
+) {
+    push_error(errors, nt.add_event(&event.name, loc));
+    push_error(errors, nt.add_link(&event.link, loc));
+}
+
+/*
+    Store the error, if any, and tell
+    the caller whether the operation succeeded
+*/
+fn push_error<'a>(errors: &mut Vec<NameError<'a>>, res: NameResult<'a>) -> bool {
+    match res {
+        Ok(()) => true,
+        Err(err) => {
+            errors.push(err);
+            false
+        }
+    }
 }
 
 #[cfg(test)]
@@ -156,6 +222,10 @@ mod test {
 
         let err =
             build_name_table(&ast).expect_err("`duplicate-begin` should contain semantic errors");
+
+        assert_eq!(err.len(), 1, "{:?}", err);
+
+        let err = err.into_iter().next().unwrap();
         match err {
             NameError::BeginStateError(BeginStateError {
                 name,
@@ -181,6 +251,10 @@ mod test {
 
         let err =
             build_name_table(&ast).expect_err("`missing-begin` should contain semantic errors");
+
+        assert_eq!(err.len(), 1, "{:?}", err);
+
+        let err = err.into_iter().next().unwrap();
         match err {
             NameError::BeginStateError(BeginStateError {
                 name,
@@ -239,6 +313,10 @@ mod test {
 
         let err =
             build_name_table(&ast).expect_err("`undefined-network` contains `MissingNetwork`");
+
+        assert_eq!(err.len(), 1, "{:?}", err);
+
+        let err = err.into_iter().next().unwrap();
         match err {
             NameError::UndefinedNetwork(err) => {
                 assert_eq!(err.names.len(), 1);
@@ -255,6 +333,10 @@ mod test {
 
         let err =
             build_name_table(&ast).expect_err("`mistmatch-name-type` contains a mistmatch name s0");
+
+        assert_eq!(err.len(), 1, "{:?}", err);
+
+        let err = err.into_iter().next().unwrap();
         match err {
             NameError::MismatchedType(err) => {
                 assert_eq!(err.name, "s0");
@@ -283,6 +365,8 @@ mod test {
             file, name
         );
         let err = build_name_table(&ast).expect_err(&expect_msg);
+        assert_eq!(err.len(), 1, "{:?}", err);
+        let err = err.into_iter().next().unwrap();
         match err {
             NameError::UndefinedNameError(err) => {
                 assert_eq!(err.name, name);
@@ -298,6 +382,10 @@ mod test {
 
         let err = build_name_table(&ast).expect_err("rel label `r4` is not defined");
 
+        assert_eq!(err.len(), 1, "{:?}", err);
+
+        let err = err.into_iter().next().unwrap();
+
         match err {
             NameError::UndefinedLabel(err) => {
                 assert_eq!(err.name, "r4");
@@ -317,6 +405,10 @@ mod test {
             file, name
         );
         let err = build_name_table(&ast).expect_err(&expect_msg);
+        let err = err
+            .into_iter()
+            .find(|e| matches!(e, NameError::NameRidefinitionError(_)))
+            .expect("a NameRidefinitionError is expected");
         match err {
             NameError::NameRidefinitionError(err) => {
                 assert_eq!(err.name, name);
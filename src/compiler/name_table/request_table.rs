@@ -1,5 +1,7 @@
 use super::Loc;
-use fsa_net_parser::syntax_tree::{Command, CommandDecl, DiagnosisCommand};
+use fsa_net_parser::syntax_tree::{
+    Command, CommandDecl, DiagnoserCommand, DiagnosisCommand, ObsStep,
+};
 
 use indexmap::IndexMap;
 
@@ -29,8 +31,13 @@ impl<'a> RequestTable<'a> {
 
     pub fn add_request(&mut self, req: Request<'a>) {
         match req.1 {
-            RequestType::Diagnosis(DiagnosisRequest::Load(file)) => self.insert_file(file),
+            RequestType::Diagnosis(DiagnosisRequest::Load(file))
+            | RequestType::Check((DiagnosisRequest::Load(file), _)) => self.insert_file(file),
             RequestType::Linspace((_, Some(file))) => self.insert_file(file),
+            RequestType::Diagnoser(DiagnoserRequest::Build((_, Some(file)))) => {
+                self.insert_file(file)
+            }
+            RequestType::Diagnoser(DiagnoserRequest::Load((file, _))) => self.insert_file(file),
             _ => {}
         };
         self.requests.push(req)
@@ -43,30 +50,59 @@ impl<'a> RequestTable<'a> {
         }
     }
 
-    pub fn get_linspace_labels(&self) -> impl Iterator<Item = &Vec<&'a str>> {
-        self.requests.iter().filter_map(|(_, cmd)| {
-            if let RequestType::Linspace((lbls, _)) = cmd {
-                Some(lbls)
-            } else {
-                None
-            }
-        })
-    }
-
-    pub fn get_diagnosis_labels(&self) -> impl Iterator<Item = &Vec<&'a str>> {
+    pub fn get_linspace_labels(&self) -> impl Iterator<Item = (Loc, &'a str)> + '_ {
         self.requests
             .iter()
-            .filter_map(|(_, cmd)| {
-                if let RequestType::Diagnosis(lbls) = cmd {
-                    Some(lbls)
+            .filter_map(|(loc, cmd)| {
+                if let RequestType::Linspace((steps, _)) = cmd {
+                    Some((*loc, steps))
                 } else {
                     None
                 }
             })
-            .filter_map(|req| match req {
-                DiagnosisRequest::Fresh(lbls) => Some(lbls),
+            .flat_map(|(loc, steps)| step_names(loc, steps))
+    }
+
+    pub fn get_diagnosis_labels(&self) -> impl Iterator<Item = (Loc, &'a str)> + '_ {
+        self.requests
+            .iter()
+            .filter_map(|(loc, cmd)| match cmd {
+                RequestType::Diagnosis(lbls)
+                | RequestType::Check((lbls, _))
+                | RequestType::Witness(lbls)
+                | RequestType::Likely(lbls)
+                | RequestType::Cheapest(lbls) => Some((*loc, lbls)),
+                _ => None,
+            })
+            .filter_map(|(loc, req)| match req {
+                DiagnosisRequest::Fresh(steps) => Some((loc, steps)),
                 DiagnosisRequest::Load(_) => None,
             })
+            .flat_map(|(loc, steps)| step_names(loc, steps))
+    }
+
+    pub fn get_diagnoser_labels(&self) -> impl Iterator<Item = (Loc, &'a str)> + '_ {
+        self.requests
+            .iter()
+            .filter_map(|(loc, cmd)| match cmd {
+                RequestType::Diagnoser(DiagnoserRequest::Build((lbls, _)))
+                | RequestType::Diagnoser(DiagnoserRequest::Load((_, lbls))) => Some((*loc, lbls)),
+                _ => None,
+            })
+            .flat_map(|(loc, lbls)| lbls.iter().map(move |l| (loc, *l)))
+    }
+
+    pub fn get_check_labels(&self) -> impl Iterator<Item = (Loc, &'a str)> + '_ {
+        self.requests
+            .iter()
+            .filter_map(|(loc, cmd)| {
+                if let RequestType::Check((_, strings)) = cmd {
+                    Some((*loc, strings))
+                } else {
+                    None
+                }
+            })
+            .flat_map(|(loc, strings)| strings.iter().flatten().map(move |l| (loc, *l)))
     }
 
     pub fn get_file_index(&self, file: &str) -> usize {
@@ -81,14 +117,39 @@ impl<'a> RequestTable<'a> {
 pub fn convert_command<'a>(cmd: &CommandDecl<'a>) -> Request<'a> {
     match &cmd.cmd {
         Command::Space => (cmd.get_location(), RequestType::Space),
+        Command::Closures => (cmd.get_location(), RequestType::Closures),
+        Command::Diagnosability => (cmd.get_location(), RequestType::Diagnosability),
         Command::Linspace(cmd) => (
             cmd.get_location(),
-            RequestType::Linspace((weak_copy(&cmd.name_list), cmd.save_file)),
+            RequestType::Linspace((cmd.obs_list.clone(), cmd.save_file)),
         ),
         Command::Diagnosis(cmd) => {
             let (loc, cmd) = convert_diagnosis(cmd);
             (loc, RequestType::Diagnosis(cmd))
         }
+        Command::Diagnoser(cmd) => {
+            let (loc, cmd) = convert_diagnoser(cmd);
+            (loc, RequestType::Diagnoser(cmd))
+        }
+        Command::Check(cmd) => {
+            let (_, diagnosis) = convert_diagnosis(&cmd.diagnosis);
+            (
+                cmd.get_location(),
+                RequestType::Check((diagnosis, cmd.strings.clone())),
+            )
+        }
+        Command::Witness(cmd) => (
+            cmd.get_location(),
+            RequestType::Witness(DiagnosisRequest::Fresh(cmd.diagnosis.obs_list.clone())),
+        ),
+        Command::Likely(cmd) => (
+            cmd.get_location(),
+            RequestType::Likely(DiagnosisRequest::Fresh(cmd.diagnosis.obs_list.clone())),
+        ),
+        Command::Cheapest(cmd) => (
+            cmd.get_location(),
+            RequestType::Cheapest(DiagnosisRequest::Fresh(cmd.diagnosis.obs_list.clone())),
+        ),
     }
 }
 
@@ -96,12 +157,30 @@ fn convert_diagnosis<'a>(cmd: &DiagnosisCommand<'a>) -> (Loc, DiagnosisRequest<'
     match cmd {
         DiagnosisCommand::Fresh(fresh) => (
             fresh.get_location(),
-            DiagnosisRequest::Fresh(weak_copy(&fresh.name_list)),
+            DiagnosisRequest::Fresh(fresh.obs_list.clone()),
         ),
         DiagnosisCommand::Load(load) => (load.get_location(), DiagnosisRequest::Load(load.file)),
     }
 }
 
+fn convert_diagnoser<'a>(cmd: &DiagnoserCommand<'a>) -> (Loc, DiagnoserRequest<'a>) {
+    match cmd {
+        DiagnoserCommand::Build(build) => (
+            build.get_location(),
+            DiagnoserRequest::Build((weak_copy(&build.name_list), build.save_file)),
+        ),
+        DiagnoserCommand::Load(load) => (
+            load.get_location(),
+            DiagnoserRequest::Load((load.file, weak_copy(&load.name_list))),
+        ),
+    }
+}
+
+fn step_names<'a>(loc: Loc, steps: &[ObsStep<'a>]) -> impl Iterator<Item = (Loc, &'a str)> {
+    let names: Vec<_> = steps.iter().flat_map(|s| s.names()).collect();
+    names.into_iter().map(move |n| (loc, n))
+}
+
 fn weak_copy<'a>(names: &[&'a str]) -> Vec<&'a str> {
     names.iter().map(|n| *n).collect()
 }
@@ -111,16 +190,29 @@ pub type Request<'a> = (Loc, RequestType<'a>);
 #[derive(Debug)]
 pub enum RequestType<'a> {
     Space,
-    Linspace((Vec<&'a str>, Option<&'a str>)),
+    Closures,
+    Diagnosability,
+    Linspace((Vec<ObsStep<'a>>, Option<&'a str>)),
     Diagnosis(DiagnosisRequest<'a>),
+    Diagnoser(DiagnoserRequest<'a>),
+    Check((DiagnosisRequest<'a>, Vec<Vec<&'a str>>)),
+    Witness(DiagnosisRequest<'a>),
+    Likely(DiagnosisRequest<'a>),
+    Cheapest(DiagnosisRequest<'a>),
 }
 
 #[derive(Debug)]
 pub enum DiagnosisRequest<'a> {
-    Fresh(Vec<&'a str>),
+    Fresh(Vec<ObsStep<'a>>),
     Load(&'a str),
 }
 
+#[derive(Debug)]
+pub enum DiagnoserRequest<'a> {
+    Build((Vec<&'a str>, Option<&'a str>)),
+    Load((&'a str, Vec<&'a str>)),
+}
+
 #[cfg(test)]
 mod test {
 
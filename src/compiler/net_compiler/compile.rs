@@ -10,7 +10,7 @@ use fsa_net_parser::Code;
 
 pub fn compile_networks(code: &Code, table: &GlobalNameTable) -> Vec<CompileNetwork> {
     code.iter()
-        .map(|blk| compile_block(blk, table))
+        .filter_map(|blk| compile_block(blk, table))
         .fold(ResultBuilder::new(), |builder, (name, item)| {
             builder.insert_node(name, item)
         })
@@ -20,15 +20,16 @@ pub fn compile_networks(code: &Code, table: &GlobalNameTable) -> Vec<CompileNetw
 fn compile_block<'a>(
     block: &'a syntax_tree::Block,
     table: &GlobalNameTable,
-) -> (&'a str, ItemType) {
+) -> Option<(&'a str, ItemType)> {
     match block {
-        syntax_tree::Block::Network(net) => (
+        syntax_tree::Block::Network(net) => Some((
             net.name,
             compile_network::compile_network(net, table).into(),
-        ),
-        syntax_tree::Block::Request(req) => (
+        )),
+        syntax_tree::Block::Request(req) => Some((
             req.name,
             compile_requests::compile_requests(req, table).into(),
-        ),
+        )),
+        syntax_tree::Block::Import(_) | syntax_tree::Block::Template(_) => None,
     }
 }
@@ -8,6 +8,7 @@ use fsa_net_parser::syntax_tree;
 pub fn compile_network(net: &syntax_tree::Network, table: &GlobalNameTable) -> network::Network {
     let mut automata_list = Vec::new();
     let mut link_list = Vec::new();
+    let mut criterion = network::FinalCriterion::default();
 
     for entry in &net.params {
         match &entry.param {
@@ -19,10 +20,20 @@ pub fn compile_network(net: &syntax_tree::Network, table: &GlobalNameTable) -> n
                 let tmp = compile_link(link, table, net.name);
                 link_list.push(tmp);
             }
+            syntax_tree::NetworkParameter::Final(decl) => criterion = convert_criterion(*decl),
             _ => {}
         }
     }
-    network::Network::new(automata_list, link_list)
+    network::Network::new(automata_list, link_list).set_criterion(criterion)
+}
+
+fn convert_criterion(criterion: syntax_tree::FinalCriterion) -> network::FinalCriterion {
+    match criterion {
+        syntax_tree::FinalCriterion::EmptyLinks => network::FinalCriterion::EmptyLinks,
+        syntax_tree::FinalCriterion::FinalStates => network::FinalCriterion::FinalStates,
+        syntax_tree::FinalCriterion::Both => network::FinalCriterion::Both,
+        syntax_tree::FinalCriterion::Any => network::FinalCriterion::Any,
+    }
 }
 
 fn compile_automata(
@@ -32,6 +43,7 @@ fn compile_automata(
 ) -> network::Automata {
     let mut builder = graph_builder::GraphBuilder::new();
     let mut begin = 0;
+    let mut finals = Vec::new();
     for decl in &auto_decl.params {
         match &decl.param {
             syntax_tree::AutomataParameter::StateDecl(state) => {
@@ -42,12 +54,20 @@ fn compile_automata(
             syntax_tree::AutomataParameter::Transition(trans) => {
                 compile_transition(trans, table, net_name, auto_decl.name, &mut builder)
             }
+            syntax_tree::AutomataParameter::Final(states) => finals.extend(
+                states
+                    .iter()
+                    .map(|name| table.get_automata_name_index(net_name, auto_decl.name, name)),
+            ),
         }
     }
 
     let adj_list = builder.build_graph();
     let automata_index = table.get_network_name_index(net_name, auto_decl.name);
-    network::Automata::new(begin, automata_index, adj_list)
+    finals.into_iter().fold(
+        network::Automata::new(begin, automata_index, adj_list),
+        |automata, state| automata.add_final(state),
+    )
 }
 
 fn compile_transition(
@@ -93,6 +113,18 @@ fn compile_transition(
         out_trans
     };
 
+    let out_trans = if let Some(prob) = trans.prob {
+        out_trans.set_probability(prob)
+    } else {
+        out_trans
+    };
+
+    let out_trans = if let Some(cost) = trans.cost {
+        out_trans.set_cost(cost)
+    } else {
+        out_trans
+    };
+
     builder.add_arc(src_state, dst_state, out_trans);
 }
 
@@ -113,5 +145,11 @@ fn compile_link(
 ) -> network::Link {
     let src = table.get_network_name_index(net_name, decl.source);
     let dst = table.get_network_name_index(net_name, decl.destination);
-    network::Link::new(src, dst)
+    let capacity = decl.capacity.unwrap_or(network::DEFAULT_LINK_CAPACITY);
+    let link = network::Link::new(src, dst, capacity);
+    if let Some(init) = decl.init {
+        link.set_init(table.get_network_name_index(net_name, init))
+    } else {
+        link
+    }
 }
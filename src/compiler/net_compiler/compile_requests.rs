@@ -1,6 +1,7 @@
 use super::super::name_table::GlobalNameTable;
 
-use crate::command::{Command, DiagnosisCommand, Requests};
+use crate::command::{Command, DiagnoserCommand, DiagnosisCommand, ObsStep, Requests};
+use crate::engine::EliminationOrder;
 
 use fsa_net_parser::syntax_tree;
 
@@ -20,12 +21,39 @@ fn compile_request(
 ) -> Command {
     match &cmd.cmd {
         syntax_tree::Command::Space => Command::FullSpace,
+        syntax_tree::Command::Closures => Command::Closures,
+        syntax_tree::Command::Diagnosability => Command::Diagnosability,
         syntax_tree::Command::Linspace(labels) => {
             Command::Linspace(compile_linspace(labels, req_name, table))
         }
         syntax_tree::Command::Diagnosis(labels) => {
             Command::Diagnosis(compile_diagnosis(labels, req_name, table))
         }
+        syntax_tree::Command::Diagnoser(cmd) => {
+            Command::Diagnoser(compile_diagnoser(cmd, req_name, table))
+        }
+        syntax_tree::Command::Check(cmd) => Command::Check((
+            compile_diagnosis(&cmd.diagnosis, req_name, table),
+            cmd.strings
+                .iter()
+                .map(|string| map_rel_label(string, req_name, table))
+                .collect(),
+        )),
+        syntax_tree::Command::Witness(cmd) => Command::Witness((
+            map_obs_steps(&cmd.diagnosis.obs_list, req_name, table),
+            convert_order(cmd.diagnosis.order),
+            cmd.length,
+        )),
+        syntax_tree::Command::Likely(cmd) => Command::Likely((
+            map_obs_steps(&cmd.diagnosis.obs_list, req_name, table),
+            convert_order(cmd.diagnosis.order),
+            cmd.count,
+        )),
+        syntax_tree::Command::Cheapest(cmd) => Command::Cheapest((
+            map_obs_steps(&cmd.diagnosis.obs_list, req_name, table),
+            convert_order(cmd.diagnosis.order),
+            cmd.count,
+        )),
     }
 }
 
@@ -33,9 +61,9 @@ fn compile_linspace(
     labels: &syntax_tree::LinspaceCommand,
     req_name: &str,
     table: &GlobalNameTable,
-) -> (Vec<usize>, Option<usize>) {
+) -> (Vec<ObsStep>, Option<usize>) {
     (
-        map_obs_label(&labels.name_list, req_name, table),
+        map_obs_steps(&labels.obs_list, req_name, table),
         convert_file_index(&labels.save_file, req_name, table),
     )
 }
@@ -46,12 +74,31 @@ fn compile_diagnosis(
     table: &GlobalNameTable,
 ) -> DiagnosisCommand {
     match label {
-        syntax_tree::DiagnosisCommand::Fresh(lbls) => {
-            DiagnosisCommand::Fresh(map_obs_label(&lbls.name_list, req_name, table))
-        }
-        syntax_tree::DiagnosisCommand::Load(file) => {
-            DiagnosisCommand::Load(table.get_file_index(req_name, file.file))
-        }
+        syntax_tree::DiagnosisCommand::Fresh(lbls) => DiagnosisCommand::Fresh((
+            map_obs_steps(&lbls.obs_list, req_name, table),
+            convert_order(lbls.order),
+        )),
+        syntax_tree::DiagnosisCommand::Load(file) => DiagnosisCommand::Load((
+            table.get_file_index(req_name, file.file),
+            convert_order(file.order),
+        )),
+    }
+}
+
+fn compile_diagnoser(
+    cmd: &syntax_tree::DiagnoserCommand,
+    req_name: &str,
+    table: &GlobalNameTable,
+) -> DiagnoserCommand {
+    match cmd {
+        syntax_tree::DiagnoserCommand::Build(build) => DiagnoserCommand::Build((
+            map_obs_label(&build.name_list, req_name, table),
+            convert_file_index(&build.save_file, req_name, table),
+        )),
+        syntax_tree::DiagnoserCommand::Load(load) => DiagnoserCommand::Load((
+            table.get_file_index(req_name, load.file),
+            map_obs_label(&load.name_list, req_name, table),
+        )),
     }
 }
 
@@ -62,6 +109,45 @@ fn map_obs_label(labels: &[&str], req_name: &str, table: &GlobalNameTable) -> Ve
         .collect()
 }
 
+fn map_rel_label(labels: &[&str], req_name: &str, table: &GlobalNameTable) -> Vec<usize> {
+    labels
+        .iter()
+        .map(|lbl| table.get_network_name_index(req_name, lbl))
+        .collect()
+}
+
+fn map_obs_steps(
+    steps: &[syntax_tree::ObsStep],
+    req_name: &str,
+    table: &GlobalNameTable,
+) -> Vec<ObsStep> {
+    steps
+        .iter()
+        .map(|step| map_obs_step(step, req_name, table))
+        .collect()
+}
+
+fn map_obs_step(step: &syntax_tree::ObsStep, req_name: &str, table: &GlobalNameTable) -> ObsStep {
+    match step {
+        syntax_tree::ObsStep::Label(lbl) => {
+            ObsStep::Label(table.get_network_name_index(req_name, lbl))
+        }
+        syntax_tree::ObsStep::Set(lbls) => ObsStep::Set(map_obs_label(lbls, req_name, table)),
+        syntax_tree::ObsStep::Any => ObsStep::Any,
+        syntax_tree::ObsStep::Group(alts) => ObsStep::Group(
+            alts.iter()
+                .map(|alt| map_obs_steps(alt, req_name, table))
+                .collect(),
+        ),
+        syntax_tree::ObsStep::Star(step) => {
+            ObsStep::Star(Box::new(map_obs_step(step, req_name, table)))
+        }
+        syntax_tree::ObsStep::Plus(step) => {
+            ObsStep::Plus(Box::new(map_obs_step(step, req_name, table)))
+        }
+    }
+}
+
 fn convert_file_index(
     file: &Option<&str>,
     net_name: &str,
@@ -73,3 +159,13 @@ fn convert_file_index(
         None
     }
 }
+
+fn convert_order(order: Option<syntax_tree::EliminationOrder>) -> Option<EliminationOrder> {
+    order.map(|order| match order {
+        syntax_tree::EliminationOrder::MaxDegree => EliminationOrder::MaxDegree,
+        syntax_tree::EliminationOrder::MinProduct => EliminationOrder::MinProduct,
+        syntax_tree::EliminationOrder::MinSize => EliminationOrder::MinSize,
+        syntax_tree::EliminationOrder::FewestLoops => EliminationOrder::FewestLoops,
+        syntax_tree::EliminationOrder::Topological => EliminationOrder::Topological,
+    })
+}
@@ -78,15 +78,16 @@ impl CompileStorage {
         }
     }
 
+    /*
+        Networks without requests are compiled too,
+        so that their index matches the index table
+        and they can still be monitored.
+    */
     fn get_compile_result(self) -> Option<CompileNetwork> {
-        if let Some(req) = self.req {
-            let output = CompileNetwork {
-                net: self.net.unwrap(),
-                req,
-            };
-            Some(output)
-        } else {
-            None
-        }
+        let net = self.net?;
+        let req = self
+            .req
+            .unwrap_or_else(|| command::Requests::new(Vec::new()));
+        Some(CompileNetwork { net, req })
     }
 }
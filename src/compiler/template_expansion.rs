@@ -0,0 +1,338 @@
+/*
+    Replace each automata instance with
+    a copy of its template where link parameters
+    are replaced by the instance arguments.
+    The expanded code contains neither templates
+    nor instances, so the following compilation
+    steps are unaware of them.
+*/
+
+use ahash::{AHashMap, AHashSet};
+use fsa_net_parser::syntax_tree::*;
+use fsa_net_parser::Code;
+
+type Loc = (usize, usize);
+
+#[derive(Debug)]
+pub enum TemplateError<'a> {
+    UndefinedTemplate(UndefinedTemplate<'a>),
+    TemplateRidefinition(TemplateRidefinition<'a>),
+    ArityMismatch(ArityMismatch<'a>),
+    UnresolvedLink(UnresolvedLink<'a>),
+}
+
+#[derive(Debug)]
+pub struct UndefinedTemplate<'a> {
+    pub name: &'a str,
+    pub loc: Loc,
+}
+
+#[derive(Debug)]
+pub struct TemplateRidefinition<'a> {
+    pub name: &'a str,
+    pub orig_loc: Loc,
+    pub ridef_loc: Loc,
+}
+
+/**
+ * loc is the location of the instance,
+ * template_loc the location of the template
+ */
+#[derive(Debug)]
+pub struct ArityMismatch<'a> {
+    pub template: &'a str,
+    pub expected: usize,
+    pub found: usize,
+    pub loc: Loc,
+    pub template_loc: Loc,
+}
+
+/**
+ * An instance argument that is not
+ * a link declared in the network
+ */
+#[derive(Debug)]
+pub struct UnresolvedLink<'a> {
+    pub template: &'a str,
+    pub param: &'a str,
+    pub link: &'a str,
+    pub loc: Loc,
+}
+
+pub fn expand_templates<'a>(code: &Code<'a>) -> Result<Code<'a>, Vec<TemplateError<'a>>> {
+    let mut errors = vec![];
+    let templates = collect_templates(
+        code.iter().filter_map(|blk| match blk {
+            Block::Template(template) => Some(template),
+            _ => None,
+        }),
+        &mut errors,
+    );
+
+    let output = code
+        .iter()
+        .filter_map(|blk| match blk {
+            Block::Network(net) => {
+                Some(Block::Network(expand_network(net, &templates, &mut errors)))
+            }
+            Block::Template(_) => None,
+            blk => Some(blk.clone()),
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(output)
+    } else {
+        Err(errors)
+    }
+}
+
+fn collect_templates<'a, 'b>(
+    templates: impl Iterator<Item = &'b AutomataTemplate<'a>>,
+    errors: &mut Vec<TemplateError<'a>>,
+) -> AHashMap<&'a str, &'b AutomataTemplate<'a>>
+where
+    'a: 'b,
+{
+    let mut output: AHashMap<&'a str, &'b AutomataTemplate<'a>> = AHashMap::new();
+    for template in templates {
+        if let Some(prev) = output.get(template.name) {
+            let err = TemplateRidefinition {
+                name: template.name,
+                orig_loc: prev.get_location(),
+                ridef_loc: template.get_location(),
+            };
+            errors.push(TemplateError::TemplateRidefinition(err));
+        } else {
+            output.insert(template.name, template);
+        }
+    }
+    output
+}
+
+/*
+    Templates declared inside a network
+    hide global templates with the same name
+*/
+fn expand_network<'a>(
+    net: &Network<'a>,
+    global: &AHashMap<&'a str, &AutomataTemplate<'a>>,
+    errors: &mut Vec<TemplateError<'a>>,
+) -> Network<'a> {
+    let local = collect_templates(
+        net.params.iter().filter_map(|param| match &param.param {
+            NetworkParameter::Template(template) => Some(template),
+            _ => None,
+        }),
+        errors,
+    );
+    let links: AHashSet<&'a str> = net
+        .params
+        .iter()
+        .filter_map(|param| match &param.param {
+            NetworkParameter::Link(link) => Some(link.name),
+            _ => None,
+        })
+        .collect();
+
+    let params = net
+        .params
+        .iter()
+        .filter_map(|param| match &param.param {
+            NetworkParameter::Template(_) => None,
+            NetworkParameter::Instance(instance) => {
+                let template = local
+                    .get(instance.template)
+                    .or_else(|| global.get(instance.template));
+                let automata = expand_instance(instance, template, &links, errors)?;
+                let param = NetworkParameter::Automata(automata);
+                let (begin, end) = instance.get_location();
+                Some(NetworkParameterDecl::new(param).set_location(begin, end))
+            }
+            _ => Some(param.clone()),
+        })
+        .collect();
+
+    let (begin, end) = net.get_location();
+    Network::new(net.name, params).set_location(begin, end)
+}
+
+fn expand_instance<'a>(
+    instance: &AutomataInstance<'a>,
+    template: Option<&&AutomataTemplate<'a>>,
+    links: &AHashSet<&'a str>,
+    errors: &mut Vec<TemplateError<'a>>,
+) -> Option<Automata<'a>> {
+    let loc = instance.get_location();
+    let template = if let Some(template) = template {
+        template
+    } else {
+        let err = UndefinedTemplate {
+            name: instance.template,
+            loc,
+        };
+        errors.push(TemplateError::UndefinedTemplate(err));
+        return None;
+    };
+
+    if template.args.len() != instance.args.len() {
+        let err = ArityMismatch {
+            template: template.name,
+            expected: template.args.len(),
+            found: instance.args.len(),
+            loc,
+            template_loc: template.get_location(),
+        };
+        errors.push(TemplateError::ArityMismatch(err));
+        return None;
+    }
+
+    let mut resolved = true;
+    for (param, link) in template.args.iter().zip(instance.args.iter()) {
+        if !links.contains(link) {
+            let err = UnresolvedLink {
+                template: template.name,
+                param,
+                link,
+                loc,
+            };
+            errors.push(TemplateError::UnresolvedLink(err));
+            resolved = false;
+        }
+    }
+    if !resolved {
+        return None;
+    }
+
+    let bindings: AHashMap<&'a str, &'a str> = template
+        .args
+        .iter()
+        .copied()
+        .zip(instance.args.iter().copied())
+        .collect();
+    let params = template
+        .params
+        .iter()
+        .map(|param| bind_parameter(param, &bindings))
+        .collect();
+    let (begin, end) = loc;
+    Some(Automata::new(instance.name, params).set_location(begin, end))
+}
+
+fn bind_parameter<'a>(
+    param: &AutomataParameterDecl<'a>,
+    bindings: &AHashMap<&'a str, &'a str>,
+) -> AutomataParameterDecl<'a> {
+    let mut output = param.clone();
+    if let AutomataParameter::Transition(trans) = &mut output.param {
+        if let Some(input) = &mut trans.input {
+            bind_event(input, bindings);
+        }
+        if let Some(outputs) = &mut trans.output {
+            for output in outputs {
+                bind_event(output, bindings);
+            }
+        }
+    }
+    output
+}
+
+fn bind_event<'a>(event: &mut Event<'a>, bindings: &AHashMap<&'a str, &'a str>) {
+    if let Some(link) = bindings.get(event.link) {
+        event.link = link;
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use fsa_net_parser::parse;
+    use test_utils::load_code_from_file;
+
+    fn get_network<'a, 'b>(code: &'b Code<'a>, name: &str) -> &'b Network<'a> {
+        code.iter()
+            .find_map(|blk| match blk {
+                Block::Network(net) if net.name == name => Some(net),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn get_automata<'a, 'b>(net: &'b Network<'a>, name: &str) -> &'b Automata<'a> {
+        net.params
+            .iter()
+            .find_map(|param| match &param.param {
+                NetworkParameter::Automata(automata) if automata.name == name => Some(automata),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_expand_templates() {
+        let src_code = load_code_from_file("template-network");
+        let code = parse(&src_code).expect("`template-network` should be syntactically correct");
+        let code = expand_templates(&code).expect("`template-network` has correct templates");
+
+        assert_eq!(code.len(), 2);
+        let net = get_network(&code, "Breakers");
+        let b1 = get_automata(net, "B1");
+        let b2 = get_automata(net, "B2");
+        for (automata, input, output) in &[(b1, "L1", "L2"), (b2, "L2", "L1")] {
+            let links: Vec<&str> = automata
+                .params
+                .iter()
+                .filter_map(|param| match &param.param {
+                    AutomataParameter::Transition(trans) => Some(trans),
+                    _ => None,
+                })
+                .flat_map(|trans| {
+                    let input = trans.input.iter().map(|ev| ev.link);
+                    let output = trans.output.iter().flatten().map(|ev| ev.link);
+                    input.chain(output)
+                })
+                .collect();
+            assert_eq!(links, vec![*input, *output]);
+        }
+        assert!(!net.params.iter().any(|param| matches!(
+            &param.param,
+            NetworkParameter::Template(_) | NetworkParameter::Instance(_)
+        )));
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let src_code = load_code_from_file("template-errors");
+        let code = parse(&src_code).expect("`template-errors` should be syntactically correct");
+        let errors = match expand_templates(&code) {
+            Ok(_) => panic!("`template-errors` contains errors"),
+            Err(errors) => errors,
+        };
+        assert_eq!(errors.len(), 4);
+
+        match &errors[0] {
+            TemplateError::TemplateRidefinition(err) => assert_eq!(err.name, "Breaker"),
+            err => panic!("expected TemplateRidefinition, found {:?}", err),
+        }
+        match &errors[1] {
+            TemplateError::ArityMismatch(err) => {
+                assert_eq!(err.template, "Breaker");
+                assert_eq!(err.expected, 2);
+                assert_eq!(err.found, 1);
+            }
+            err => panic!("expected ArityMismatch, found {:?}", err),
+        }
+        match &errors[2] {
+            TemplateError::UnresolvedLink(err) => {
+                assert_eq!(err.param, "Out");
+                assert_eq!(err.link, "L9");
+            }
+            err => panic!("expected UnresolvedLink, found {:?}", err),
+        }
+        match &errors[3] {
+            TemplateError::UndefinedTemplate(err) => assert_eq!(err.name, "Sensor"),
+            err => panic!("expected UndefinedTemplate, found {:?}", err),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use super::Report;
+use crate::compiler::error::*;
+
+/**
+ * Convert all the compile errors into Reports,
+ * sorted by their position in the source code
+ */
+pub fn compile_error_reports(errors: &[CompileError]) -> Vec<Report> {
+    let mut reports: Vec<Report> = errors.iter().flat_map(compile_error_report).collect();
+    reports.sort_by_key(|r| r.get_location());
+    reports
+}
+
+fn compile_error_report(err: &CompileError) -> Vec<Report> {
+    match err {
+        CompileError::NameError(err) => name_error_report(err),
+        CompileError::GraphError(err) => vec![graph_error_report(err)],
+        CompileError::LinkError(err) => link_error_report(err),
+        CompileError::TemplateError(err) => vec![template_error_report(err)],
+    }
+}
+
+fn name_error_report(err: &NameError) -> Vec<Report> {
+    match err {
+        NameError::NameRidefinitionError(err) => vec![ridefinition_report(err)],
+        NameError::BeginStateError(err) => vec![begin_state_report(err)],
+        NameError::UndefinedNameError(err) => {
+            let msg = format!("`{}` is used but never defined", err.name);
+            vec![Report::error(msg).with_label(err.loc, Some("used here".to_owned()))]
+        }
+        NameError::UndefinedNetwork(err) => err
+            .names
+            .iter()
+            .map(|(name, loc)| {
+                let msg = format!("request on undefined network `{}`", name);
+                Report::error(msg).with_label(*loc, None)
+            })
+            .collect(),
+        NameError::UndefinedLabel(err) => {
+            let msg = format!("undefined {} `{}`", err.class, err.name);
+            vec![Report::error(msg).with_label(err.loc, Some("used in this request".to_owned()))]
+        }
+        NameError::MismatchedType(err) => {
+            let msg = format!("`{}` is a {}, expected {}", err.name, err.orig, err.curr);
+            vec![Report::error(msg).with_label(err.loc, Some("used in this request".to_owned()))]
+        }
+    }
+}
+
+fn ridefinition_report(err: &NameRidefinitionError) -> Report {
+    if err.orig_class == err.ridef_class {
+        let msg = format!(
+            "{} `{}` is defined multiple times",
+            err.orig_class, err.name
+        );
+        Report::error(msg)
+            .with_label(err.ridef_loc, Some("redefined here".to_owned()))
+            .with_secondary_label(err.orig_loc, Some("originally defined here".to_owned()))
+    } else {
+        let msg = format!(
+            "`{}` is used as {} and as {}",
+            err.name, err.orig_class, err.ridef_class
+        );
+        Report::error(msg)
+            .with_label(
+                err.ridef_loc,
+                Some(format!("used as {} here", err.ridef_class)),
+            )
+            .with_secondary_label(
+                err.orig_loc,
+                Some(format!("previously used as {} here", err.orig_class)),
+            )
+    }
+}
+
+fn begin_state_report(err: &BeginStateError) -> Report {
+    match &err.class {
+        BeginStateErrorClass::NoBeginState => {
+            let msg = format!("automata `{}` has no begin state", err.name);
+            Report::error(msg).with_label(err.loc, None)
+        }
+        BeginStateErrorClass::MultipleBeginState(states) => {
+            let mut states = states.clone();
+            states.sort_unstable();
+            let msg = format!("automata `{}` has multiple begin states", err.name);
+            Report::error(msg)
+                .with_label(err.loc, None)
+                .with_note(format!("begin states: {}", states.join(", ")))
+        }
+    }
+}
+
+fn graph_error_report(err: &GraphError) -> Report {
+    let msg = format!("automata `{}` has unreachable states", err.automata);
+    let mut states = err.states.clone();
+    states.sort_by_key(|(_, loc)| *loc);
+    let report = states.iter().fold(Report::error(msg), |report, (_, loc)| {
+        report.with_label(*loc, Some("not reachable from the begin state".to_owned()))
+    });
+    report.with_secondary_label(err.loc, Some("in this automata".to_owned()))
+}
+
+fn link_error_report(err: &LinkError) -> Vec<Report> {
+    match err {
+        LinkError::NotInput(err) => {
+            let msg = format!(
+                "link `{}` is used as input by `{}`, which is not its destination",
+                err.link, err.automata
+            );
+            vec![link_connection_report(msg, err)]
+        }
+        LinkError::NotOutput(err) => {
+            let msg = format!(
+                "link `{}` is used as output by `{}`, which is not its source",
+                err.link, err.automata
+            );
+            vec![link_connection_report(msg, err)]
+        }
+        LinkError::MultipleLinkUse(errs) => errs.iter().map(link_count_report).collect(),
+        LinkError::InitNotConsumed(err) => vec![link_init_report(err)],
+    }
+}
+
+fn link_connection_report(msg: String, err: &LinkConnectionError) -> Report {
+    Report::error(msg)
+        .with_label(err.loc, Some("in this transition".to_owned()))
+        .with_secondary_label(err.link_loc, Some("link declared here".to_owned()))
+}
+
+fn link_count_report(err: &LinkCountError) -> Report {
+    let msg = format!(
+        "link `{}` is used {} times by the same transition in `{}`",
+        err.link, err.count, err.automata
+    );
+    Report::error(msg).with_label(err.loc, None)
+}
+
+fn link_init_report(err: &LinkInitError) -> Report {
+    let msg = format!(
+        "initial event `{}` of link `{}` is never consumed by `{}`",
+        err.event, err.link, err.automata
+    );
+    Report::error(msg).with_label(err.loc, Some("link declared here".to_owned()))
+}
+
+fn template_error_report(err: &TemplateError) -> Report {
+    match err {
+        TemplateError::UndefinedTemplate(err) => {
+            let msg = format!("undefined template `{}`", err.name);
+            Report::error(msg).with_label(err.loc, None)
+        }
+        TemplateError::TemplateRidefinition(err) => {
+            let msg = format!("template `{}` is defined multiple times", err.name);
+            Report::error(msg)
+                .with_label(err.ridef_loc, Some("redefined here".to_owned()))
+                .with_secondary_label(err.orig_loc, Some("originally defined here".to_owned()))
+        }
+        TemplateError::ArityMismatch(err) => {
+            let msg = format!(
+                "template `{}` takes {} link parameters, found {}",
+                err.template, err.expected, err.found
+            );
+            Report::error(msg)
+                .with_label(err.loc, None)
+                .with_secondary_label(err.template_loc, Some("template defined here".to_owned()))
+        }
+        TemplateError::UnresolvedLink(err) => {
+            let msg = format!(
+                "link `{}` for parameter `{}` of template `{}` is not declared in this network",
+                err.link, err.param, err.template
+            );
+            Report::error(msg).with_label(err.loc, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::SourceMap;
+    use super::*;
+    use crate::compiler::compile;
+    use fsa_net_parser::parse;
+
+    #[test]
+    fn test_multiple_errors() {
+        let code = "network N {\n    automata A {\n        begin s0\n        state s0\n        state s1\n    }\n    automata B {\n        state s0\n    }\n}";
+        let ast = parse(code).expect("should be syntactically correct");
+        let errors = compile(&ast).expect_err("contains semantic errors");
+        let reports = compile_error_reports(&errors);
+        assert_eq!(reports.len(), 3);
+
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        let output: Vec<String> = reports.iter().map(|r| r.render(&src)).collect();
+        assert_eq!(
+            output[0],
+            "error: state `s0` is defined multiple times\n \
+             --> test.fnl:4:9\n  \
+             |\n\
+             4 |         state s0\n  \
+             |         ^^^^^^^^ redefined here\n \
+             --> test.fnl:3:9\n  \
+             |\n\
+             3 |         begin s0\n  \
+             |         -------- originally defined here\n"
+        );
+        assert!(output[1].starts_with("error: automata `A` has unreachable states\n"));
+        assert_eq!(
+            output[2],
+            "error: automata `B` has no begin state\n \
+             --> test.fnl:7:5\n  \
+             |\n\
+             7 |     automata B {\n  \
+             |     ^^^^^^^^^^^^\n"
+        );
+    }
+}
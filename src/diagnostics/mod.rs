@@ -0,0 +1,11 @@
+mod compile_error;
+mod report;
+mod source_file;
+mod source_map;
+mod syntax_error;
+
+pub use compile_error::compile_error_reports;
+pub use report::Report;
+pub use source_file::SourceFile;
+pub use source_map::SourceMap;
+pub use syntax_error::syntax_error_report;
@@ -0,0 +1,240 @@
+use super::{SourceFile, SourceMap};
+use std::fmt::Write;
+
+type Loc = (usize, usize);
+
+/*
+    A Report is a message for the user
+    pointing to one or more locations in the source code.
+*/
+#[derive(Debug)]
+pub struct Report {
+    message: String,
+    labels: Vec<Label>,
+    notes: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Label {
+    loc: Loc,
+    message: Option<String>,
+    primary: bool,
+}
+
+impl Report {
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        Self {
+            message: message.into(),
+            labels: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn with_label(mut self, loc: Loc, message: Option<String>) -> Self {
+        self.labels.push(Label {
+            loc,
+            message,
+            primary: true,
+        });
+        self
+    }
+
+    /**
+     * Secondary labels give context to the error,
+     * i.e. the original definition of a redefined name
+     */
+    pub fn with_secondary_label(mut self, loc: Loc, message: Option<String>) -> Self {
+        self.labels.push(Label {
+            loc,
+            message,
+            primary: false,
+        });
+        self
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /**
+     * Return each label as (location, message, is primary)
+     */
+    pub fn get_labels(&self) -> impl Iterator<Item = (Loc, Option<&str>, bool)> {
+        self.labels
+            .iter()
+            .map(|l| (l.loc, l.message.as_deref(), l.primary))
+    }
+
+    /**
+     * Location of the first primary label
+     */
+    pub fn get_location(&self) -> Option<Loc> {
+        self.labels.iter().find(|l| l.primary).map(|l| l.loc)
+    }
+
+    pub fn with_note<S: Into<String>>(mut self, note: S) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn render(&self, src: &SourceMap) -> String {
+        let mut output = format!("error: {}\n", self.message);
+        let gutter = self.gutter_width(src);
+        for label in &self.labels {
+            let file = src.find_file(label.loc.0);
+            render_label(&mut output, label, file, gutter);
+        }
+        for note in &self.notes {
+            writeln!(output, "{:>width$} = {}", "", note, width = gutter).unwrap();
+        }
+        output
+    }
+
+    fn gutter_width(&self, src: &SourceMap) -> usize {
+        self.labels
+            .iter()
+            .map(|l| src.find_file(l.loc.0).get_position(l.loc.0).0)
+            .max()
+            .unwrap_or(0)
+            .to_string()
+            .len()
+    }
+}
+
+fn render_label(output: &mut String, label: &Label, src: &SourceFile, gutter: usize) {
+    let (begin, end) = label.loc;
+    let (line, column) = src.get_position(begin);
+    let text = src.get_line(line - 1);
+    writeln!(
+        output,
+        "{:>width$}--> {}:{}:{}",
+        "",
+        src.get_name(),
+        line,
+        column,
+        width = gutter
+    )
+    .unwrap();
+    writeln!(output, "{:>width$} |", "", width = gutter).unwrap();
+    writeln!(output, "{:>width$} | {}", line, text, width = gutter).unwrap();
+
+    let padding = underline_padding(text, column - 1);
+    let line_end = src.get_line_begin(line - 1) + text.len();
+    let end = end.min(line_end).max(begin);
+    let length = underline_length(src, begin, end);
+    let marker = if label.primary { "^" } else { "-" }.repeat(length);
+    if let Some(msg) = &label.message {
+        writeln!(
+            output,
+            "{:>width$} | {}{} {}",
+            "",
+            padding,
+            marker,
+            msg,
+            width = gutter
+        )
+        .unwrap();
+    } else {
+        writeln!(
+            output,
+            "{:>width$} | {}{}",
+            "",
+            padding,
+            marker,
+            width = gutter
+        )
+        .unwrap();
+    }
+}
+
+/*
+    Keep tabs in the padding so that the
+    underline is aligned with the source line
+*/
+fn underline_padding(text: &str, column: usize) -> String {
+    text.chars()
+        .take(column)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect()
+}
+
+fn underline_length(src: &SourceFile, begin: usize, end: usize) -> usize {
+    let (_, begin_col) = src.get_position(begin);
+    let (_, end_col) = src.get_position(end);
+    if end_col > begin_col {
+        end_col - begin_col
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let code = "network A {\n\tautomata B {\n}\n}";
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+
+        let report = Report::error("something is wrong")
+            .with_label((22, 23), Some("here".to_owned()))
+            .with_note("a note");
+        let expected = "error: something is wrong\n \
+                        --> test.fnl:2:11\n  \
+                        |\n\
+                        2 | \tautomata B {\n  \
+                        | \t         ^ here\n  \
+                        = a note\n";
+        assert_eq!(report.render(&src), expected);
+    }
+
+    #[test]
+    fn test_multiple_files() {
+        let mut src = SourceMap::default();
+        src.add_file("a.fnl", "network A {\n}");
+        src.add_file("b.fnl", "request A {\n space\n}");
+
+        let report = Report::error("something is wrong")
+            .with_label((27, 32), None)
+            .with_secondary_label((0, 7), None);
+        let expected = "error: something is wrong\n \
+                        --> b.fnl:2:2\n  \
+                        |\n\
+                        2 |  space\n  \
+                        |  ^^^^^\n \
+                        --> a.fnl:1:1\n  \
+                        |\n\
+                        1 | network A {\n  \
+                        | -------\n";
+        assert_eq!(report.render(&src), expected);
+    }
+
+    #[test]
+    fn test_secondary_label() {
+        let code = "state s0\nstate s0";
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+
+        let report = Report::error("redefined")
+            .with_label((15, 17), Some("redefined here".to_owned()))
+            .with_secondary_label((6, 8), Some("originally defined here".to_owned()));
+        assert_eq!(report.get_location(), Some((15, 17)));
+        let expected = "error: redefined\n \
+                        --> test.fnl:2:7\n  \
+                        |\n\
+                        2 | state s0\n  \
+                        |       ^^ redefined here\n \
+                        --> test.fnl:1:7\n  \
+                        |\n\
+                        1 | state s0\n  \
+                        |       -- originally defined here\n";
+        assert_eq!(report.render(&src), expected);
+    }
+}
@@ -0,0 +1,126 @@
+/*
+    Map byte offsets, as produced by the parser,
+    into the line/column pairs shown to the user.
+    Each file begins at `offset`: all the offsets
+    taken and returned by SourceFile are global.
+*/
+pub struct SourceFile {
+    name: String,
+    code: String,
+    offset: usize,
+    line_begin: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: String, code: String, offset: usize) -> Self {
+        let line_begin = std::iter::once(0)
+            .chain(code.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            name,
+            code,
+            offset,
+            line_begin,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn get_offset(&self) -> usize {
+        self.offset
+    }
+
+    /**
+     * True if the given offset belongs to this file.
+     * The end of file is considered part of the file.
+     */
+    pub fn contains(&self, offset: usize) -> bool {
+        offset >= self.offset && offset <= self.offset + self.code.len()
+    }
+
+    /**
+     * Return the zero based line index
+     * containing the given offset
+     */
+    pub fn get_line_index(&self, offset: usize) -> usize {
+        let offset = self.to_local(offset);
+        match self.line_begin.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        }
+    }
+
+    /**
+     * Return the one based (line, column) pair
+     * of the given offset. Columns count characters, not bytes.
+     */
+    pub fn get_position(&self, offset: usize) -> (usize, usize) {
+        let line = self.get_line_index(offset);
+        let begin = self.line_begin[line];
+        let column = self.code[begin..self.to_local(offset)].chars().count();
+        (line + 1, column + 1)
+    }
+
+    /**
+     * Return the text of the given zero based line,
+     * without the line terminator
+     */
+    pub fn get_line(&self, line: usize) -> &str {
+        let begin = self.line_begin[line];
+        let end = if line + 1 < self.line_begin.len() {
+            self.line_begin[line + 1] - 1
+        } else {
+            self.code.len()
+        };
+        self.code[begin..end].trim_end_matches('\r')
+    }
+
+    pub fn get_line_begin(&self, line: usize) -> usize {
+        self.line_begin[line] + self.offset
+    }
+
+    fn to_local(&self, offset: usize) -> usize {
+        offset.saturating_sub(self.offset).min(self.code.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let code = "network A {\n    automata B {\n}\n}";
+        let src = SourceFile::new("test.fnl".to_owned(), code.to_owned(), 0);
+
+        assert_eq!(src.get_position(0), (1, 1));
+        assert_eq!(src.get_position(8), (1, 9));
+        assert_eq!(src.get_position(12), (2, 1));
+        assert_eq!(src.get_position(16), (2, 5));
+        assert_eq!(src.get_position(code.len()), (4, 2));
+
+        assert_eq!(src.get_line(0), "network A {");
+        assert_eq!(src.get_line(1), "    automata B {");
+        assert_eq!(src.get_line(3), "}");
+    }
+
+    #[test]
+    fn test_offset_position() {
+        let code = "network A {\n    automata B {\n}\n}";
+        let src = SourceFile::new("test.fnl".to_owned(), code.to_owned(), 100);
+
+        assert!(!src.contains(99));
+        assert!(src.contains(100));
+        assert!(src.contains(100 + code.len()));
+        assert_eq!(src.get_position(100), (1, 1));
+        assert_eq!(src.get_position(116), (2, 5));
+        assert_eq!(src.get_line_begin(1), 112);
+    }
+}
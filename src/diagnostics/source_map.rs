@@ -0,0 +1,70 @@
+use super::SourceFile;
+
+/*
+    Collect all the files that make up a
+    specification. Each file gets a distinct
+    range of offsets so that a location identifies
+    both the file and the position inside it.
+*/
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+    next_offset: usize,
+}
+
+impl SourceMap {
+    /**
+     * Add a new file and return its index
+     */
+    pub fn add_file<N, C>(&mut self, name: N, code: C) -> usize
+    where
+        N: Into<String>,
+        C: Into<String>,
+    {
+        let code = code.into();
+        let offset = self.next_offset;
+        // keep a gap so that the end of a file
+        // is not the beginning of the next one
+        self.next_offset += code.len() + 1;
+        self.files.push(SourceFile::new(name.into(), code, offset));
+        self.files.len() - 1
+    }
+
+    pub fn get_file(&self, index: usize) -> &SourceFile {
+        &self.files[index]
+    }
+
+    pub fn files(&self) -> impl DoubleEndedIterator<Item = &SourceFile> {
+        self.files.iter()
+    }
+
+    /**
+     * Return the file containing the given offset
+     */
+    pub fn find_file(&self, offset: usize) -> &SourceFile {
+        self.files
+            .iter()
+            .find(|f| f.contains(offset))
+            .unwrap_or_else(|| panic!("offset {} is not in any file", offset))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_find_file() {
+        let mut map = SourceMap::default();
+        let first = map.add_file("a.fnl", "network A {\n}");
+        let second = map.add_file("b.fnl", "request A {\n space\n}");
+        assert_eq!(map.get_file(first).get_offset(), 0);
+        assert_eq!(map.get_file(second).get_offset(), 14);
+
+        assert_eq!(map.find_file(0).get_name(), "a.fnl");
+        assert_eq!(map.find_file(13).get_name(), "a.fnl");
+        assert_eq!(map.find_file(14).get_name(), "b.fnl");
+        assert_eq!(map.find_file(20).get_position(20), (1, 7));
+    }
+}
@@ -0,0 +1,241 @@
+use super::Report;
+use fsa_net_parser::syntax_tree::{
+    DeclarationError, DeclarationErrorType, EliminationOrder, FinalCriterion,
+    TransitionFactoryError, TransitionFactoryErrorType, UserError,
+};
+use fsa_net_parser::{ParseError, SyntaxError};
+
+/**
+ * Convert a parser error into a Report
+ */
+pub fn syntax_error_report(err: &SyntaxError) -> Report {
+    match err {
+        ParseError::InvalidToken { location } => {
+            Report::error("invalid token").with_label((*location, *location + 1), None)
+        }
+        ParseError::UnrecognizedEOF { location, expected } => {
+            let report =
+                Report::error("unexpected end of file").with_label((*location, *location), None);
+            add_expected(report, expected)
+        }
+        ParseError::UnrecognizedToken {
+            token: (begin, token, end),
+            expected,
+        } => {
+            let msg = format!("unexpected token `{}`", token.1);
+            let report = Report::error(msg).with_label((*begin, *end), None);
+            add_expected(report, expected)
+        }
+        ParseError::ExtraToken {
+            token: (begin, token, end),
+        } => {
+            let msg = format!("extra token `{}`", token.1);
+            Report::error(msg).with_label((*begin, *end), None)
+        }
+        ParseError::User { error } => user_error_report(error),
+    }
+}
+
+fn user_error_report(err: &UserError) -> Report {
+    match err {
+        UserError::Transition(err) => transition_error_report(err),
+        UserError::Declaration(err) => declaration_error_report(err),
+    }
+}
+
+fn transition_error_report(err: &TransitionFactoryError) -> Report {
+    let loc = err.get_location();
+    match err.get_error_type() {
+        TransitionFactoryErrorType::DuplicatedKey => Report::error("duplicated transition key")
+            .with_label(loc, Some("this key is already set".to_owned())),
+        TransitionFactoryErrorType::MissingSourceOrDestination => {
+            Report::error("transition without source or destination")
+                .with_label(loc, Some("both `src` and `dst` are required".to_owned()))
+        }
+        TransitionFactoryErrorType::InvalidProbability => {
+            Report::error("invalid transition probability")
+                .with_label(loc, Some("expected a positive number".to_owned()))
+        }
+        TransitionFactoryErrorType::InvalidCost => Report::error("invalid transition cost")
+            .with_label(loc, Some("expected a non negative integer".to_owned())),
+    }
+}
+
+fn declaration_error_report(err: &DeclarationError) -> Report {
+    let loc = err.get_location();
+    match err.get_error_type() {
+        DeclarationErrorType::InvalidCapacity => Report::error("invalid link capacity")
+            .with_label(loc, Some("expected a positive integer".to_owned())),
+        DeclarationErrorType::InvalidStrategy => Report::error("unknown elimination strategy")
+            .with_label(
+                loc,
+                Some(format!(
+                    "expected one of {}",
+                    EliminationOrder::NAMES.join(", ")
+                )),
+            ),
+        DeclarationErrorType::InvalidLength => Report::error("invalid witness length")
+            .with_label(loc, Some("expected a non negative integer".to_owned())),
+        DeclarationErrorType::InvalidCount => Report::error("invalid number of diagnoses")
+            .with_label(loc, Some("expected a positive integer".to_owned())),
+        DeclarationErrorType::InvalidCriterion => Report::error("unknown final state criterion")
+            .with_label(
+                loc,
+                Some(format!(
+                    "expected one of {}",
+                    FinalCriterion::NAMES.join(", ")
+                )),
+            ),
+    }
+}
+
+fn add_expected(report: Report, expected: &[String]) -> Report {
+    let expected = translate_expected(expected);
+    match expected.len() {
+        0 => report,
+        1 => report.with_note(format!("expected {}", expected[0])),
+        _ => report.with_note(format!("expected one of {}", expected.join(", "))),
+    }
+}
+
+/*
+    LALRPOP reports the expected terminals
+    as they are written in the grammar: keywords are quoted
+    and names are the regular expression used by the lexer.
+*/
+fn translate_expected(expected: &[String]) -> Vec<String> {
+    let mut output: Vec<String> = Vec::with_capacity(expected.len());
+    for exp in expected {
+        let exp = translate_terminal(exp);
+        if !output.contains(&exp) {
+            output.push(exp);
+        }
+    }
+    output
+}
+
+fn translate_terminal(terminal: &str) -> String {
+    if terminal.starts_with("r#") {
+        "name".to_owned()
+    } else {
+        format!("`{}`", terminal.trim_matches('"'))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::SourceMap;
+    use super::*;
+    use fsa_net_parser::parse;
+
+    #[test]
+    fn test_unrecognized_token() {
+        let code = "network A {\n    automata B {\n        begin s0\n        stat s1\n    }\n}";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        let output = report.render(&src);
+        let expected = "error: unexpected token `stat`\n \
+                        --> test.fnl:4:9\n  \
+                        |\n\
+                        4 |         stat s1\n  \
+                        |         ^^^^\n  \
+                        = expected one of `begin`, `final`, `state`, `trans`, `}`\n";
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_unexpected_eof() {
+        let code = "network A {\n    link L1 A B\n";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        assert!(report
+            .render(&src)
+            .starts_with("error: unexpected end of file\n --> test.fnl:2:16\n"));
+    }
+
+    #[test]
+    fn test_transition_error() {
+        let code =
+            "network A {\n automata B {\n  trans t {\n   src a\n   src b\n   dst c\n  }\n }\n}";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        assert!(report
+            .render(&src)
+            .starts_with("error: duplicated transition key\n --> test.fnl:5:4\n"));
+
+        let code = "network A {\n automata B {\n  trans t {\n   src a\n  }\n }\n}";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        assert!(report
+            .render(&src)
+            .starts_with("error: transition without source or destination\n --> test.fnl:3:3\n"));
+    }
+
+    #[test]
+    fn test_declaration_error() {
+        let code = "network A {\n link L A B capacity 0\n}";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        assert!(report
+            .render(&src)
+            .starts_with("error: invalid link capacity\n --> test.fnl:2:13\n"));
+
+        let code = "network A { events e }\nrequest A {\n diagnosis o strategy fast\n}";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        assert!(report
+            .render(&src)
+            .starts_with("error: unknown elimination strategy\n --> test.fnl:3:14\n"));
+
+        let code = "network A { events e }\nrequest A {\n diagnosis o witness x\n}";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        assert!(report
+            .render(&src)
+            .starts_with("error: invalid witness length\n --> test.fnl:3:22\n"));
+
+        let code = "network A { events e }\nrequest A {\n diagnosis o likely 0\n}";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        assert!(report
+            .render(&src)
+            .starts_with("error: invalid number of diagnoses\n --> test.fnl:3:21\n"));
+
+        let code = "network A {\n events e\n final all\n}";
+        let err = parse(code).err().unwrap();
+        let report = syntax_error_report(&err);
+        let mut src = SourceMap::default();
+        src.add_file("test.fnl", code);
+        assert!(report
+            .render(&src)
+            .starts_with("error: unknown final state criterion\n --> test.fnl:3:2\n"));
+    }
+
+    #[test]
+    fn test_translate_expected() {
+        let expected = vec![
+            r###""state""###.to_owned(),
+            r###"r#"\"[^\"]+\""#"###.to_owned(),
+            r###"r#"'[^']+'"#"###.to_owned(),
+            r###"r#"[A-Za-z0-9]+"#"###.to_owned(),
+        ];
+        assert_eq!(translate_expected(&expected), vec!["`state`", "name"]);
+    }
+}
@@ -0,0 +1,150 @@
+use super::diagnosis::{partial_diagnosis, DiagnosisResult};
+use super::explanation::{best_explanations, Explanation, Score};
+use super::linspace::{compute_linear_space, LinSpaceResult};
+use super::EliminationOrder;
+use super::EngineConfig;
+use crate::command::ObsStep;
+use crate::graph;
+use crate::network;
+use std::cmp::Ordering;
+
+pub struct CheapestResult {
+    pub diagnosis: DiagnosisResult,
+    pub linspace: LinSpaceResult,
+    // sorted from the cheapest
+    pub explanations: Vec<Explanation<Cost>>,
+    pub timeout: bool,
+}
+
+impl From<CheapestResult> for super::NetworkResult {
+    fn from(result: CheapestResult) -> Self {
+        Self::Cheapest(result)
+    }
+}
+
+/*
+    Total cost of the transitions of a trajectory:
+    lower costs are better
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cost(pub usize);
+
+impl PartialOrd for Cost {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        other.0.partial_cmp(&self.0)
+    }
+}
+
+impl Score for Cost {
+    fn initial() -> Self {
+        Self(0)
+    }
+
+    fn extend(
+        self,
+        _: &graph::Graph<network::TransEvent>,
+        _: usize,
+        ev: &network::TransEvent,
+    ) -> Self {
+        Self(self.0 + ev.get_cost())
+    }
+}
+
+pub fn compute_cheapest(
+    net: &network::Network,
+    obs_labels: &[ObsStep],
+    conf: &EngineConfig,
+    order: EliminationOrder,
+    count: usize,
+) -> CheapestResult {
+    let linspace = compute_linear_space(net, obs_labels, conf);
+    let diagnosis = partial_diagnosis(&linspace.graph, &linspace.frontier, conf, order);
+    let timer = conf.timer_factory.new_timer();
+    let (explanations, timeout) = best_explanations(&linspace.graph, count, &timer);
+    CheapestResult {
+        diagnosis,
+        linspace,
+        explanations,
+        timeout,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::timer::TimerFactory;
+
+    fn event(rel: Option<usize>, cost: Option<usize>) -> network::TransEvent {
+        network::TransEvent {
+            auto: 0,
+            trans: 0,
+            obs: None,
+            rel,
+            prob: None,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_cheapest() {
+        // 0 -f (5)-> 2, 0 -r (1)-> 1 -f (1)-> 2, 0 -(4)-> 2, node 2 is final
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_simple_node(1);
+        builder.add_final_node(2);
+        builder.add_arc(0, 2, event(Some(0), Some(5)));
+        builder.add_arc(0, 1, event(Some(1), Some(1)));
+        builder.add_arc(1, 2, event(Some(0), Some(1)));
+        builder.add_arc(0, 2, event(None, Some(4)));
+        let g = builder.build_graph();
+
+        let timer = TimerFactory::from_value(None).new_timer();
+        let (explanations, timeout) = best_explanations::<Cost>(&g, 3, &timer);
+        assert!(!timeout);
+        let found: Vec<(Vec<usize>, usize)> = explanations
+            .iter()
+            .map(|e| (e.labels.clone(), e.score.0))
+            .collect();
+        assert_eq!(found, vec![(vec![1, 0], 2), (vec![], 4), (vec![0], 5)]);
+        assert_eq!(explanations[0].nodes, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_cheapest_dead_end() {
+        // 0 -(1)-> 1, 0 -f-> 2 -f-> 2, node 1 is final
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_final_node(1);
+        builder.add_simple_node(2);
+        builder.add_arc(0, 1, event(None, Some(1)));
+        builder.add_arc(0, 2, event(Some(0), None));
+        builder.add_arc(2, 2, event(Some(0), None));
+        let g = builder.build_graph();
+
+        let timer = TimerFactory::from_value(None).new_timer();
+        let (explanations, timeout) = best_explanations::<Cost>(&g, 3, &timer);
+        assert!(!timeout);
+        assert_eq!(explanations.len(), 1);
+        assert!(explanations[0].labels.is_empty());
+    }
+
+    #[test]
+    fn test_cheapest_cycle() {
+        // 0 -f-> 1 -f-> 0, node 1 is final
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_final_node(1);
+        builder.add_arc(0, 1, event(Some(0), Some(1)));
+        builder.add_arc(1, 0, event(Some(0), Some(1)));
+        let g = builder.build_graph();
+
+        // f repeated any odd number of times is an explanation,
+        // only the strings up to the number of nodes are searched
+        let timer = TimerFactory::from_value(None).new_timer();
+        let (explanations, timeout) = best_explanations::<Cost>(&g, 3, &timer);
+        assert!(!timeout);
+        let labels: Vec<Vec<usize>> = explanations.iter().map(|e| e.labels.clone()).collect();
+        assert_eq!(labels, vec![vec![0]]);
+    }
+}
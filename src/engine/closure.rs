@@ -0,0 +1,241 @@
+use super::diagnosis::build_regex;
+use super::elimination::get_strategy;
+use super::full_space::{compute_full_space, FullSpaceResult};
+use super::EngineConfig;
+use super::Regex;
+use crate::graph;
+use crate::network;
+use crate::timer::Timer;
+
+use indexmap::IndexMap;
+use std::collections::VecDeque;
+
+/*
+    A silent closure is the sub graph of the behavioral
+    space reachable from an entry state through unobservable
+    transitions. Entries are the initial state and the targets
+    of the observable transitions: closures are numbered in
+    breadth first order starting from the initial one.
+    A state may belong to more than one closure.
+*/
+pub struct ClosureSpaceResult {
+    pub space: FullSpaceResult,
+    pub closures: Vec<Closure>,
+    pub complete: bool,
+}
+
+impl From<ClosureSpaceResult> for super::NetworkResult {
+    fn from(result: ClosureSpaceResult) -> Self {
+        Self::Closures(result)
+    }
+}
+
+pub struct Closure {
+    // states of the behavioral space,
+    // the entry is always the first one
+    pub states: Vec<usize>,
+    // unobservable transitions, as indexes in `states`
+    pub arcs: Vec<(usize, usize, network::TransEvent)>,
+    pub exits: Vec<ClosureExit>,
+}
+
+/*
+    An exit is a state of the closure that is
+    final or has some outgoing observable transition.
+    The regex describes the relevance labels of the
+    paths from the entry to this state.
+*/
+pub struct ClosureExit {
+    pub state: usize,
+    pub is_final: bool,
+    pub regex: Regex,
+    // observable transitions and the closure they lead to
+    pub arcs: Vec<(network::TransEvent, usize)>,
+}
+
+impl Closure {
+    pub fn get_entry(&self) -> usize {
+        self.states[0]
+    }
+}
+
+pub fn compute_closure_space(net: &network::Network, conf: &EngineConfig) -> ClosureSpaceResult {
+    let space = compute_full_space(net, conf);
+    let (closures, complete) = build_closures(&space.graph, conf);
+    ClosureSpaceResult {
+        complete: space.complete && complete,
+        space,
+        closures,
+    }
+}
+
+/**
+ * Split the graph into silent closures, return
+ * false if the decoration of some exit did not end
+ * before the timeout: in this case the list
+ * contains only the closures completed so far.
+ */
+pub fn build_closures(
+    graph: &graph::Graph<network::TransEvent>,
+    conf: &EngineConfig,
+) -> (Vec<Closure>, bool) {
+    let mut entries = IndexMap::new();
+    let mut queue = VecDeque::new();
+    if !graph.get_adjacent_list().is_empty() {
+        entries.insert(0, 0);
+        queue.push_back(0);
+    }
+
+    let timer = conf.timer_factory.new_timer();
+    let mut output = vec![];
+    while let Some(entry) = queue.pop_front() {
+        let (states, arcs) = collect_closure(graph, entry);
+        let mut exits = vec![];
+        for (local, state) in states.iter().enumerate() {
+            let is_final = graph.get_node_kind_list()[*state] == graph::NodeKind::Final;
+            let observable: Vec<_> = graph.get_adjacent_list()[*state]
+                .iter()
+                .filter(|a| a.label.obs.is_some())
+                .collect();
+            if !is_final && observable.is_empty() {
+                continue;
+            }
+
+            let regex = match decorate(states.len(), &arcs, local, &timer, conf) {
+                Some(regex) => regex,
+                None => return (output, false),
+            };
+            let arcs = observable
+                .into_iter()
+                .map(|arc| {
+                    let next = if let Some(next) = entries.get(&arc.next) {
+                        *next
+                    } else {
+                        let next = entries.len();
+                        entries.insert(arc.next, next);
+                        queue.push_back(arc.next);
+                        next
+                    };
+                    (arc.label.clone(), next)
+                })
+                .collect();
+            exits.push(ClosureExit {
+                state: local,
+                is_final,
+                regex,
+                arcs,
+            });
+        }
+        output.push(Closure {
+            states,
+            arcs,
+            exits,
+        });
+    }
+    (output, true)
+}
+
+fn collect_closure(
+    graph: &graph::Graph<network::TransEvent>,
+    entry: usize,
+) -> (Vec<usize>, Vec<(usize, usize, network::TransEvent)>) {
+    let mut index = IndexMap::new();
+    let mut arcs = vec![];
+    index.insert(entry, ());
+    let mut src = 0;
+    while let Some((state, _)) = index.get_index(src) {
+        let silent = graph.get_adjacent_list()[*state]
+            .iter()
+            .filter(|a| a.label.obs.is_none());
+        for arc in silent {
+            let (dst, _) = index.insert_full(arc.next, ());
+            arcs.push((src, dst, arc.label.clone()));
+        }
+        src += 1;
+    }
+    (index.into_iter().map(|(s, _)| s).collect(), arcs)
+}
+
+/*
+    Run state elimination on the closure
+    with the exit as the only final state
+*/
+fn decorate(
+    state_count: usize,
+    arcs: &[(usize, usize, network::TransEvent)],
+    exit: usize,
+    timer: &Timer,
+    conf: &EngineConfig,
+) -> Option<Regex> {
+    let mut builder = graph::GraphBuilder::new();
+    for i in 0..state_count {
+        if i == exit {
+            builder.add_final_node(i);
+        } else {
+            builder.add_simple_node(i);
+        }
+    }
+    for (src, dst, ev) in arcs {
+        builder.add_arc(*src, *dst, ev.rel);
+    }
+    let strategy = get_strategy(conf.elimination);
+    let result = build_regex(&builder.build_graph(), &[], timer, strategy);
+    if result.timeout {
+        None
+    } else {
+        Some(result.regex.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::GraphMode;
+    use super::*;
+    use crate::compiler::compile;
+    use crate::timer;
+    use fsa_net_parser::parse;
+    use test_utils::load_code_from_file;
+
+    #[test]
+    fn test_closures() {
+        let src_code = load_code_from_file("simple-network");
+        let code = parse(&src_code).expect("`simple-network` should be syntactically correct");
+        let comp_res = compile(&code).expect("`simple-network` should be semantically correct");
+        let net = &comp_res.compile_network[0].net;
+        let config = EngineConfig::new(GraphMode::Prune, timer::TimerFactory::from_value(None));
+
+        let result = compute_closure_space(net, &config);
+        assert!(result.complete);
+        let entries: Vec<usize> = result.closures.iter().map(|c| c.get_entry()).collect();
+        assert_eq!(entries, vec![0, 1, 2, 8, 9, 11, 12]);
+
+        let closure = &result.closures[2];
+        assert_eq!(closure.states, vec![2, 3, 4, 7, 5, 0, 6]);
+        let graph = &result.space.graph;
+        for (src, dst, ev) in &closure.arcs {
+            assert_eq!(ev.obs, None);
+            let src = closure.states[*src];
+            let dst = closure.states[*dst];
+            assert!(graph.get_adjacent_list()[src]
+                .iter()
+                .any(|a| a.next == dst && a.label.obs.is_none()));
+        }
+
+        let exits: Vec<(usize, bool)> = closure
+            .exits
+            .iter()
+            .map(|e| (closure.states[e.state], e.is_final))
+            .collect();
+        assert_eq!(
+            exits,
+            vec![(3, true), (4, true), (7, false), (0, true), (6, true)]
+        );
+        assert_eq!(closure.exits[0].regex, Regex::default());
+        assert_eq!(closure.exits[1].regex, Regex::Value(vec![1]));
+
+        let (ev, next) = &closure.exits[2].arcs[0];
+        assert_eq!(ev.obs, Some(1));
+        assert_eq!(result.closures[*next].get_entry(), 9);
+    }
+}
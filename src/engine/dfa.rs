@@ -0,0 +1,305 @@
+use super::diagnosis::DiagnosisResult;
+use super::positions::{PositionAutomaton, PositionBuilder, Positions};
+use super::Regex;
+use std::collections::{HashMap, VecDeque};
+
+/*
+    Membership of the relevance strings in the diagnosis,
+    unknown when the diagnosis did not complete in time
+*/
+pub struct CheckResult {
+    pub diagnosis: DiagnosisResult,
+    pub strings: Vec<Vec<usize>>,
+    pub accepted: Option<Vec<bool>>,
+}
+
+impl From<CheckResult> for super::NetworkResult {
+    fn from(result: CheckResult) -> Self {
+        Self::Check(result)
+    }
+}
+
+pub fn check_strings(diagnosis: DiagnosisResult, strings: &[Vec<usize>]) -> CheckResult {
+    let accepted = if diagnosis.timeout {
+        None
+    } else {
+        let dfa = Dfa::new(diagnosis.matrix.as_ref());
+        Some(strings.iter().map(|s| dfa.accepts(s)).collect())
+    };
+    CheckResult {
+        diagnosis,
+        strings: strings.to_vec(),
+        accepted,
+    }
+}
+
+/*
+    Minimal deterministic automaton over relevance
+    labels accepting the language of a diagnosis.
+    State 0 is the initial one, a missing transition
+    rejects the string: every state reaches a final one,
+    so there is no dead state. The only exception is the
+    empty language, made of the initial state alone.
+*/
+#[derive(Debug, PartialEq)]
+pub struct Dfa {
+    // (label, next) pairs sorted by label
+    adjacent: Vec<Vec<(usize, usize)>>,
+    finals: Vec<bool>,
+}
+
+impl Dfa {
+    /**
+     * Build the automaton of the regex, a missing regex
+     * is the empty language: no string explains the observation
+     */
+    pub fn new(regex: Option<&Regex>) -> Self {
+        let mut builder = PositionAutomaton::builder();
+        let pos = match regex {
+            Some(regex) => positions(&mut builder, regex),
+            None => Positions::none(),
+        };
+        let dfa = determinize(&builder.build(pos));
+        dfa.minimize()
+    }
+
+    pub fn accepts(&self, string: &[usize]) -> bool {
+        let mut state = 0;
+        for label in string {
+            match self.next(state, *label) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+        self.finals[state]
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.adjacent.len()
+    }
+
+    pub fn get_adjacent_list(&self) -> &[Vec<(usize, usize)>] {
+        &self.adjacent
+    }
+
+    pub fn is_final(&self, state: usize) -> bool {
+        self.finals[state]
+    }
+
+    fn next(&self, state: usize, label: usize) -> Option<usize> {
+        let adj = &self.adjacent[state];
+        adj.binary_search_by_key(&label, |(lbl, _)| *lbl)
+            .ok()
+            .map(|i| adj[i].1)
+    }
+
+    /*
+        Moore partition refinement: states are split by
+        their final flag and then by the classes reached
+        by each label until no class is split anymore.
+    */
+    fn minimize(self) -> Self {
+        let mut class: Vec<usize> = self.finals.iter().map(|f| *f as usize).collect();
+        let mut count = 0;
+        loop {
+            let mut signatures = HashMap::new();
+            let next_class: Vec<usize> = (0..self.adjacent.len())
+                .map(|state| {
+                    let trans: Vec<(usize, usize)> = self.adjacent[state]
+                        .iter()
+                        .map(|(lbl, next)| (*lbl, class[*next]))
+                        .collect();
+                    let len = signatures.len();
+                    *signatures.entry((class[state], trans)).or_insert(len)
+                })
+                .collect();
+            let next_count = signatures.len();
+            class = next_class;
+            if next_count == count {
+                break;
+            }
+            count = next_count;
+        }
+        self.merge(&class, count)
+    }
+
+    fn merge(self, class: &[usize], count: usize) -> Self {
+        // number the classes in visit order to keep the initial state first
+        let mut index = vec![None; count];
+        let mut queue = VecDeque::new();
+        let mut adjacent = vec![];
+        let mut finals = vec![];
+        index[class[0]] = Some(0);
+        queue.push_back(0);
+        while let Some(state) = queue.pop_front() {
+            let trans = self.adjacent[state]
+                .iter()
+                .map(|(lbl, next)| {
+                    let next_class = class[*next];
+                    let next_index = match index[next_class] {
+                        Some(i) => i,
+                        None => {
+                            let i = adjacent.len() + queue.len() + 1;
+                            index[next_class] = Some(i);
+                            queue.push_back(*next);
+                            i
+                        }
+                    };
+                    (*lbl, next_index)
+                })
+                .collect();
+            adjacent.push(trans);
+            finals.push(self.finals[state]);
+        }
+        Self { adjacent, finals }
+    }
+}
+
+/*
+    Subset construction on the position automaton:
+    the states following a subset are grouped by label
+*/
+fn determinize(positions: &PositionAutomaton<usize>) -> Dfa {
+    let mut index: HashMap<Vec<usize>, usize> = HashMap::new();
+    let mut subsets = vec![vec![0]];
+    index.insert(vec![0], 0);
+    let mut adjacent = vec![];
+    let mut finals = vec![];
+    let mut curr = 0;
+    while curr < subsets.len() {
+        let subset = subsets[curr].clone();
+        let mut moves: Vec<(usize, usize)> = subset
+            .iter()
+            .flat_map(|state| positions.follow(*state).iter())
+            .map(|next| (*positions.label(*next), *next))
+            .collect();
+        moves.sort_unstable();
+        moves.dedup();
+
+        let mut trans = vec![];
+        let mut begin = 0;
+        while begin < moves.len() {
+            let label = moves[begin].0;
+            let end = moves[begin..]
+                .iter()
+                .position(|(lbl, _)| *lbl != label)
+                .map_or(moves.len(), |e| begin + e);
+            let next: Vec<usize> = moves[begin..end].iter().map(|(_, n)| *n).collect();
+            let next_index = match index.get(&next) {
+                Some(i) => *i,
+                None => {
+                    let i = subsets.len();
+                    index.insert(next.clone(), i);
+                    subsets.push(next);
+                    i
+                }
+            };
+            trans.push((label, next_index));
+            begin = end;
+        }
+        adjacent.push(trans);
+        finals.push(subset.iter().any(|s| positions.is_final(*s)));
+        curr += 1;
+    }
+    Dfa { adjacent, finals }
+}
+
+fn positions(builder: &mut PositionBuilder<usize>, regex: &Regex) -> Positions {
+    match regex {
+        Regex::Value(labels) => {
+            let mut output = Positions::empty();
+            for label in labels {
+                let pos = builder.position(*label);
+                output = builder.concat(output, pos);
+            }
+            output
+        }
+        Regex::Chain(chain) => {
+            let mut output = Positions::empty();
+            for re in chain {
+                let pos = positions(builder, re);
+                output = builder.concat(output, pos);
+            }
+            output
+        }
+        Regex::Alternative(alt) => alt.iter().fold(Positions::none(), |output, re| {
+            output.union(positions(builder, re))
+        }),
+        Regex::Optional(re) => positions(builder, re).optional(),
+        Regex::ZeroMore(re) => {
+            let pos = positions(builder, re);
+            builder.star(pos)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_empty_string() {
+        let dfa = Dfa::new(Some(&Regex::default()));
+        assert_eq!(dfa.state_count(), 1);
+        assert!(dfa.accepts(&[]));
+        assert!(!dfa.accepts(&[0]));
+    }
+
+    #[test]
+    fn test_empty_language() {
+        // the observation has no explanation
+        let dfa = Dfa::new(None);
+        assert_eq!(dfa.state_count(), 1);
+        assert!(!dfa.is_final(0));
+        assert!(!dfa.accepts(&[]));
+        assert!(!dfa.accepts(&[0]));
+
+        let check = check_strings(super::super::diagnosis::fail_diagnosis(), &[vec![]]);
+        assert_eq!(check.accepted, Some(vec![false]));
+    }
+
+    #[test]
+    fn test_minimal_dfa() {
+        // (ab | b)* a b | (b)* a b
+        let star = Regex::ZeroMore(Box::new(Regex::Alternative(vec![
+            Regex::Value(vec![0, 1]),
+            Regex::Value(vec![1]),
+        ])));
+        let left = Regex::Chain(vec![star, Regex::Value(vec![0, 1])]);
+        let right = Regex::Chain(vec![
+            Regex::ZeroMore(Box::new(Regex::Value(vec![1]))),
+            Regex::Value(vec![0, 1]),
+        ]);
+        let regex = Regex::Alternative(vec![left, right]);
+        let dfa = Dfa::new(Some(&regex));
+
+        // the language is (a | b)* a b without aa: (ab | b)* ab
+        assert_eq!(dfa.state_count(), 3);
+        assert!(dfa.accepts(&[0, 1]));
+        assert!(dfa.accepts(&[1, 1, 0, 1, 0, 1]));
+        assert!(!dfa.accepts(&[0, 0, 1]));
+        assert!(!dfa.accepts(&[0, 1, 1]));
+        assert!(!dfa.accepts(&[]));
+        assert!(dfa.is_final(2));
+        assert_eq!(dfa.get_adjacent_list()[0], vec![(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn test_optional() {
+        // r (f)? r*
+        let regex = Regex::Chain(vec![
+            Regex::Value(vec![0]),
+            Regex::Optional(Box::new(Regex::Value(vec![1]))),
+            Regex::ZeroMore(Box::new(Regex::Value(vec![0]))),
+        ]);
+        let dfa = Dfa::new(Some(&regex));
+        assert_eq!(dfa.state_count(), 3);
+        assert!(dfa.accepts(&[0]));
+        assert!(dfa.accepts(&[0, 1]));
+        assert!(dfa.accepts(&[0, 1, 0, 0]));
+        assert!(dfa.accepts(&[0, 0]));
+        assert!(!dfa.accepts(&[0, 0, 1]));
+        assert!(!dfa.accepts(&[1]));
+    }
+}
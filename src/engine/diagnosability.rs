@@ -0,0 +1,406 @@
+use super::full_space::{compute_full_space, FullSpaceResult};
+use super::EngineConfig;
+use crate::graph;
+use crate::network;
+use crate::timer::Timer;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/*
+    A relevance label is diagnosable when every trajectory
+    containing it is eventually told apart, by its observation,
+    from all the trajectories without it. The twin plant pairs
+    two copies of the observable behaviour moving on the same
+    observable labels, each copy remembering whether the label
+    occurred: a cycle where only one copy saw the label is an
+    indeterminate cycle and makes the label not diagnosable.
+    As usual, cycles of unobservable transitions are ignored.
+*/
+pub struct DiagnosabilityResult {
+    pub space: FullSpaceResult,
+    // indexed by relevance label: labels after
+    // the last one never occur in the space
+    pub labels: Vec<LabelDiagnosability>,
+    pub complete: bool,
+}
+
+impl From<DiagnosabilityResult> for super::NetworkResult {
+    fn from(result: DiagnosabilityResult) -> Self {
+        Self::Diagnosability(result)
+    }
+}
+
+pub struct LabelDiagnosability {
+    // None when the analysis did not end before the timeout
+    pub diagnosable: Option<bool>,
+    pub counterexample: Option<Counterexample>,
+}
+
+/*
+    Two trajectories with the same observation, only the
+    faulty one contains the label: both repeat their cycle forever
+*/
+pub struct Counterexample {
+    pub faulty: Lasso,
+    pub correct: Lasso,
+}
+
+/*
+    Event i fires moving from nodes[i] to nodes[i + 1],
+    the events from `cycle` on lead back to nodes[cycle]
+*/
+pub struct Lasso {
+    pub nodes: Vec<usize>,
+    pub events: Vec<network::TransEvent>,
+    pub cycle: usize,
+}
+
+pub fn compute_diagnosability(net: &network::Network, conf: &EngineConfig) -> DiagnosabilityResult {
+    let space = compute_full_space(net, conf);
+    let timer = conf.timer_factory.new_timer();
+    let label_count = space
+        .graph
+        .get_adjacent_list()
+        .iter()
+        .flatten()
+        .filter_map(|arc| arc.label.rel)
+        .max()
+        .map_or(0, |l| l + 1);
+
+    let mut complete = space.complete;
+    let labels = (0..label_count)
+        .map(|label| match check_label(&space.graph, label, &timer) {
+            TwinResult::Counterexample(counterexample) => LabelDiagnosability {
+                diagnosable: Some(false),
+                counterexample: Some(counterexample),
+            },
+            TwinResult::Diagnosable => LabelDiagnosability {
+                diagnosable: if space.complete { Some(true) } else { None },
+                counterexample: None,
+            },
+            TwinResult::Timeout => {
+                complete = false;
+                LabelDiagnosability {
+                    diagnosable: None,
+                    counterexample: None,
+                }
+            }
+        })
+        .collect();
+
+    DiagnosabilityResult {
+        space,
+        labels,
+        complete,
+    }
+}
+
+enum TwinResult {
+    Diagnosable,
+    Counterexample(Counterexample),
+    Timeout,
+}
+
+// node of the space and whether the label occurred
+type Replica = (usize, bool);
+
+/*
+    Unobservable path followed by an observable
+    transition: arcs are (source node, index
+    in its adjacent list)
+*/
+struct ObsMove {
+    obs: usize,
+    next: Replica,
+    arcs: Vec<(usize, usize)>,
+}
+
+// (next pair, move of the first copy, move of the second one)
+type TwinArc = (usize, usize, usize);
+
+struct TwinPlant<'a> {
+    adj: &'a graph::AdjList<network::TransEvent>,
+    label: usize,
+    moves: HashMap<Replica, Vec<ObsMove>>,
+    pairs: Vec<(Replica, Replica)>,
+    // (previous pair, move of the first copy, move of the second one)
+    // reaching each pair in the breadth first visit
+    parents: Vec<Option<(usize, usize, usize)>>,
+    arcs: Vec<Vec<TwinArc>>,
+}
+
+fn check_label(g: &graph::Graph<network::TransEvent>, label: usize, timer: &Timer) -> TwinResult {
+    let adj = g.get_adjacent_list();
+    if adj.is_empty() {
+        return TwinResult::Diagnosable;
+    }
+    let mut twin = TwinPlant {
+        adj,
+        label,
+        moves: HashMap::new(),
+        pairs: vec![],
+        parents: vec![],
+        arcs: vec![],
+    };
+    if !twin.build(timer) {
+        return TwinResult::Timeout;
+    }
+    match twin.find_cycle() {
+        Some((pair, cycle)) => TwinResult::Counterexample(twin.counterexample(pair, &cycle)),
+        None => TwinResult::Diagnosable,
+    }
+}
+
+impl<'a> TwinPlant<'a> {
+    fn build(&mut self, timer: &Timer) -> bool {
+        let begin = ((0, false), (0, false));
+        let mut index = HashMap::new();
+        index.insert(begin, 0);
+        self.pairs.push(begin);
+        self.parents.push(None);
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+        while let Some(curr) = queue.pop_front() {
+            if timer.timeout() {
+                return false;
+            }
+            let (first, second) = self.pairs[curr];
+            self.cache_moves(first);
+            self.cache_moves(second);
+            let mut arcs = vec![];
+            for (i, j, pair) in self.synchronize(first, second) {
+                let next = match index.get(&pair) {
+                    Some(next) => *next,
+                    None => {
+                        let next = self.pairs.len();
+                        index.insert(pair, next);
+                        self.pairs.push(pair);
+                        self.parents.push(Some((curr, i, j)));
+                        queue.push_back(next);
+                        next
+                    }
+                };
+                arcs.push((next, i, j));
+            }
+            self.arcs.push(arcs);
+        }
+        true
+    }
+
+    // pairs of moves of the two copies on the same observable label
+    fn synchronize(
+        &self,
+        first: Replica,
+        second: Replica,
+    ) -> Vec<(usize, usize, (Replica, Replica))> {
+        let mut output = vec![];
+        for (i, m1) in self.moves[&first].iter().enumerate() {
+            for (j, m2) in self.moves[&second].iter().enumerate() {
+                if m1.obs == m2.obs {
+                    output.push((i, j, (m1.next, m2.next)));
+                }
+            }
+        }
+        output
+    }
+
+    fn cache_moves(&mut self, copy: Replica) {
+        if !self.moves.contains_key(&copy) {
+            let moves = observable_moves(self.adj, copy, self.label);
+            self.moves.insert(copy, moves);
+        }
+    }
+
+    fn is_indeterminate(&self, pair: usize) -> bool {
+        let ((_, first), (_, second)) = self.pairs[pair];
+        first && !second
+    }
+
+    /*
+        Depth first search restricted to the pairs where
+        only the first copy saw the label: a back arc closes
+        an indeterminate cycle, returned with its first pair
+    */
+    fn find_cycle(&self) -> Option<(usize, Vec<TwinArc>)> {
+        let mut color = vec![0u8; self.pairs.len()];
+        for root in (0..self.pairs.len()).filter(|p| self.is_indeterminate(*p)) {
+            if color[root] != 0 {
+                continue;
+            }
+            color[root] = 1;
+            let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+            let mut path: Vec<TwinArc> = vec![];
+            while let Some((pair, i)) = stack.pop() {
+                match self.arcs[pair].get(i) {
+                    Some(arc) if self.is_indeterminate(arc.0) => {
+                        stack.push((pair, i + 1));
+                        match color[arc.0] {
+                            0 => {
+                                color[arc.0] = 1;
+                                stack.push((arc.0, 0));
+                                path.push(*arc);
+                            }
+                            1 => {
+                                let begin = stack.iter().position(|(p, _)| *p == arc.0).unwrap();
+                                let mut cycle = path[begin..].to_vec();
+                                cycle.push(*arc);
+                                return Some((arc.0, cycle));
+                            }
+                            _ => {}
+                        }
+                    }
+                    Some(_) => stack.push((pair, i + 1)),
+                    None => {
+                        color[pair] = 2;
+                        path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn counterexample(&self, pair: usize, cycle: &[TwinArc]) -> Counterexample {
+        let mut prefix = vec![];
+        let mut curr = pair;
+        while let Some((prev, i, j)) = self.parents[curr] {
+            prefix.push((curr, i, j));
+            curr = prev;
+        }
+        prefix.reverse();
+
+        Counterexample {
+            faulty: self.lasso(&prefix, cycle, false),
+            correct: self.lasso(&prefix, cycle, true),
+        }
+    }
+
+    // the trajectory of the first or of the second copy
+    fn lasso(&self, prefix: &[TwinArc], cycle: &[TwinArc], second: bool) -> Lasso {
+        let mut lasso = Lasso {
+            nodes: vec![0],
+            events: vec![],
+            cycle: 0,
+        };
+        let mut curr = 0;
+        for (i, (next, m1, m2)) in prefix.iter().chain(cycle).enumerate() {
+            if i == prefix.len() {
+                lasso.cycle = lasso.events.len();
+            }
+            let (first_copy, second_copy) = self.pairs[curr];
+            let (copy, index) = if second {
+                (second_copy, *m2)
+            } else {
+                (first_copy, *m1)
+            };
+            for (src, arc) in &self.moves[&copy][index].arcs {
+                let arc = &self.adj[*src][*arc];
+                lasso.events.push(arc.label.clone());
+                lasso.nodes.push(arc.next);
+            }
+            curr = *next;
+        }
+        lasso
+    }
+}
+
+/*
+    Breadth first search through the unobservable transitions:
+    one move for each observable label and reached copy
+*/
+fn observable_moves(
+    adj: &graph::AdjList<network::TransEvent>,
+    begin: Replica,
+    label: usize,
+) -> Vec<ObsMove> {
+    let mut visits: Vec<(Replica, Option<(usize, usize)>)> = vec![(begin, None)];
+    let mut visited = HashSet::new();
+    visited.insert(begin);
+    let mut moves: Vec<ObsMove> = vec![];
+    let mut curr = 0;
+    while curr < visits.len() {
+        let ((node, faulty), _) = visits[curr];
+        for (i, arc) in adj[node].iter().enumerate() {
+            let next = (arc.next, faulty || arc.label.rel == Some(label));
+            if let Some(obs) = arc.label.obs {
+                if !moves.iter().any(|m| m.obs == obs && m.next == next) {
+                    let mut arcs = silent_path(&visits, curr);
+                    arcs.push((node, i));
+                    moves.push(ObsMove { obs, next, arcs });
+                }
+            } else if visited.insert(next) {
+                visits.push((next, Some((curr, i))));
+            }
+        }
+        curr += 1;
+    }
+    moves
+}
+
+fn silent_path(visits: &[(Replica, Option<(usize, usize)>)], last: usize) -> Vec<(usize, usize)> {
+    let mut arcs = vec![];
+    let mut curr = last;
+    while let Some((prev, arc)) = visits[curr].1 {
+        arcs.push(((visits[prev].0).0, arc));
+        curr = prev;
+    }
+    arcs.reverse();
+    arcs
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::timer::TimerFactory;
+
+    fn event(obs: Option<usize>, rel: Option<usize>) -> network::TransEvent {
+        network::TransEvent {
+            auto: 0,
+            trans: 0,
+            obs,
+            rel,
+            prob: None,
+            cost: None,
+        }
+    }
+
+    fn build_graph(faulty_obs: usize) -> graph::Graph<network::TransEvent> {
+        // 0 -f-> 1, 0 -> 2, 1 loops on faulty_obs, 2 loops on a
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_simple_node(1);
+        builder.add_simple_node(2);
+        builder.add_arc(0, 1, event(None, Some(0)));
+        builder.add_arc(0, 2, event(None, None));
+        builder.add_arc(1, 1, event(Some(faulty_obs), None));
+        builder.add_arc(2, 2, event(Some(0), None));
+        builder.build_graph()
+    }
+
+    #[test]
+    fn test_not_diagnosable() {
+        let timer = TimerFactory::from_value(None).new_timer();
+        let counterexample = match check_label(&build_graph(0), 0, &timer) {
+            TwinResult::Counterexample(counterexample) => counterexample,
+            _ => panic!("expected a counterexample"),
+        };
+        let faulty = counterexample.faulty;
+        assert_eq!(faulty.nodes, vec![0, 1, 1, 1]);
+        assert_eq!(faulty.cycle, 2);
+        assert_eq!(faulty.events[0].rel, Some(0));
+        let correct = counterexample.correct;
+        assert_eq!(correct.nodes, vec![0, 2, 2, 2]);
+        assert_eq!(correct.cycle, 2);
+        assert!(correct.events.iter().all(|ev| ev.rel.is_none()));
+    }
+
+    #[test]
+    fn test_diagnosable() {
+        let timer = TimerFactory::from_value(None).new_timer();
+        match check_label(&build_graph(1), 0, &timer) {
+            TwinResult::Diagnosable => {}
+            _ => panic!("expected the label to be diagnosable"),
+        }
+    }
+}
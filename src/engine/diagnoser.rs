@@ -0,0 +1,252 @@
+use super::closure::{build_closures, Closure};
+use super::diagnosis::{fail_diagnosis, DiagnosisResult};
+use super::full_space::compute_full_space;
+use super::simplify::simplify;
+use super::EngineConfig;
+use super::Regex;
+use crate::network;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/*
+    The diagnoser has a state for each silent closure
+    of the behavioral space. Each arc carries the relevance
+    regex of the paths from the entry of the source closure
+    to the target closure, the last observable transition
+    included.
+*/
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Diagnoser {
+    states: Vec<DiagnoserState>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct DiagnoserState {
+    arcs: Vec<DiagnoserArc>,
+    // regex of the paths reaching a final state
+    // inside the closure, None if there is none
+    exit: Option<Regex>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct DiagnoserArc {
+    obs: usize,
+    next: usize,
+    regex: Regex,
+}
+
+/*
+    Diagnoser states reached by an observation, each one
+    with the relevance regex of the paths leading to it
+*/
+pub type Belief = Vec<(usize, Regex)>;
+
+pub struct DiagnoserResult {
+    pub diagnoser: Option<Diagnoser>,
+    pub complete: bool,
+}
+
+impl From<DiagnoserResult> for super::NetworkResult {
+    fn from(result: DiagnoserResult) -> Self {
+        Self::Diagnoser(result)
+    }
+}
+
+impl Diagnoser {
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn transition_count(&self) -> usize {
+        self.states.iter().map(|s| s.arcs.len()).sum()
+    }
+
+    pub fn save(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn load(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    /**
+     * Compute the diagnosis of the given observation
+     * walking the diagnoser, without exploring the network
+     */
+    pub fn diagnosis(&self, obs_labels: &[usize]) -> DiagnosisResult {
+        let mut belief = self.initial_belief();
+        for obs in obs_labels {
+            belief = self.step(&belief, *obs);
+        }
+        self.conclude(&belief)
+    }
+
+    pub fn initial_belief(&self) -> Belief {
+        if self.states.is_empty() {
+            vec![]
+        } else {
+            vec![(0, Regex::default())]
+        }
+    }
+
+    /**
+     * Follow the arcs labeled with the given observation,
+     * the result is empty when the observation is not
+     * consistent with the belief. The regexes are simplified
+     * at each step, so that they do not grow exponentially
+     * with the length of the observation
+     */
+    pub fn step(&self, belief: &[(usize, Regex)], obs: usize) -> Belief {
+        let mut next: IndexMap<usize, Vec<Regex>> = IndexMap::new();
+        for (state, regex) in belief {
+            for arc in self.states[*state].arcs.iter().filter(|a| a.obs == obs) {
+                let regex = chain(regex.clone(), arc.regex.clone());
+                next.entry(arc.next).or_default().push(regex);
+            }
+        }
+        next.into_iter()
+            .map(|(s, r)| (s, simplify(alternative(r))))
+            .collect()
+    }
+
+    pub fn conclude(&self, belief: &[(usize, Regex)]) -> DiagnosisResult {
+        let output: Vec<Regex> = belief
+            .iter()
+            .filter_map(|(state, regex)| {
+                let exit = self.states[*state].exit.as_ref()?;
+                Some(chain(regex.clone(), exit.clone()))
+            })
+            .collect();
+
+        if output.is_empty() {
+            fail_diagnosis()
+        } else {
+            DiagnosisResult {
+                matrix: Some(
+                    alternative(output)
+                        .fix_empty()
+                        .map(simplify)
+                        .unwrap_or_default(),
+                ),
+                complete: true,
+                timeout: false,
+                strategy: None,
+                faults: None,
+                stats: None,
+            }
+        }
+    }
+
+    pub fn from_closures(closures: Vec<Closure>) -> Self {
+        let states = closures.into_iter().map(DiagnoserState::new).collect();
+        Self { states }
+    }
+}
+
+pub fn build_diagnoser(net: &network::Network, conf: &EngineConfig) -> DiagnoserResult {
+    let space = compute_full_space(net, conf);
+    let (closures, complete) = build_closures(&space.graph, conf);
+    if !space.complete || !complete {
+        return DiagnoserResult {
+            diagnoser: None,
+            complete: false,
+        };
+    }
+
+    DiagnoserResult {
+        diagnoser: Some(Diagnoser::from_closures(closures)),
+        complete: true,
+    }
+}
+
+impl DiagnoserState {
+    fn new(closure: Closure) -> Self {
+        let mut arcs = vec![];
+        let mut exit = vec![];
+        for closure_exit in closure.exits {
+            for (ev, next) in closure_exit.arcs {
+                let rel = Regex::Value(ev.rel.into_iter().collect());
+                arcs.push(DiagnoserArc {
+                    obs: ev.obs.unwrap(),
+                    next,
+                    regex: chain(closure_exit.regex.clone(), rel),
+                });
+            }
+            if closure_exit.is_final {
+                exit.push(closure_exit.regex);
+            }
+        }
+
+        let exit = if exit.is_empty() {
+            None
+        } else {
+            Some(alternative(exit))
+        };
+        Self { arcs, exit }
+    }
+}
+
+fn chain(first: Regex, second: Regex) -> Regex {
+    Regex::Chain(vec![first, second])
+        .fix_empty()
+        .unwrap_or_default()
+}
+
+fn alternative(mut list: Vec<Regex>) -> Regex {
+    if list.len() == 1 {
+        list.pop().unwrap()
+    } else {
+        Regex::Alternative(list).fix_empty().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::GraphMode;
+    use super::*;
+    use crate::compiler::compile;
+    use crate::timer;
+    use fsa_net_parser::parse;
+    use test_utils::load_code_from_file;
+
+    #[test]
+    fn test_diagnoser() {
+        let src_code = load_code_from_file("simple-network");
+        let code = parse(&src_code).expect("`simple-network` should be syntactically correct");
+        let comp_res = compile(&code).expect("`simple-network` should be semantically correct");
+        let net = &comp_res.compile_network[0].net;
+        let config = EngineConfig::new(GraphMode::Prune, timer::TimerFactory::from_value(None));
+
+        let result = build_diagnoser(net, &config);
+        assert!(result.complete);
+        let diagnoser = result.diagnoser.unwrap();
+        assert_eq!(diagnoser.state_count(), 7);
+        assert_eq!(diagnoser.transition_count(), 12);
+
+        let regex = diagnoser.diagnosis(&[1, 0]).matrix.unwrap();
+        // (f(r(f)?)?)?
+        let expected = Regex::Optional(Box::new(Regex::Chain(vec![
+            Regex::Value(vec![1]),
+            Regex::Optional(Box::new(Regex::Chain(vec![
+                Regex::Value(vec![0]),
+                Regex::Optional(Box::new(Regex::Value(vec![1]))),
+            ]))),
+        ])));
+        assert_eq!(regex, expected);
+
+        // the empty observation ends in the initial state
+        let regex = diagnoser.diagnosis(&[]).matrix;
+        assert_eq!(regex, Some(Regex::default()));
+
+        // the observation cannot be produced by the network
+        let result = diagnoser.diagnosis(&[0]);
+        assert!(result.matrix.is_none() && result.complete && !result.timeout);
+        let result = diagnoser.diagnosis(&[1]);
+        assert!(result.matrix.is_none());
+
+        let loaded = Diagnoser::load(&diagnoser.save()).unwrap();
+        assert_eq!(loaded, diagnoser);
+    }
+}
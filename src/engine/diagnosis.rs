@@ -1,5 +1,9 @@
+use super::elimination::{build_node_cost, get_strategy, EliminationStrategy};
+use super::faults::{classify_faults, FaultOccurrence};
+use super::simplify::simplify;
+use super::EliminationOrder;
 use super::EngineConfig;
-use super::Regex;
+use super::{Regex, FRONTIER};
 use crate::enumerate;
 use crate::graph;
 use crate::network::TransEvent;
@@ -8,56 +12,123 @@ use crate::utils::{clear, zeros};
 use std::collections::HashSet;
 
 pub struct DiagnosisResult {
+    // None if no string explains the observation
     pub matrix: Option<Regex>,
     pub complete: bool,
     pub timeout: bool,
+    // elimination order, None if no state elimination took place
+    pub strategy: Option<EliminationOrder>,
+    // occurrences of each relevance label, None without a graph
+    pub faults: Option<Vec<FaultOccurrence>>,
+    // progress of the state elimination, None without a graph
+    pub stats: Option<DiagnosisStats>,
 }
 
-pub fn diagnosis<T>(g: &graph::Graph<T>, conf: &EngineConfig) -> DiagnosisResult
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DiagnosisStats {
+    // nodes of the explored space
+    pub nodes: usize,
+    // nodes whose successors were not explored
+    pub frontier: usize,
+    // nodes removed by the state elimination
+    pub eliminated: usize,
+}
+
+pub fn diagnosis_with<T>(
+    g: &graph::Graph<T>,
+    conf: &EngineConfig,
+    order: EliminationOrder,
+) -> DiagnosisResult
+where
+    T: AsLabel,
+{
+    partial_diagnosis(g, &[], conf, order)
+}
+
+/**
+ * Diagnosis of a space whose exploration stopped at the
+ * `frontier` nodes: the strings leaving the explored part
+ * end with the FRONTIER label. The same happens to the nodes
+ * not yet eliminated when the state elimination times out.
+ */
+pub fn partial_diagnosis<T>(
+    g: &graph::Graph<T>,
+    frontier: &[usize],
+    conf: &EngineConfig,
+    order: EliminationOrder,
+) -> DiagnosisResult
 where
     T: AsLabel,
 {
     let node_count = g.get_adjacent_list().len();
-    if node_count == 0 {
-        empty_diagnosis()
+    let result = if node_count == 0 {
+        empty_diagnosis(order)
     } else {
-        real_diagnosis(g, conf)
-    }
+        real_diagnosis(g, frontier, conf, order)
+    };
+    let faults = if frontier.is_empty() {
+        Some(classify_faults(g))
+    } else {
+        None
+    };
+    DiagnosisResult { faults, ..result }
 }
 
-fn real_diagnosis<T>(g: &graph::Graph<T>, conf: &EngineConfig) -> DiagnosisResult
+fn real_diagnosis<T>(
+    g: &graph::Graph<T>,
+    frontier: &[usize],
+    conf: &EngineConfig,
+    order: EliminationOrder,
+) -> DiagnosisResult
 where
     T: AsLabel,
 {
-    let regex = build_regex(g, conf);
+    let timer = conf.timer_factory.new_timer();
+    let result = build_regex(g, frontier, &timer, get_strategy(order));
+    let timeout = result.timeout || !frontier.is_empty();
+    let stats = DiagnosisStats {
+        nodes: g.get_adjacent_list().len(),
+        frontier: frontier.len(),
+        eliminated: result.eliminated,
+    };
 
-    match regex {
-        BuildResult::Regex(regex) => DiagnosisResult {
-            matrix: regex,
-            complete: true,
-            timeout: false,
-        },
-        BuildResult::Timeout => DiagnosisResult {
-            matrix: None,
-            complete: false,
-            timeout: true,
-        },
+    DiagnosisResult {
+        matrix: result.regex,
+        complete: !timeout,
+        timeout,
+        strategy: Some(order),
+        faults: None,
+        stats: Some(stats),
     }
 }
 
-fn empty_diagnosis() -> DiagnosisResult {
+fn empty_diagnosis(order: EliminationOrder) -> DiagnosisResult {
     DiagnosisResult {
-        matrix: Some(Regex::default()),
+        matrix: None,
         complete: true,
         timeout: false,
+        strategy: Some(order),
+        faults: None,
+        stats: Some(DiagnosisStats {
+            nodes: 0,
+            frontier: 0,
+            eliminated: 0,
+        }),
     }
 }
 
+/*
+    Diagnosis of an observation that no
+    trajectory of the network can produce
+*/
 pub fn fail_diagnosis() -> DiagnosisResult {
     DiagnosisResult {
         matrix: None,
         complete: true,
         timeout: false,
+        strategy: None,
+        faults: None,
+        stats: None,
     }
 }
 
@@ -98,11 +169,23 @@ fn lbl_to_regex<T: AsLabel>(t: &T) -> Regex {
     Regex::Value(vect)
 }
 
-fn build_regex<T: AsLabel>(g: &graph::Graph<T>, conf: &EngineConfig) -> BuildResult {
+/**
+ * State elimination alone, without the rest of the
+ * diagnosis: the timer may be shared by many calls
+ */
+pub fn build_regex<T: AsLabel>(
+    g: &graph::Graph<T>,
+    frontier: &[usize],
+    timer: &Timer,
+    strategy: &dyn EliminationStrategy,
+) -> BuildResult {
     let mut g = g.convert(lbl_to_regex).add_fake_nodes();
-    let timer = conf.timer_factory.new_timer();
+    let node_count = g.get_node_kind_list().len();
+    for node in frontier {
+        g = g.add_arc(node + 1, node_count - 1, Regex::Value(vec![FRONTIER]));
+    }
     let mut timeout = false;
-    while continue_process(&g, &mut timeout, &timer) {
+    while continue_process(&g, &mut timeout, timer) {
         let node_count = g.get_node_kind_list().len();
         let trans_count = build_in_out_count(g.get_adjacent_list(), node_count);
         if let Some(chain) = find_chain(g.get_adjacent_list(), &trans_count) {
@@ -110,22 +193,52 @@ fn build_regex<T: AsLabel>(g: &graph::Graph<T>, conf: &EngineConfig) -> BuildRes
         } else if let Some(parallel) = find_parallel(g.get_adjacent_list()) {
             g = apply_parallel(parallel, g);
         } else {
-            g = process_best_node(g, &trans_count);
+            g = process_best_node(g, &trans_count, strategy);
         }
     }
-    if timeout {
-        BuildResult::Timeout
+    let eliminated = node_count - g.get_node_kind_list().len();
+    let regex = if timeout {
+        partial_regex(&g)
     } else {
-        let output = g.remove_arc(0, 1).pop().unwrap().label;
-
-        let regex = output.fix_empty();
-        BuildResult::Regex(regex)
+        let last = g.get_node_kind_list().len() - 1;
+        g.remove_arc(0, last).pop().map(|arc| arc.label)
+    };
+    BuildResult {
+        regex: regex.map(|regex| regex.fix_empty().map(simplify).unwrap_or_default()),
+        timeout,
+        eliminated,
     }
 }
 
-enum BuildResult {
-    Timeout,
-    Regex(Option<Regex>),
+pub struct BuildResult {
+    // None if the final node is not reachable
+    pub regex: Option<Regex>,
+    pub timeout: bool,
+    pub eliminated: usize,
+}
+
+/*
+    Strings from the initial node: the arcs
+    reaching an inner node lead to a part of
+    the space not yet eliminated.
+*/
+fn partial_regex(g: &graph::Graph<Regex>) -> Option<Regex> {
+    let last = g.get_node_kind_list().len() - 1;
+    let arcs = &g.get_adjacent_list()[0];
+    if arcs.is_empty() {
+        return None;
+    }
+    let alt = arcs
+        .iter()
+        .map(|arc| {
+            if arc.next == last {
+                arc.label.clone()
+            } else {
+                Regex::Chain(vec![arc.label.clone(), Regex::Value(vec![FRONTIER])])
+            }
+        })
+        .collect();
+    Some(Regex::Alternative(alt))
 }
 
 fn continue_process<T>(g: &graph::Graph<T>, timeout: &mut bool, timer: &Timer) -> bool {
@@ -137,8 +250,13 @@ fn continue_process<T>(g: &graph::Graph<T>, timeout: &mut bool, timer: &Timer) -
     }
 }
 
-fn process_best_node(mut g: graph::Graph<Regex>, count: &[TransCount]) -> graph::Graph<Regex> {
-    let best = guess_best(&count);
+fn process_best_node(
+    mut g: graph::Graph<Regex>,
+    count: &[TransCount],
+    strategy: &dyn EliminationStrategy,
+) -> graph::Graph<Regex> {
+    let adj = g.get_adjacent_list();
+    let best = strategy.choose(adj, &build_node_cost(adj));
 
     let adj = &g.get_adjacent_list()[best];
     let best_count = &count[best];
@@ -183,14 +301,6 @@ fn find_auto_trans(g: &mut graph::Graph<Regex>, node: usize) -> Option<Regex> {
     }
 }
 
-fn guess_best(trans_count: &[TransCount]) -> usize {
-    let index = enumerate!(trans_count[1..trans_count.len() - 1])
-        .max_by_key(|(_, tc)| tc.incoming + tc.outcoming)
-        .unwrap()
-        .0;
-    index + 1
-}
-
 fn apply_chain(mut chain: Vec<usize>, mut g: graph::Graph<Regex>) -> graph::Graph<Regex> {
     let first = *chain.first().unwrap();
     let last = *chain.last().unwrap();
@@ -387,22 +497,89 @@ mod test {
 
         let graph = builder.build_graph();
         let config = EngineConfig::new(GraphMode::Full, timer::TimerFactory::from_value(None));
-        let regex = diagnosis(&graph, &config).matrix.unwrap();
-        let expected = Regex::Chain(vec![Regex::Optional(Box::new(Regex::Chain(vec![
-            Regex::Chain(vec![
-                Regex::Value(vec![0]),
-                Regex::Optional(Box::new(Regex::Chain(vec![Regex::Chain(vec![
-                    Regex::Value(vec![1]),
-                    Regex::Optional(Box::new(Regex::Chain(vec![Regex::Chain(vec![
-                        Regex::Value(vec![0]),
-                    ])]))),
-                ])]))),
-            ]),
-        ])))]);
+        let regex = diagnosis_with(&graph, &config, EliminationOrder::default())
+            .matrix
+            .unwrap();
+        let expected = Regex::Optional(Box::new(Regex::Chain(vec![
+            Regex::Value(vec![0]),
+            Regex::Optional(Box::new(Regex::Chain(vec![
+                Regex::Value(vec![1]),
+                Regex::Optional(Box::new(Regex::Value(vec![0]))),
+            ]))),
+        ])));
 
         assert_eq!(expected, regex);
     }
 
+    #[test]
+    fn test_partial_diagnosis() {
+        // 0 -f-> 1 -r-> 2, node 2 is final, node 1 was not expanded
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_simple_node(1);
+        builder.add_final_node(2);
+        builder.add_arc(0, 1, Some(0));
+        builder.add_arc(0, 2, None);
+        builder.add_arc(1, 2, Some(1));
+
+        let graph = builder.build_graph();
+        let config = EngineConfig::new(GraphMode::Full, timer::TimerFactory::from_value(None));
+        let result = partial_diagnosis(&graph, &[1], &config, EliminationOrder::default());
+        assert!(!result.complete && result.timeout);
+        assert!(result.faults.is_none());
+        let stats = DiagnosisStats {
+            nodes: 3,
+            frontier: 1,
+            eliminated: 3,
+        };
+        assert_eq!(result.stats, Some(stats));
+
+        let dfa = super::super::Dfa::new(result.matrix.as_ref());
+        assert!(dfa.accepts(&[]));
+        assert!(dfa.accepts(&[0, 1]));
+        assert!(dfa.accepts(&[0, FRONTIER]));
+        assert!(!dfa.accepts(&[0]));
+    }
+
+    #[test]
+    fn test_no_explanation() {
+        // 0 -f-> 1, no final node
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_simple_node(1);
+        builder.add_arc(0, 1, Some(0));
+        let config = EngineConfig::new(GraphMode::Full, timer::TimerFactory::from_value(None));
+        let result = diagnosis_with(&builder.build_graph(), &config, EliminationOrder::default());
+        assert!(result.matrix.is_none() && result.complete);
+
+        // the initial node is the final one
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_final_node(0);
+        builder.add_simple_node(1);
+        builder.add_arc(0, 1, Some(0));
+        let result = diagnosis_with(&builder.build_graph(), &config, EliminationOrder::default());
+        assert_eq!(result.matrix, Some(Regex::default()));
+    }
+
+    #[test]
+    fn test_partial_regex() {
+        // the initial node reaches the final one and an inner node
+        let mut builder = graph::GraphBuilder::new();
+        for i in 0..3 {
+            builder.add_simple_node(i);
+        }
+        builder.add_arc(0, 2, Regex::Value(vec![0]));
+        builder.add_arc(0, 1, Regex::Value(vec![1]));
+        builder.add_arc(1, 2, Regex::Value(vec![0]));
+
+        let regex = partial_regex(&builder.build_graph()).unwrap();
+        let expected = Regex::Alternative(vec![
+            Regex::Value(vec![0]),
+            Regex::Chain(vec![Regex::Value(vec![1]), Regex::Value(vec![FRONTIER])]),
+        ]);
+        assert_eq!(regex, expected);
+    }
+
     #[test]
     fn test_chain() {
         let graph = build_sample_graph();
@@ -0,0 +1,199 @@
+use super::Regex;
+use crate::enumerate;
+use crate::graph;
+use crate::utils::zeros;
+use super::EliminationOrder;
+
+/*
+    Cost of removing a node of the graph during
+    state elimination: degrees count self loops
+    on both sides, sizes are the ones of the regexes
+    on the arcs that are not self loops
+*/
+#[derive(Default, Debug, PartialEq)]
+pub struct NodeCost {
+    pub incoming: usize,
+    pub outcoming: usize,
+    pub loops: usize,
+    in_size: usize,
+    out_size: usize,
+    loop_size: usize,
+}
+
+impl NodeCost {
+    /**
+     * Number of arcs added by the removal of the node
+     */
+    pub fn product(&self) -> usize {
+        (self.incoming - self.loops) * (self.outcoming - self.loops)
+    }
+
+    /**
+     * Total size of the regexes added
+     * by the removal of the node
+     */
+    pub fn size(&self) -> usize {
+        let incoming = self.incoming - self.loops;
+        let outcoming = self.outcoming - self.loops;
+        self.in_size * outcoming + self.out_size * incoming + self.loop_size * self.product()
+    }
+}
+
+pub fn build_node_cost(g: &graph::AdjList<Regex>) -> Vec<NodeCost> {
+    let mut output: Vec<NodeCost> = zeros(g.len());
+    for (src, adj) in enumerate! {g} {
+        for arc in adj {
+            let size = arc.label.size();
+            output[src].outcoming += 1;
+            output[arc.next].incoming += 1;
+            if arc.next == src {
+                output[src].loops += 1;
+                output[src].loop_size += size;
+            } else {
+                output[src].out_size += size;
+                output[arc.next].in_size += size;
+            }
+        }
+    }
+    output
+}
+
+pub trait EliminationStrategy {
+    /**
+     * Choose the node to remove among the inner ones:
+     * the first and the last node are never removed
+     */
+    fn choose(&self, g: &graph::AdjList<Regex>, cost: &[NodeCost]) -> usize;
+}
+
+pub fn get_strategy(order: EliminationOrder) -> &'static dyn EliminationStrategy {
+    match order {
+        EliminationOrder::MaxDegree => &MaxDegree,
+        EliminationOrder::MinProduct => &MinProduct,
+        EliminationOrder::MinSize => &MinSize,
+        EliminationOrder::FewestLoops => &FewestLoops,
+        EliminationOrder::Topological => &Topological,
+    }
+}
+
+fn inner_nodes(cost: &[NodeCost]) -> impl Iterator<Item = (usize, &NodeCost)> {
+    enumerate!(cost[1..cost.len() - 1]).map(|(i, c)| (i + 1, c))
+}
+
+pub struct MaxDegree;
+
+impl EliminationStrategy for MaxDegree {
+    fn choose(&self, _: &graph::AdjList<Regex>, cost: &[NodeCost]) -> usize {
+        inner_nodes(cost)
+            .max_by_key(|(_, c)| c.incoming + c.outcoming)
+            .unwrap()
+            .0
+    }
+}
+
+pub struct MinProduct;
+
+impl EliminationStrategy for MinProduct {
+    fn choose(&self, _: &graph::AdjList<Regex>, cost: &[NodeCost]) -> usize {
+        inner_nodes(cost).min_by_key(|(_, c)| c.product()).unwrap().0
+    }
+}
+
+pub struct MinSize;
+
+impl EliminationStrategy for MinSize {
+    fn choose(&self, _: &graph::AdjList<Regex>, cost: &[NodeCost]) -> usize {
+        inner_nodes(cost).min_by_key(|(_, c)| c.size()).unwrap().0
+    }
+}
+
+pub struct FewestLoops;
+
+impl EliminationStrategy for FewestLoops {
+    fn choose(&self, _: &graph::AdjList<Regex>, cost: &[NodeCost]) -> usize {
+        inner_nodes(cost)
+            .min_by_key(|(_, c)| (c.loops, c.product()))
+            .unwrap()
+            .0
+    }
+}
+
+/*
+    Remove the nodes in topological order: the first
+    inner node whose only predecessor, self loops apart,
+    is the initial one. When every inner node lies on or
+    after a cycle, the oldest one in exploration order
+    is removed, that is the first inner node.
+*/
+pub struct Topological;
+
+impl EliminationStrategy for Topological {
+    fn choose(&self, g: &graph::AdjList<Regex>, _: &[NodeCost]) -> usize {
+        let last = g.len() - 1;
+        let mut has_inner_pred = vec![false; g.len()];
+        for (src, adj) in enumerate!(g[1..last]) {
+            for arc in adj.iter().filter(|arc| arc.next != src + 1) {
+                has_inner_pred[arc.next] = true;
+            }
+        }
+        (1..last).find(|node| !has_inner_pred[*node]).unwrap_or(1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_node_cost() {
+        let mut builder = graph::GraphBuilder::new();
+        for i in 0..4 {
+            builder.add_simple_node(i);
+        }
+        let arcs = [(0, 1, 1), (1, 1, 2), (1, 2, 1), (1, 3, 1), (0, 2, 4), (2, 3, 2)];
+        for (s, d, l) in &arcs {
+            builder.add_arc(*s, *d, Regex::Value(vec![0; *l]));
+        }
+        let g = builder.build_graph();
+        let cost = build_node_cost(g.get_adjacent_list());
+
+        assert_eq!(cost[1].incoming, 2);
+        assert_eq!(cost[1].outcoming, 3);
+        assert_eq!(cost[1].product(), 2);
+        // 1 * 2 + 2 * 1 + 2 * 2
+        assert_eq!(cost[1].size(), 8);
+        assert_eq!(cost[2].product(), 2);
+        assert_eq!(cost[2].size(), 9);
+
+        let adj = g.get_adjacent_list();
+        assert_eq!(MaxDegree.choose(adj, &cost), 1);
+        assert_eq!(MinSize.choose(adj, &cost), 1);
+        assert_eq!(FewestLoops.choose(adj, &cost), 2);
+        assert_eq!(Topological.choose(adj, &cost), 1);
+    }
+
+    #[test]
+    fn test_topological() {
+        // 0 -> 2 -> 1 -> 3: node 2 comes first, not the oldest one
+        let g = build_graph(4, &[(0, 2), (2, 1), (1, 3)]);
+        let cost = build_node_cost(g.get_adjacent_list());
+        assert_eq!(Topological.choose(g.get_adjacent_list(), &cost), 2);
+
+        // 0 -> 2 -> 1 -> 2 -> 3: every inner node is on the cycle
+        let g = build_graph(4, &[(0, 2), (2, 1), (1, 2), (2, 3)]);
+        let cost = build_node_cost(g.get_adjacent_list());
+        assert_eq!(Topological.choose(g.get_adjacent_list(), &cost), 1);
+    }
+
+    fn build_graph(nodes: usize, arcs: &[(usize, usize)]) -> graph::Graph<Regex> {
+        let mut builder = graph::GraphBuilder::new();
+        for i in 0..nodes {
+            builder.add_simple_node(i);
+        }
+        for (s, d) in arcs {
+            builder.add_arc(*s, *d, Regex::Value(vec![0]));
+        }
+        builder.build_graph()
+    }
+}
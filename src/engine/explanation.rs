@@ -0,0 +1,192 @@
+use super::faults::useful_nodes;
+use crate::graph;
+use crate::network;
+use crate::timer::Timer;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+// explanations computed when the request does not give a count
+pub const DEFAULT_COUNT: usize = 1;
+
+/*
+    A relevance string with the score of its best trajectory
+    in the linspace, from the initial node to a final one:
+    event i fires moving from nodes[i] to nodes[i + 1]
+*/
+pub struct Explanation<S> {
+    pub labels: Vec<usize>,
+    pub score: S,
+    pub nodes: Vec<usize>,
+    pub events: Vec<network::TransEvent>,
+}
+
+/*
+    How good a trajectory is: greater is better
+    and extending a trajectory never improves it
+*/
+pub trait Score: Copy + PartialOrd {
+    fn initial() -> Self;
+
+    // the score after firing `ev` from `node`
+    fn extend(
+        self,
+        g: &graph::Graph<network::TransEvent>,
+        node: usize,
+        ev: &network::TransEvent,
+    ) -> Self;
+}
+
+struct Visit<S> {
+    node: usize,
+    labels: Vec<usize>,
+    score: S,
+    // (previous visit, arc index)
+    parent: Option<(usize, usize)>,
+    done: bool,
+}
+
+struct Candidate<S> {
+    score: S,
+    visit: usize,
+}
+
+impl<S: PartialOrd> PartialEq for Candidate<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S: PartialOrd> Eq for Candidate<S> {}
+
+impl<S: PartialOrd> Ord for Candidate<S> {
+    // scores are never NaN, ties go to the older visit
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .partial_cmp(&other.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.visit.cmp(&self.visit))
+    }
+}
+
+impl<S: PartialOrd> PartialOrd for Candidate<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/*
+    Dijkstra on the product of the graph with the labels
+    produced so far: scores only get worse along a path, so
+    the first time a final node is settled with some labels
+    gives the best trajectory producing them. Stops after
+    `count` explanations, sorted from the best one.
+    Only nodes reaching a final one are visited, and the
+    strings are at most as long as the number of nodes:
+    the longer ones repeat a cycle, and searching them
+    would never end when there are fewer than `count`.
+*/
+pub fn best_explanations<S: Score>(
+    g: &graph::Graph<network::TransEvent>,
+    count: usize,
+    timer: &Timer,
+) -> (Vec<Explanation<S>>, bool) {
+    let adj = g.get_adjacent_list();
+    if adj.is_empty() {
+        return (vec![], false);
+    }
+    let kinds = g.get_node_kind_list();
+    let finals: Vec<bool> = kinds.iter().map(|k| *k == graph::NodeKind::Final).collect();
+    let useful = useful_nodes(adj, &finals);
+    if !useful[0] {
+        return (vec![], false);
+    }
+
+    let mut visits = vec![Visit {
+        node: 0,
+        labels: vec![],
+        score: S::initial(),
+        parent: None,
+        done: false,
+    }];
+    let mut index = HashMap::new();
+    index.insert((0, vec![]), 0);
+    let mut queue = BinaryHeap::new();
+    queue.push(Candidate {
+        score: S::initial(),
+        visit: 0,
+    });
+    let mut found = HashSet::new();
+    let mut explanations = vec![];
+    let mut timeout = false;
+    while let Some(Candidate { visit: curr, .. }) = queue.pop() {
+        if timer.timeout() {
+            timeout = true;
+            break;
+        }
+        if visits[curr].done {
+            continue;
+        }
+        visits[curr].done = true;
+        let node = visits[curr].node;
+        if kinds[node] == graph::NodeKind::Final && found.insert(visits[curr].labels.clone()) {
+            explanations.push(build_explanation(&visits, curr, adj));
+            if explanations.len() == count {
+                break;
+            }
+        }
+        for (i, arc) in adj[node].iter().enumerate() {
+            let mut labels = visits[curr].labels.clone();
+            labels.extend(arc.label.rel);
+            if !useful[arc.next] || labels.len() > adj.len() {
+                continue;
+            }
+            let score = visits[curr].score.extend(g, node, &arc.label);
+            let key = (arc.next, labels);
+            let next = match index.get(&key) {
+                Some(next) if visits[*next].done || visits[*next].score >= score => continue,
+                Some(next) => {
+                    visits[*next].score = score;
+                    visits[*next].parent = Some((curr, i));
+                    *next
+                }
+                None => {
+                    index.insert(key.clone(), visits.len());
+                    visits.push(Visit {
+                        node: key.0,
+                        labels: key.1,
+                        score,
+                        parent: Some((curr, i)),
+                        done: false,
+                    });
+                    visits.len() - 1
+                }
+            };
+            queue.push(Candidate { score, visit: next });
+        }
+    }
+    (explanations, timeout)
+}
+
+fn build_explanation<S: Copy>(
+    visits: &[Visit<S>],
+    last: usize,
+    adj: &graph::AdjList<network::TransEvent>,
+) -> Explanation<S> {
+    let mut nodes = vec![visits[last].node];
+    let mut events = vec![];
+    let mut curr = last;
+    while let Some((prev, arc)) = visits[curr].parent {
+        let src = visits[prev].node;
+        events.push(adj[src][arc].label.clone());
+        nodes.push(src);
+        curr = prev;
+    }
+    nodes.reverse();
+    events.reverse();
+    Explanation {
+        labels: visits[last].labels.clone(),
+        score: visits[last].score,
+        nodes,
+        events,
+    }
+}
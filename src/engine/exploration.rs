@@ -0,0 +1,223 @@
+use crate::graph;
+use crate::network::{State, TransEvent};
+use crate::state_table::{ShardedStateTable, StateTable};
+use crate::timer::Timer;
+
+use rayon::prelude::*;
+use std::collections::VecDeque;
+
+use super::engine_utils::{get_next_index, get_next_state};
+use super::EngineConfig;
+
+pub struct Exploration {
+    pub builder: graph::GraphBuilder<TransEvent>,
+    pub table: StateTable<State>,
+    pub complete: bool,
+    // states found but not expanded before the timeout
+    pub frontier: Vec<usize>,
+}
+
+/**
+ * Explore all the states reachable from `begin`.
+ * `step` returns the successors of a state, `is_final`
+ * tells whether a state is a final one.
+ * The result does not depend on the number of threads.
+ */
+pub fn explore<S, F>(begin: State, conf: &EngineConfig, step: S, is_final: F) -> Exploration
+where
+    S: Fn(&State) -> Vec<(TransEvent, State)> + Sync,
+    F: Fn(&State) -> bool,
+{
+    let timer = conf.timer_factory.new_timer();
+    if conf.threads > 1 {
+        let space = explore_parallel(begin, conf.threads, &timer, &step);
+        replay(space, is_final)
+    } else {
+        explore_sequential(begin, &timer, step, is_final)
+    }
+}
+
+fn explore_sequential<S, F>(begin: State, timer: &Timer, step: S, is_final: F) -> Exploration
+where
+    S: Fn(&State) -> Vec<(TransEvent, State)>,
+    F: Fn(&State) -> bool,
+{
+    let mut builder = graph::GraphBuilder::new();
+    let mut table = StateTable::new();
+    let mut stack = VecDeque::new();
+    let begin_index = table.insert_state(begin);
+    stack.push_front(begin_index);
+    let mut timeout = false;
+    while let Some(state_index) = get_next_state(&mut stack, timer, &mut timeout) {
+        let curr_state = table.get_object(state_index);
+        add_node(&mut builder, state_index, curr_state, &is_final);
+
+        for (ev, next_state) in step(curr_state) {
+            let next_index = get_next_index(next_state, &mut table, &mut stack);
+            builder.add_arc(state_index, next_index, ev);
+        }
+    }
+
+    let frontier: Vec<usize> = stack.into_iter().collect();
+    for state_index in &frontier {
+        add_node(
+            &mut builder,
+            *state_index,
+            table.get_object(*state_index),
+            &is_final,
+        );
+    }
+    Exploration {
+        builder,
+        table,
+        complete: !timeout,
+        frontier,
+    }
+}
+
+fn add_node<F>(
+    builder: &mut graph::GraphBuilder<TransEvent>,
+    index: usize,
+    state: &State,
+    is_final: F,
+) where
+    F: Fn(&State) -> bool,
+{
+    if is_final(state) {
+        builder.add_final_node(index);
+    } else {
+        builder.add_simple_node(index);
+    }
+}
+
+/*
+    States are indexed in discovery order,
+    which depends on thread scheduling.
+    A state without successors list was not
+    expanded before the timeout.
+*/
+struct ParallelSpace {
+    states: Vec<Option<State>>,
+    successors: Vec<Option<Vec<(TransEvent, usize)>>>,
+    complete: bool,
+}
+
+/*
+    Breadth first exploration: the states of each
+    level are expanded in parallel by the rayon
+    work stealing pool, new states are shared
+    through a sharded state table.
+*/
+fn explore_parallel<S>(begin: State, threads: usize, timer: &Timer, step: &S) -> ParallelSpace
+where
+    S: Fn(&State) -> Vec<(TransEvent, State)> + Sync,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    let table = ShardedStateTable::new(threads * 4);
+    let mut space = ParallelSpace {
+        states: vec![],
+        successors: vec![],
+        complete: true,
+    };
+
+    let (begin_index, _) = table.insert_state(&begin);
+    let mut frontier = vec![(begin_index, begin)];
+    while !frontier.is_empty() {
+        if timer.timeout() {
+            space.complete = false;
+            for (index, state) in frontier {
+                space.set_state(index, state);
+            }
+            break;
+        }
+
+        let expanded: Vec<_> = pool.install(|| {
+            frontier
+                .into_par_iter()
+                .map(|(index, state)| {
+                    let mut successors = vec![];
+                    let mut discovered = vec![];
+                    for (ev, next) in step(&state) {
+                        let (next_index, is_new) = table.insert_state(&next);
+                        if is_new {
+                            discovered.push((next_index, next));
+                        }
+                        successors.push((ev, next_index));
+                    }
+                    (index, state, successors, discovered)
+                })
+                .collect()
+        });
+
+        frontier = vec![];
+        for (index, state, successors, mut discovered) in expanded {
+            space.set_state(index, state);
+            space.successors[index] = Some(successors);
+            frontier.append(&mut discovered);
+        }
+    }
+    space
+}
+
+impl ParallelSpace {
+    fn set_state(&mut self, index: usize, state: State) {
+        if self.states.len() <= index {
+            self.states.resize_with(index + 1, || None);
+            self.successors.resize_with(index + 1, || None);
+        }
+        self.states[index] = Some(state);
+    }
+}
+
+/*
+    Walk the explored space in the same order
+    of the sequential exploration: in this way
+    each state gets the same index it would get
+    running on a single thread.
+*/
+fn replay<F>(mut space: ParallelSpace, is_final: F) -> Exploration
+where
+    F: Fn(&State) -> bool,
+{
+    let mut builder = graph::GraphBuilder::new();
+    let mut table = StateTable::new();
+    let mut remap: Vec<Option<usize>> = vec![None; space.states.len()];
+    let mut stack = VecDeque::new();
+    let mut frontier = vec![];
+
+    remap[0] = Some(table.insert_state(space.states[0].take().unwrap()));
+    stack.push_front(0);
+    while let Some(old_index) = stack.pop_front() {
+        let index = remap[old_index].unwrap();
+        add_node(&mut builder, index, table.get_object(index), &is_final);
+
+        let successors = match space.successors[old_index].take() {
+            Some(successors) => successors,
+            None => {
+                frontier.push(index);
+                continue;
+            }
+        };
+        for (ev, next) in successors {
+            let next_index = if let Some(next_index) = remap[next] {
+                next_index
+            } else {
+                let next_index = table.insert_state(space.states[next].take().unwrap());
+                remap[next] = Some(next_index);
+                stack.push_front(next);
+                next_index
+            };
+            builder.add_arc(index, next_index, ev);
+        }
+    }
+
+    Exploration {
+        builder,
+        table,
+        complete: space.complete,
+        frontier,
+    }
+}
@@ -0,0 +1,319 @@
+use super::diagnosis::AsLabel;
+use crate::graph;
+use std::collections::VecDeque;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FaultClass {
+    Certain,
+    Possible,
+    Excluded,
+}
+
+/*
+    How many times a relevance label occurs on the
+    paths from the initial node to a final node,
+    max is None when a cycle makes it unbounded
+*/
+#[derive(Debug, PartialEq, Clone)]
+pub struct FaultOccurrence {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl FaultOccurrence {
+    pub fn excluded() -> Self {
+        Self {
+            min: 0,
+            max: Some(0),
+        }
+    }
+
+    pub fn class(&self) -> FaultClass {
+        if self.min > 0 {
+            FaultClass::Certain
+        } else if self.max == Some(0) {
+            FaultClass::Excluded
+        } else {
+            FaultClass::Possible
+        }
+    }
+}
+
+/**
+ * Occurrences of each relevance label, indexed by label:
+ * labels after the last one in the list are excluded
+ */
+pub fn classify_faults<T: AsLabel>(g: &graph::Graph<T>) -> Vec<FaultOccurrence> {
+    let adj = g.get_adjacent_list();
+    if adj.is_empty() {
+        return vec![];
+    }
+    let finals: Vec<bool> = g
+        .get_node_kind_list()
+        .iter()
+        .map(|k| *k == graph::NodeKind::Final)
+        .collect();
+    let useful = useful_nodes(adj, &finals);
+    if !useful[0] {
+        return vec![];
+    }
+
+    let arcs: Vec<Vec<(usize, Option<usize>)>> = adj
+        .iter()
+        .enumerate()
+        .map(|(src, list)| {
+            if useful[src] {
+                list.iter()
+                    .filter(|a| useful[a.next])
+                    .map(|a| (a.next, a.label.get_label()))
+                    .collect()
+            } else {
+                vec![]
+            }
+        })
+        .collect();
+    let label_count = arcs
+        .iter()
+        .flatten()
+        .filter_map(|(_, lbl)| *lbl)
+        .max()
+        .map_or(0, |l| l + 1);
+    let components = Components::new(&arcs);
+
+    (0..label_count)
+        .map(|label| {
+            if arcs.iter().flatten().any(|(_, lbl)| *lbl == Some(label)) {
+                FaultOccurrence {
+                    min: min_occurrence(&arcs, &finals, label),
+                    max: components.max_occurrence(&arcs, &finals, label),
+                }
+            } else {
+                FaultOccurrence::excluded()
+            }
+        })
+        .collect()
+}
+
+// nodes reachable from the initial one that reach a final one
+pub(super) fn useful_nodes<T>(adj: &graph::AdjList<T>, finals: &[bool]) -> Vec<bool> {
+    let mut reach = vec![false; adj.len()];
+    let mut stack = vec![0];
+    reach[0] = true;
+    while let Some(node) = stack.pop() {
+        for arc in &adj[node] {
+            if !reach[arc.next] {
+                reach[arc.next] = true;
+                stack.push(arc.next);
+            }
+        }
+    }
+
+    let mut reverse = vec![vec![]; adj.len()];
+    for (src, list) in adj.iter().enumerate() {
+        for arc in list {
+            reverse[arc.next].push(src);
+        }
+    }
+    let mut coreach = finals.to_vec();
+    let mut stack: Vec<usize> = (0..adj.len()).filter(|n| finals[*n]).collect();
+    while let Some(node) = stack.pop() {
+        for prev in &reverse[node] {
+            if !coreach[*prev] {
+                coreach[*prev] = true;
+                stack.push(*prev);
+            }
+        }
+    }
+    reach.iter().zip(coreach).map(|(r, c)| *r && c).collect()
+}
+
+// 0-1 BFS: arcs with the label weigh one, the others zero
+fn min_occurrence(arcs: &[Vec<(usize, Option<usize>)>], finals: &[bool], label: usize) -> usize {
+    let mut dist = vec![usize::MAX; arcs.len()];
+    let mut queue = VecDeque::new();
+    dist[0] = 0;
+    queue.push_back(0);
+    while let Some(node) = queue.pop_front() {
+        for (next, lbl) in &arcs[node] {
+            let weight = (*lbl == Some(label)) as usize;
+            if dist[node] + weight < dist[*next] {
+                dist[*next] = dist[node] + weight;
+                if weight == 0 {
+                    queue.push_front(*next);
+                } else {
+                    queue.push_back(*next);
+                }
+            }
+        }
+    }
+    (0..arcs.len())
+        .filter(|n| finals[*n])
+        .map(|n| dist[n])
+        .min()
+        .unwrap()
+}
+
+/*
+    Strongly connected components numbered in
+    topological order of the condensation (Kosaraju)
+*/
+struct Components {
+    component: Vec<usize>,
+    count: usize,
+}
+
+impl Components {
+    fn new(arcs: &[Vec<(usize, Option<usize>)>]) -> Self {
+        let mut order = Vec::with_capacity(arcs.len());
+        let mut visited = vec![false; arcs.len()];
+        for root in 0..arcs.len() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![(root, 0)];
+            while let Some((node, i)) = stack.pop() {
+                if let Some((next, _)) = arcs[node].get(i) {
+                    stack.push((node, i + 1));
+                    if !visited[*next] {
+                        visited[*next] = true;
+                        stack.push((*next, 0));
+                    }
+                } else {
+                    order.push(node);
+                }
+            }
+        }
+
+        let mut reverse = vec![vec![]; arcs.len()];
+        for (src, list) in arcs.iter().enumerate() {
+            for (next, _) in list {
+                reverse[*next].push(src);
+            }
+        }
+        let mut component = vec![usize::MAX; arcs.len()];
+        let mut count = 0;
+        for root in order.into_iter().rev() {
+            if component[root] != usize::MAX {
+                continue;
+            }
+            component[root] = count;
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                for prev in &reverse[node] {
+                    if component[*prev] == usize::MAX {
+                        component[*prev] = count;
+                        stack.push(*prev);
+                    }
+                }
+            }
+            count += 1;
+        }
+        Self { component, count }
+    }
+
+    // longest path on the condensation, the label on a cycle is unbounded
+    fn max_occurrence(
+        &self,
+        arcs: &[Vec<(usize, Option<usize>)>],
+        finals: &[bool],
+        label: usize,
+    ) -> Option<usize> {
+        let mut members = vec![vec![]; self.count];
+        for (node, comp) in self.component.iter().enumerate() {
+            members[*comp].push(node);
+        }
+        let mut best: Vec<Option<usize>> = vec![None; self.count];
+        best[self.component[0]] = Some(0);
+        for comp in 0..self.count {
+            for node in &members[comp] {
+                for (next, lbl) in &arcs[*node] {
+                    let weight = (*lbl == Some(label)) as usize;
+                    let next_comp = self.component[*next];
+                    if next_comp == comp {
+                        if weight > 0 {
+                            return None;
+                        }
+                    } else if let Some(curr) = best[comp] {
+                        let value = curr + weight;
+                        if best[next_comp].is_none_or(|b| b < value) {
+                            best[next_comp] = Some(value);
+                        }
+                    }
+                }
+            }
+        }
+        (0..arcs.len())
+            .filter(|n| finals[*n])
+            .filter_map(|n| best[self.component[n]])
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn build_graph(
+        nodes: usize,
+        finals: &[usize],
+        arcs: &[(usize, usize, Option<usize>)],
+    ) -> graph::Graph<Option<usize>> {
+        let mut builder = graph::GraphBuilder::new();
+        for i in 0..nodes {
+            if finals.contains(&i) {
+                builder.add_final_node(i);
+            } else {
+                builder.add_simple_node(i);
+            }
+        }
+        for (src, dst, lbl) in arcs {
+            builder.add_arc(*src, *dst, *lbl);
+        }
+        builder.build_graph()
+    }
+
+    #[test]
+    fn test_classify_faults() {
+        // 0 -f-> 1 -r-> 2 -r-> 1, 0 -n-> 3 -f-> 2, node 4 is unreachable
+        let g = build_graph(
+            5,
+            &[1, 2],
+            &[
+                (0, 1, Some(0)),
+                (1, 2, Some(1)),
+                (2, 1, Some(1)),
+                (0, 3, None),
+                (3, 2, Some(0)),
+                (4, 0, Some(2)),
+            ],
+        );
+        let faults = classify_faults(&g);
+        assert_eq!(faults.len(), 2);
+        assert_eq!((faults[0].min, faults[0].max), (1, Some(1)));
+        assert_eq!(faults[0].class(), FaultClass::Certain);
+        assert_eq!((faults[1].min, faults[1].max), (0, None));
+        assert_eq!(faults[1].class(), FaultClass::Possible);
+    }
+
+    #[test]
+    fn test_bounded_max() {
+        // 0 -f-> 1 -f-> 2, 0 -> 2, 1 -r-> 1, 2 -> 2
+        let g = build_graph(
+            3,
+            &[2],
+            &[
+                (0, 1, Some(0)),
+                (1, 2, Some(0)),
+                (0, 2, None),
+                (1, 1, Some(1)),
+                (2, 2, None),
+            ],
+        );
+        let faults = classify_faults(&g);
+        assert_eq!((faults[0].min, faults[0].max), (0, Some(2)));
+        assert_eq!((faults[1].min, faults[1].max), (0, None));
+        assert_eq!(FaultOccurrence::excluded().class(), FaultClass::Excluded);
+    }
+}
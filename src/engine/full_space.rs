@@ -1,47 +1,27 @@
 use crate::graph;
 use crate::network;
-use crate::state_table;
 
-use std::collections::VecDeque;
-
-use super::engine_utils::{get_next_index, get_next_state};
+use super::exploration::explore;
 use super::EngineConfig;
 
 pub struct FullSpaceResult {
     pub graph: graph::Graph<network::TransEvent>,
     pub states: Vec<network::State>,
+    pub layout: network::StateLayout,
     pub complete: bool,
 }
 
 pub fn compute_full_space(net: &network::Network, conf: &EngineConfig) -> FullSpaceResult {
-    let mut builder = graph::GraphBuilder::new();
-    let mut table = state_table::StateTable::new();
-    let mut stack = VecDeque::new();
-    let begin_state = net.get_initial_state();
-    let begin_index = table.insert_state(begin_state);
-    stack.push_front(begin_index);
-    let mut timeout = false;
-    let timer = conf.timer_factory.new_timer();
-    while let Some(state_index) = get_next_state(&mut stack, &timer, &mut timeout) {
-        let curr_state = table.get_object(state_index);
-        if curr_state.is_final() {
-            builder.add_final_node(state_index);
-        } else {
-            builder.add_simple_node(state_index);
-        }
-
-        let next_state = net.step_one(curr_state);
-        for (ev, next_state) in next_state.into_iter() {
-            let next_index = get_next_index(next_state, &mut table, &mut stack);
-            builder.add_arc(state_index, next_index, ev);
-        }
-    }
-    let (graph, states) = conf.mode.build_graph(builder, table);
+    let step = |state: &network::State| net.step_one(state);
+    let is_final = |state: &network::State| net.is_final(state);
+    let space = explore(net.get_initial_state(), conf, step, is_final);
+    let (graph, states) = conf.mode.build_graph(space.builder, space.table);
 
     FullSpaceResult {
         graph,
         states,
-        complete: !timeout,
+        layout: net.get_layout().clone(),
+        complete: space.complete,
     }
 }
 
@@ -95,4 +75,43 @@ mod test {
 
         assert_eq!(adjacent_list, &expected);
     }
+
+    #[test]
+    fn test_fifo_space() {
+        let src_code = load_code_from_file("fifo-network");
+        let code = parse(&src_code).expect("`fifo-network` should be syntactically correct");
+        let comp_res = compile(&code).expect("`fifo-network` should be semantically correct");
+        let net = &comp_res.compile_network[0].net;
+
+        let config = EngineConfig::new(GraphMode::Full, timer::TimerFactory::from_value(None));
+
+        let result = compute_full_space(net, &config);
+
+        // with capacity 2 the producer can emit both
+        // events before the consumer reads the first one
+        let adjacent_list = result.graph.get_adjacent_list();
+        assert_eq!(adjacent_list.len(), 6);
+        assert_eq!(adjacent_list[1], vec![2, 3]);
+    }
+
+    #[test]
+    fn test_parallel_space() {
+        for name in ["simple-network", "fifo-network"] {
+            let src_code = load_code_from_file(name);
+            let code = parse(&src_code).unwrap();
+            let comp_res = compile(&code).unwrap();
+            let net = &comp_res.compile_network[0].net;
+
+            let config = EngineConfig::new(GraphMode::Full, timer::TimerFactory::from_value(None));
+            let expected = compute_full_space(net, &config);
+            let result = compute_full_space(net, &config.set_threads(4));
+
+            assert_eq!(
+                result.graph.get_adjacent_list(),
+                expected.graph.get_adjacent_list()
+            );
+            assert_eq!(result.states, expected.states);
+            assert!(result.complete);
+        }
+    }
 }
@@ -0,0 +1,140 @@
+use super::diagnosis::{partial_diagnosis, DiagnosisResult};
+use super::explanation::{best_explanations, Explanation, Score};
+use super::linspace::{compute_linear_space, LinSpaceResult};
+use super::EliminationOrder;
+use super::EngineConfig;
+use crate::command::ObsStep;
+use crate::graph;
+use crate::network;
+
+pub struct LikelyResult {
+    pub diagnosis: DiagnosisResult,
+    pub linspace: LinSpaceResult,
+    // sorted from the most likely
+    pub explanations: Vec<Explanation<Probability>>,
+    pub timeout: bool,
+}
+
+impl From<LikelyResult> for super::NetworkResult {
+    fn from(result: LikelyResult) -> Self {
+        Self::Likely(result)
+    }
+}
+
+/*
+    Probability of a trajectory: the one of an arc is its
+    weight over the total weight of the arcs leaving its source
+*/
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Probability(pub f64);
+
+impl Score for Probability {
+    fn initial() -> Self {
+        Self(1.0)
+    }
+
+    fn extend(
+        self,
+        g: &graph::Graph<network::TransEvent>,
+        node: usize,
+        ev: &network::TransEvent,
+    ) -> Self {
+        let total: f64 = g.get_adjacent_list()[node]
+            .iter()
+            .map(|arc| arc.label.weight())
+            .sum();
+        Self(self.0 * ev.weight() / total)
+    }
+}
+
+pub fn compute_likely(
+    net: &network::Network,
+    obs_labels: &[ObsStep],
+    conf: &EngineConfig,
+    order: EliminationOrder,
+    count: usize,
+) -> LikelyResult {
+    let linspace = compute_linear_space(net, obs_labels, conf);
+    let diagnosis = partial_diagnosis(&linspace.graph, &linspace.frontier, conf, order);
+    let timer = conf.timer_factory.new_timer();
+    let (explanations, timeout) = best_explanations(&linspace.graph, count, &timer);
+    LikelyResult {
+        diagnosis,
+        linspace,
+        explanations,
+        timeout,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::timer::TimerFactory;
+
+    fn event(rel: Option<usize>, prob: Option<f64>) -> network::TransEvent {
+        network::TransEvent {
+            auto: 0,
+            trans: 0,
+            obs: None,
+            rel,
+            prob,
+            cost: None,
+        }
+    }
+
+    fn build_graph() -> graph::Graph<network::TransEvent> {
+        // 0 -f (3)-> 1 -r-> 2, 0 -> 2, 0 -f-> 2, node 2 is final
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_simple_node(1);
+        builder.add_final_node(2);
+        builder.add_arc(0, 1, event(Some(0), Some(3.0)));
+        builder.add_arc(1, 2, event(Some(1), None));
+        builder.add_arc(0, 2, event(None, None));
+        builder.add_arc(0, 2, event(Some(0), None));
+        builder.build_graph()
+    }
+
+    #[test]
+    fn test_most_likely() {
+        let timer = TimerFactory::from_value(None).new_timer();
+        let (explanations, timeout) = best_explanations::<Probability>(&build_graph(), 3, &timer);
+        assert!(!timeout);
+        let labels: Vec<Vec<usize>> = explanations.iter().map(|e| e.labels.clone()).collect();
+        assert_eq!(labels, vec![vec![0, 1], vec![], vec![0]]);
+        let probs: Vec<f64> = explanations.iter().map(|e| e.score.0).collect();
+        assert_eq!(probs, vec![0.6, 0.2, 0.2]);
+        assert_eq!(explanations[0].nodes, vec![0, 1, 2]);
+        assert_eq!(explanations[0].events.len(), 2);
+    }
+
+    #[test]
+    fn test_most_likely_count() {
+        let timer = TimerFactory::from_value(None).new_timer();
+        let (explanations, _) = best_explanations::<Probability>(&build_graph(), 1, &timer);
+        assert_eq!(explanations.len(), 1);
+        assert_eq!(explanations[0].labels, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_most_likely_dead_end() {
+        // 0 -> 1, 0 -f-> 2 -f-> 2, 2 -> 3, node 1 is final
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_final_node(1);
+        builder.add_simple_node(2);
+        builder.add_simple_node(3);
+        builder.add_arc(0, 1, event(None, None));
+        builder.add_arc(0, 2, event(Some(0), None));
+        builder.add_arc(2, 2, event(Some(0), None));
+        builder.add_arc(2, 3, event(None, None));
+        let g = builder.build_graph();
+
+        let timer = TimerFactory::from_value(None).new_timer();
+        let (explanations, timeout) = best_explanations::<Probability>(&g, 3, &timer);
+        assert!(!timeout);
+        assert_eq!(explanations.len(), 1);
+        assert_eq!(explanations[0].score, Probability(0.5));
+    }
+}
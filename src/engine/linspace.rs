@@ -1,60 +1,54 @@
+use crate::command::ObsStep;
 use crate::graph;
 use crate::network;
-use crate::state_table;
 
-use std::collections::VecDeque;
-
-use super::engine_utils::{get_next_index, get_next_state};
+use super::exploration::explore;
+use super::observation::ObsAutomaton;
 use super::EngineConfig;
 
 pub struct LinSpaceResult {
     pub graph: graph::Graph<network::TransEvent>,
     pub states: Vec<network::State>,
+    pub layout: network::StateLayout,
     pub complete: bool,
+    // nodes not expanded before the timeout
+    pub frontier: Vec<usize>,
 }
 
 pub fn compute_linear_space(
     net: &network::Network,
-    obs_labels: &[usize],
+    obs_labels: &[ObsStep],
     conf: &EngineConfig,
 ) -> LinSpaceResult {
-    let mut builder = graph::GraphBuilder::new();
-    let mut table = state_table::StateTable::new();
-    let mut stack = VecDeque::new();
-    let begin_state = net.get_initial_state();
-    let begin_index = table.insert_state(begin_state);
-    stack.push_front(begin_index);
-    let mut timeout = false;
-    let timer = conf.timer_factory.new_timer();
-    while let Some(state_index) = get_next_state(&mut stack, &timer, &mut timeout) {
-        let curr_state = table.get_object(state_index);
-        let obs_index = curr_state.get_index();
-        if curr_state.is_final() && curr_state.get_index() == obs_labels.len() {
-            builder.add_final_node(state_index);
-        } else {
-            builder.add_simple_node(state_index);
-        }
-        let next_state = net.step_one(curr_state);
-        for (event, next_state) in next_state.into_iter() {
-            if let Some(obs) = event.obs {
-                if obs_index < obs_labels.len() && obs == obs_labels[obs_index] {
-                    let next_state = next_state.set_index(obs_index + 1);
-                    let next_index = get_next_index(next_state, &mut table, &mut stack);
-
-                    builder.add_arc(state_index, next_index, event);
+    let observation = ObsAutomaton::new(obs_labels);
+    let step = |state: &network::State| {
+        let obs_index = state.get_index();
+        net.step_one(state)
+            .into_iter()
+            .flat_map(|(event, next_state)| {
+                if let Some(obs) = event.obs {
+                    observation
+                        .step(obs_index, obs)
+                        .map(|next| (event.clone(), next_state.clone().set_index(next)))
+                        .collect()
+                } else {
+                    vec![(event, next_state.set_index(obs_index))]
                 }
-            } else {
-                let next_state = next_state.set_index(obs_index);
-                let next_index = get_next_index(next_state, &mut table, &mut stack);
-                builder.add_arc(state_index, next_index, event);
-            }
-        }
-    }
-    let (graph, states) = conf.mode.build_graph(builder, table);
+            })
+            .collect()
+    };
+    let is_final =
+        |state: &network::State| net.is_final(state) && observation.is_final(state.get_index());
+    let space = explore(net.get_initial_state(), conf, step, is_final);
+    let (graph, states, frontier) =
+        conf.mode
+            .build_partial_graph(space.builder, space.table, space.frontier);
     LinSpaceResult {
         graph,
         states,
-        complete: !timeout,
+        layout: net.get_layout().clone(),
+        complete: space.complete,
+        frontier,
     }
 }
 
@@ -84,7 +78,7 @@ mod test {
         let comp_res = compile(&code).expect("`simple-network` should be semantically correct");
         let net = &comp_res.compile_network[0].net;
 
-        let obs_labels = [1, 0];
+        let obs_labels = [ObsStep::Label(1), ObsStep::Label(0)];
         let config = EngineConfig::new(GraphMode::Full, timer::TimerFactory::from_value(None));
 
         let linspace = compute_linear_space(&net, &obs_labels, &config);
@@ -127,4 +121,89 @@ mod test {
         let node_kind = graph.get_node_kind_list();
         assert_eq!(node_kind, &expect_kind);
     }
+
+    #[test]
+    fn test_parallel_linspace() {
+        let src_code = load_code_from_file("simple-network");
+        let code = parse(&src_code).expect("`simple-network` should be syntactically correct");
+        let comp_res = compile(&code).expect("`simple-network` should be semantically correct");
+        let net = &comp_res.compile_network[0].net;
+
+        let obs_labels = [ObsStep::Label(1), ObsStep::Label(0)];
+        let config = EngineConfig::new(GraphMode::Prune, timer::TimerFactory::from_value(None));
+        let expected = compute_linear_space(net, &obs_labels, &config);
+        let result = compute_linear_space(net, &obs_labels, &config.set_threads(3));
+
+        assert_eq!(
+            result.graph.get_adjacent_list(),
+            expected.graph.get_adjacent_list()
+        );
+        assert_eq!(result.states, expected.states);
+    }
+
+    #[test]
+    fn test_uncertain_linspace() {
+        let src_code = load_code_from_file("simple-network");
+        let code = parse(&src_code).expect("`simple-network` should be syntactically correct");
+        let comp_res = compile(&code).expect("`simple-network` should be semantically correct");
+        let net = &comp_res.compile_network[0].net;
+        let config = EngineConfig::new(GraphMode::Full, timer::TimerFactory::from_value(None));
+
+        let exact = [ObsStep::Label(1), ObsStep::Label(0)];
+        let expected = compute_linear_space(net, &exact, &config);
+        let obs_labels = [ObsStep::Set(vec![1]), ObsStep::Set(vec![0])];
+        let result = compute_linear_space(net, &obs_labels, &config);
+        assert_eq!(
+            result.graph.get_adjacent_list(),
+            expected.graph.get_adjacent_list()
+        );
+        assert_eq!(result.states, expected.states);
+
+        // the network can only produce o3, o2, o3, o2 ...
+        // so accepting any label gives the same space
+        let obs_labels = [ObsStep::Any, ObsStep::Set(vec![0, 1])];
+        let result = compute_linear_space(net, &obs_labels, &config);
+        assert_eq!(
+            result.graph.get_adjacent_list(),
+            expected.graph.get_adjacent_list()
+        );
+        assert_eq!(result.states, expected.states);
+
+        let obs_labels = [ObsStep::Set(vec![0]), ObsStep::Any];
+        let result = compute_linear_space(net, &obs_labels, &config);
+        let kinds = result.graph.get_node_kind_list();
+        assert!(kinds.iter().all(|k| *k == NodeKind::Simple));
+        assert!(result.states.iter().all(|s| s.get_index() == 0));
+    }
+
+    #[test]
+    fn test_pattern_linspace() {
+        let src_code = load_code_from_file("simple-network");
+        let code = parse(&src_code).expect("`simple-network` should be syntactically correct");
+        let comp_res = compile(&code).expect("`simple-network` should be semantically correct");
+        let net = &comp_res.compile_network[0].net;
+        let config = EngineConfig::new(GraphMode::Full, timer::TimerFactory::from_value(None));
+
+        let exact = [ObsStep::Label(1), ObsStep::Label(0)];
+        let expected = compute_linear_space(net, &exact, &config);
+
+        // (o3, o2)+
+        let group = ObsStep::Group(vec![exact.to_vec()]);
+        let obs_labels = [ObsStep::Plus(Box::new(group))];
+        let result = compute_linear_space(net, &obs_labels, &config);
+        assert!(result.complete);
+        assert!(result.states.len() > expected.states.len());
+        for state in &expected.states {
+            assert!(result.states.contains(state), "{:?}", state);
+        }
+
+        // the index is the state of the observation automaton
+        let kinds = result.graph.get_node_kind_list();
+        for (state, kind) in zip(&result.states, kinds) {
+            assert!(state.get_index() <= 2);
+            if *kind == NodeKind::Final {
+                assert_eq!(state.get_index(), 2);
+            }
+        }
+    }
 }
@@ -1,27 +1,62 @@
+mod cheapest;
+mod closure;
+mod dfa;
+mod diagnosability;
+mod diagnoser;
 mod diagnosis;
+mod elimination;
 mod engine_utils;
+mod explanation;
+mod exploration;
+mod faults;
 mod full_space;
+mod likely;
 mod linspace;
+mod monitor;
+mod observation;
+mod positions;
 mod run;
+mod simplify;
+mod witness;
 
-pub use diagnosis::DiagnosisResult;
+pub use cheapest::{CheapestResult, Cost};
+pub use closure::ClosureSpaceResult;
+pub use dfa::{CheckResult, Dfa};
+pub use diagnosability::{Counterexample, DiagnosabilityResult, Lasso};
+pub use diagnoser::{Diagnoser, DiagnoserResult};
+pub use diagnosis::{DiagnosisResult, DiagnosisStats};
+pub use faults::{FaultClass, FaultOccurrence};
 pub use full_space::FullSpaceResult;
+pub use explanation::Explanation;
+pub use likely::{LikelyResult, Probability};
 pub use linspace::LinSpaceResult;
+pub use monitor::Monitor;
 pub use run::run;
+pub use witness::{Witness, WitnessResult};
 
 use crate::graph;
 use crate::state_table;
 use crate::timer;
+use serde::{Deserialize, Serialize};
 
 pub enum NetworkResult {
     FullSpace(full_space::FullSpaceResult),
     Linspace(linspace::LinSpaceResult),
     Diagnosis(diagnosis::DiagnosisResult),
+    Diagnoser(diagnoser::DiagnoserResult),
+    Closures(closure::ClosureSpaceResult),
+    Check(dfa::CheckResult),
+    Witness(witness::WitnessResult),
+    Diagnosability(diagnosability::DiagnosabilityResult),
+    Likely(likely::LikelyResult),
+    Cheapest(cheapest::CheapestResult),
 }
 
 pub struct EngineConfig {
     mode: GraphMode,
     timer_factory: timer::TimerFactory,
+    threads: usize,
+    elimination: EliminationOrder,
 }
 
 impl EngineConfig {
@@ -29,8 +64,28 @@ impl EngineConfig {
         Self {
             mode,
             timer_factory: timer,
+            threads: 1,
+            elimination: EliminationOrder::default(),
         }
     }
+
+    /**
+     * Explore the behavioral space using the given
+     * number of threads: one means sequential exploration
+     */
+    pub fn set_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /**
+     * Elimination order used by the diagnosis
+     * requests that do not choose their own
+     */
+    pub fn set_elimination(mut self, elimination: EliminationOrder) -> Self {
+        self.elimination = elimination;
+        self
+    }
 }
 
 pub enum GraphMode {
@@ -38,6 +93,55 @@ pub enum GraphMode {
     Full,
 }
 
+/*
+    Heuristic choosing the next node removed
+    by state elimination during the diagnosis
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EliminationOrder {
+    #[default]
+    MaxDegree,
+    MinProduct,
+    MinSize,
+    FewestLoops,
+    Topological,
+}
+
+impl EliminationOrder {
+    pub const NAMES: [&'static str; 5] = ["degree", "product", "size", "loops", "topological"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "degree" => Some(Self::MaxDegree),
+            "product" => Some(Self::MinProduct),
+            "size" => Some(Self::MinSize),
+            "loops" => Some(Self::FewestLoops),
+            "topological" => Some(Self::Topological),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MaxDegree => "degree",
+            Self::MinProduct => "product",
+            Self::MinSize => "size",
+            Self::FewestLoops => "loops",
+            Self::Topological => "topological",
+        }
+    }
+}
+
+impl std::str::FromStr for EliminationOrder {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::from_name(name).ok_or_else(|| {
+            format!("unknown strategy `{}`, expected one of {}", name, Self::NAMES.join(", "))
+        })
+    }
+}
+
 impl GraphMode {
     pub fn from_flag(full: bool) -> Self {
         if full {
@@ -52,18 +156,42 @@ impl GraphMode {
         builder: graph::GraphBuilder<K>,
         table: state_table::StateTable<T>,
     ) -> (graph::Graph<K>, Vec<T>)
+    where
+        T: Eq + std::hash::Hash,
+    {
+        let (graph, states, _) = self.build_partial_graph(builder, table, vec![]);
+        (graph, states)
+    }
+
+    /*
+        Frontier nodes are kept when pruning:
+        their successors are unknown, so they could
+        still reach a final node.
+    */
+    fn build_partial_graph<T, K>(
+        &self,
+        builder: graph::GraphBuilder<K>,
+        table: state_table::StateTable<T>,
+        frontier: Vec<usize>,
+    ) -> (graph::Graph<K>, Vec<T>, Vec<usize>)
     where
         T: Eq + std::hash::Hash,
     {
         let stat_list = table.to_state_list();
         match self {
-            Self::Full => (builder.build_graph(), stat_list),
-            Self::Prune => builder.build_graph().prune(stat_list),
+            Self::Full => (builder.build_graph(), stat_list, frontier),
+            Self::Prune => builder.build_graph().prune_keeping(stat_list, &frontier),
         }
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
+/**
+ * Label standing for the unexplored part of the
+ * space in the regex of a partial diagnosis
+ */
+pub const FRONTIER: usize = usize::MAX;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Regex {
     Alternative(Vec<Regex>),
     ZeroMore(Box<Regex>),
@@ -79,11 +207,22 @@ impl Default for Regex {
 }
 
 impl Regex {
-    fn is_empty(&self) -> bool {
+    /**
+     * Number of labels and operators in the regex
+     */
+    pub fn size(&self) -> usize {
         match self {
             Self::Alternative(re) | Self::Chain(re) => {
-                re.iter().map(|r| r.is_empty()).find(|e| !e).is_some()
+                re.iter().map(|r| r.size()).sum::<usize>() + 1
             }
+            Self::Optional(re) | Self::ZeroMore(re) => re.size() + 1,
+            Self::Value(val) => val.len().max(1),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Alternative(re) | Self::Chain(re) => re.iter().all(|r| r.is_empty()),
             Self::Optional(opt) => opt.is_empty(),
             Self::Value(val) => val.len() == 0,
             Self::ZeroMore(zm) => zm.is_empty(),
@@ -156,3 +295,27 @@ impl Regex {
         out
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_fix_empty() {
+        // (ε|a)? keeps the non empty alternative
+        let regex = Regex::Optional(Box::new(Regex::Alternative(vec![
+            Regex::Value(vec![]),
+            Regex::Value(vec![0]),
+        ])));
+        assert!(!regex.is_empty());
+        let expected = Regex::Optional(Box::new(Regex::Optional(Box::new(Regex::Chain(vec![
+            Regex::Value(vec![0]),
+        ])))));
+        assert_eq!(regex.fix_empty(), Some(expected));
+
+        let regex = Regex::ZeroMore(Box::new(Regex::Chain(vec![Regex::Value(vec![])])));
+        assert!(regex.is_empty());
+        assert_eq!(regex.fix_empty(), None);
+    }
+}
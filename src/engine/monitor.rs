@@ -0,0 +1,149 @@
+use super::closure::build_closures;
+use super::diagnoser::{Belief, Diagnoser};
+use super::diagnosis::DiagnosisResult;
+use super::full_space::{compute_full_space, FullSpaceResult};
+use super::EngineConfig;
+use crate::graph;
+use crate::network;
+
+/*
+    Online diagnosis: the diagnoser is built once and
+    then walked one observation at a time. An inconsistent
+    observation leaves the belief unchanged, so that
+    monitoring can go on with the following ones.
+*/
+pub struct Monitor {
+    diagnoser: Diagnoser,
+    // behavioral space state entering each closure
+    entries: Vec<usize>,
+    space: FullSpaceResult,
+    belief: Belief,
+}
+
+impl Monitor {
+    /**
+     * Return None if the behavioral space could
+     * not be built before the timeout
+     */
+    pub fn new(net: &network::Network, conf: &EngineConfig) -> Option<Self> {
+        let space = compute_full_space(net, conf);
+        let (closures, complete) = build_closures(&space.graph, conf);
+        if !space.complete || !complete {
+            return None;
+        }
+
+        let entries = closures.iter().map(|c| c.get_entry()).collect();
+        let diagnoser = Diagnoser::from_closures(closures);
+        let belief = diagnoser.initial_belief();
+        Some(Self {
+            diagnoser,
+            entries,
+            space,
+            belief,
+        })
+    }
+
+    /**
+     * Update the belief with the given observation,
+     * return false if it is not consistent with the model
+     */
+    pub fn observe(&mut self, obs: usize) -> bool {
+        let next = self.diagnoser.step(&self.belief, obs);
+        if next.is_empty() {
+            false
+        } else {
+            self.belief = next;
+            true
+        }
+    }
+
+    pub fn diagnosis(&self) -> DiagnosisResult {
+        self.diagnoser.conclude(&self.belief)
+    }
+
+    /**
+     * Network states the belief is made of, that is
+     * the states reached by the last observable transition
+     */
+    pub fn get_states(&self) -> impl Iterator<Item = (&network::State, &graph::NodeKind)> + '_ {
+        let kinds = self.space.graph.get_node_kind_list();
+        self.belief.iter().map(move |(closure, _)| {
+            let state = self.entries[*closure];
+            (&self.space.states[state], &kinds[state])
+        })
+    }
+
+    pub fn get_layout(&self) -> &network::StateLayout {
+        &self.space.layout
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::{GraphMode, Regex};
+    use super::*;
+    use crate::compiler::compile;
+    use crate::timer;
+    use fsa_net_parser::parse;
+    use test_utils::load_code_from_file;
+
+    #[test]
+    fn test_monitor() {
+        let src_code = load_code_from_file("simple-network");
+        let code = parse(&src_code).expect("`simple-network` should be syntactically correct");
+        let comp_res = compile(&code).expect("`simple-network` should be semantically correct");
+        let net = &comp_res.compile_network[0].net;
+        let config = EngineConfig::new(GraphMode::Prune, timer::TimerFactory::from_value(None));
+
+        let mut monitor = Monitor::new(net, &config).unwrap();
+        let diagnoser = Diagnoser::from_closures(build_closures(&monitor.space.graph, &config).0);
+        assert_eq!(monitor.get_states().count(), 1);
+
+        assert!(monitor.observe(1));
+        assert_eq!(monitor.diagnosis().matrix, diagnoser.diagnosis(&[1]).matrix);
+
+        // inconsistent observations do not change the belief
+        assert!(!monitor.observe(1));
+        let layout = monitor.get_layout();
+        let states: Vec<_> = monitor
+            .get_states()
+            .map(|(s, _)| layout.decode(s))
+            .collect();
+        assert_eq!(states.len(), 1);
+
+        assert!(monitor.observe(0));
+        assert_eq!(
+            monitor.diagnosis().matrix,
+            diagnoser.diagnosis(&[1, 0]).matrix
+        );
+        assert!(monitor.diagnosis().matrix.is_some());
+    }
+
+    #[test]
+    fn test_long_stream() {
+        let src_code = "network N {\n    events e\n    obs o\n    rel f, g\n    automata A {\n        \
+                        begin a\n        state b\n        trans aa { src a dst a obs o rel f }\n        \
+                        trans ab { src a dst b obs o rel g }\n        trans ba { src b dst a obs o rel f }\n        \
+                        trans bb { src b dst b obs o rel g }\n    }\n}\n";
+        let code = parse(src_code).unwrap();
+        let comp_res = compile(&code).unwrap();
+        let net = &comp_res.compile_network[0].net;
+        let config = EngineConfig::new(GraphMode::Prune, timer::TimerFactory::from_value(None));
+
+        let mut monitor = Monitor::new(net, &config).unwrap();
+        for _ in 0..3 {
+            assert!(monitor.observe(0));
+        }
+        // (f|g)(f|g)(f|g)
+        let step = Regex::Alternative(vec![Regex::Value(vec![0]), Regex::Value(vec![1])]);
+        let expected = Regex::Chain(vec![step.clone(), step.clone(), step]);
+        assert_eq!(monitor.diagnosis().matrix, Some(expected));
+
+        for _ in 3..100 {
+            assert!(monitor.observe(0));
+        }
+        let regex = monitor.diagnosis().matrix.unwrap();
+        assert!(regex.size() <= 4 * 100 + 1);
+    }
+}
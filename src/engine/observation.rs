@@ -0,0 +1,142 @@
+use super::positions::{PositionAutomaton, PositionBuilder, Positions};
+use crate::command::ObsStep;
+
+/*
+    Position automaton of an observation pattern: every
+    state but the initial one is an occurrence of a label,
+    a set or a wildcard in the pattern and is entered only
+    by the observations it matches. A plain list of n steps
+    gives the chain 0 -> 1 -> ... -> n, so in that case the
+    state is the number of consumed observations.
+*/
+pub struct ObsAutomaton {
+    positions: PositionAutomaton<Matcher>,
+}
+
+enum Matcher {
+    Labels(Vec<usize>),
+    Any,
+}
+
+impl Matcher {
+    fn matches(&self, obs: usize) -> bool {
+        match self {
+            Self::Labels(labels) => labels.contains(&obs),
+            Self::Any => true,
+        }
+    }
+}
+
+impl ObsAutomaton {
+    pub fn new(pattern: &[ObsStep]) -> Self {
+        let mut builder = PositionAutomaton::builder();
+        let pos = chain(&mut builder, pattern);
+        Self {
+            positions: builder.build(pos),
+        }
+    }
+
+    /**
+     * States reached from the given one by the observation
+     */
+    pub fn step(&self, state: usize, obs: usize) -> impl Iterator<Item = usize> + '_ {
+        self.positions
+            .follow(state)
+            .iter()
+            .copied()
+            .filter(move |next| self.positions.label(*next).matches(obs))
+    }
+
+    pub fn is_final(&self, state: usize) -> bool {
+        self.positions.is_final(state)
+    }
+}
+
+fn chain(builder: &mut PositionBuilder<Matcher>, steps: &[ObsStep]) -> Positions {
+    let mut output = Positions::empty();
+    for step in steps {
+        let pos = step_positions(builder, step);
+        output = builder.concat(output, pos);
+    }
+    output
+}
+
+fn step_positions(builder: &mut PositionBuilder<Matcher>, step: &ObsStep) -> Positions {
+    match step {
+        ObsStep::Label(label) => builder.position(Matcher::Labels(vec![*label])),
+        ObsStep::Set(labels) => builder.position(Matcher::Labels(labels.clone())),
+        ObsStep::Any => builder.position(Matcher::Any),
+        ObsStep::Group(alternatives) => {
+            alternatives.iter().fold(Positions::none(), |output, alt| {
+                output.union(chain(builder, alt))
+            })
+        }
+        ObsStep::Star(step) => {
+            let pos = step_positions(builder, step);
+            builder.star(pos)
+        }
+        ObsStep::Plus(step) => {
+            let pos = step_positions(builder, step);
+            builder.plus(pos)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn accepts(automaton: &ObsAutomaton, obs: &[usize]) -> bool {
+        let mut states = vec![0];
+        for o in obs {
+            states = states
+                .into_iter()
+                .flat_map(|s| automaton.step(s, *o).collect::<Vec<_>>())
+                .collect();
+        }
+        states.into_iter().any(|s| automaton.is_final(s))
+    }
+
+    #[test]
+    fn test_chain() {
+        let pattern = [ObsStep::Label(1), ObsStep::Set(vec![0, 2]), ObsStep::Any];
+        let automaton = ObsAutomaton::new(&pattern);
+        assert_eq!(automaton.positions.state_count(), 4);
+        assert_eq!(automaton.step(0, 1).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(automaton.step(1, 2).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(automaton.step(2, 5).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(automaton.step(1, 1).count(), 0);
+        let finals: Vec<bool> = (0..4).map(|s| automaton.is_final(s)).collect();
+        assert_eq!(finals, vec![false, false, false, true]);
+    }
+
+    #[test]
+    fn test_pattern() {
+        // o1, o2*, (o3 | o1, o2)+
+        let group = ObsStep::Group(vec![
+            vec![ObsStep::Label(3)],
+            vec![ObsStep::Label(1), ObsStep::Label(2)],
+        ]);
+        let pattern = [
+            ObsStep::Label(1),
+            ObsStep::Star(Box::new(ObsStep::Label(2))),
+            ObsStep::Plus(Box::new(group)),
+        ];
+        let automaton = ObsAutomaton::new(&pattern);
+        assert_eq!(automaton.positions.state_count(), 6);
+
+        assert!(accepts(&automaton, &[1, 3]));
+        assert!(accepts(&automaton, &[1, 2, 2, 3, 1, 2, 3]));
+        assert!(accepts(&automaton, &[1, 1, 2]));
+        assert!(!accepts(&automaton, &[1]));
+        assert!(!accepts(&automaton, &[1, 2]));
+        assert!(!accepts(&automaton, &[1, 3, 1]));
+        assert!(!accepts(&automaton, &[2, 3]));
+
+        let optional = [ObsStep::Star(Box::new(ObsStep::Any))];
+        let automaton = ObsAutomaton::new(&optional);
+        assert!(accepts(&automaton, &[]));
+        assert!(accepts(&automaton, &[4, 0, 4]));
+    }
+}
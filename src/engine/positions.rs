@@ -0,0 +1,165 @@
+/*
+    Position (Glushkov) automaton of an expression over
+    labels of type L. State 0 is the initial one, every other
+    state is an occurrence of a label in the expression and
+    is entered only through that label: there are no silent
+    transitions.
+*/
+pub struct PositionAutomaton<L> {
+    // label of state i + 1
+    labels: Vec<L>,
+    follow: Vec<Vec<usize>>,
+    finals: Vec<bool>,
+}
+
+/*
+    Positions of a sub expression: whether it matches
+    the empty string and the states it can start and end with
+*/
+pub struct Positions {
+    nullable: bool,
+    first: Vec<usize>,
+    last: Vec<usize>,
+}
+
+impl Positions {
+    /**
+     * The empty string
+     */
+    pub fn empty() -> Self {
+        Self {
+            nullable: true,
+            first: vec![],
+            last: vec![],
+        }
+    }
+
+    /**
+     * The empty language, the neutral element of `union`
+     */
+    pub fn none() -> Self {
+        Self {
+            nullable: false,
+            first: vec![],
+            last: vec![],
+        }
+    }
+
+    pub fn union(mut self, other: Positions) -> Self {
+        self.nullable |= other.nullable;
+        self.first.extend(other.first);
+        self.last.extend(other.last);
+        self
+    }
+
+    pub fn optional(self) -> Self {
+        Self {
+            nullable: true,
+            ..self
+        }
+    }
+}
+
+impl<L> PositionAutomaton<L> {
+    pub fn builder() -> PositionBuilder<L> {
+        PositionBuilder {
+            labels: vec![],
+            follow: vec![vec![]],
+        }
+    }
+
+    #[cfg(test)]
+    pub fn state_count(&self) -> usize {
+        self.follow.len()
+    }
+
+    /**
+     * Label entering the given state, which is not the initial one
+     */
+    pub fn label(&self, state: usize) -> &L {
+        &self.labels[state - 1]
+    }
+
+    /**
+     * States following the given one, sorted
+     */
+    pub fn follow(&self, state: usize) -> &[usize] {
+        &self.follow[state]
+    }
+
+    pub fn is_final(&self, state: usize) -> bool {
+        self.finals[state]
+    }
+}
+
+pub struct PositionBuilder<L> {
+    labels: Vec<L>,
+    follow: Vec<Vec<usize>>,
+}
+
+impl<L> PositionBuilder<L> {
+    /**
+     * A new occurrence of the label
+     */
+    pub fn position(&mut self, label: L) -> Positions {
+        let state = self.follow.len();
+        self.follow.push(vec![]);
+        self.labels.push(label);
+        Positions {
+            nullable: false,
+            first: vec![state],
+            last: vec![state],
+        }
+    }
+
+    pub fn concat(&mut self, mut head: Positions, tail: Positions) -> Positions {
+        self.link(&head.last, &tail.first);
+        if head.nullable {
+            head.first.extend(tail.first);
+        }
+        if tail.nullable {
+            head.last.extend(tail.last);
+        } else {
+            head.last = tail.last;
+        }
+        head.nullable &= tail.nullable;
+        head
+    }
+
+    pub fn plus(&mut self, pos: Positions) -> Positions {
+        self.link(&pos.last, &pos.first);
+        pos
+    }
+
+    pub fn star(&mut self, pos: Positions) -> Positions {
+        self.plus(pos).optional()
+    }
+
+    /**
+     * Automaton accepting the strings of the whole expression
+     */
+    pub fn build(self, pos: Positions) -> PositionAutomaton<L> {
+        let mut follow = self.follow;
+        follow[0] = pos.first;
+        for next in follow.iter_mut() {
+            next.sort_unstable();
+            next.dedup();
+        }
+        let mut finals = vec![false; follow.len()];
+        finals[0] = pos.nullable;
+        for state in pos.last {
+            finals[state] = true;
+        }
+        PositionAutomaton {
+            labels: self.labels,
+            follow,
+            finals,
+        }
+    }
+
+    fn link(&mut self, src: &[usize], dst: &[usize]) {
+        for state in src {
+            self.follow[*state].extend(dst);
+        }
+    }
+}
@@ -1,21 +1,31 @@
 use crate::command;
 use crate::network;
 
+use super::cheapest;
+use super::closure;
+use super::dfa;
+use super::diagnosability;
+use super::diagnoser;
 use super::diagnosis;
+use super::explanation;
 use super::full_space;
+use super::likely;
 use super::linspace;
+use super::witness;
 use super::NetworkResult;
 use crate::input_output::{load_str_from_file, save_str_to_file};
 
+use super::EliminationOrder;
 use crate::graph;
 
 type NRes = Result<NetworkResult, Box<dyn std::error::Error>>;
+type DRes = Result<diagnosis::DiagnosisResult, Box<dyn std::error::Error>>;
 
 pub fn run(
     net: &network::Network,
     reqs: &command::Requests,
     conf: &super::EngineConfig,
-    file_names: &Vec<&str>,
+    file_names: &[&str],
 ) -> Vec<NRes> {
     reqs.commands
         .iter()
@@ -27,22 +37,42 @@ fn run_request(
     net: &network::Network,
     req: &command::Command,
     conf: &super::EngineConfig,
-    file_names: &Vec<&str>,
+    file_names: &[&str],
 ) -> NRes {
     match req {
         command::Command::FullSpace => Ok(full_space::compute_full_space(net, conf).into()),
+        command::Command::Closures => Ok(closure::compute_closure_space(net, conf).into()),
+        command::Command::Diagnosability => {
+            Ok(diagnosability::compute_diagnosability(net, conf).into())
+        }
         command::Command::Linspace((obs_labels, out_file)) => {
             run_linspace(net, obs_labels, out_file, file_names, conf)
         }
         command::Command::Diagnosis(cmd) => run_diagnosis(net, conf, cmd, file_names),
+        command::Command::Diagnoser(cmd) => run_diagnoser(net, conf, cmd, file_names),
+        command::Command::Check((cmd, strings)) => run_check(net, conf, cmd, strings, file_names),
+        command::Command::Witness((obs_labels, order, length)) => {
+            let order = order.unwrap_or(conf.elimination);
+            Ok(witness::compute_witnesses(net, obs_labels, conf, order, *length).into())
+        }
+        command::Command::Likely((obs_labels, order, count)) => {
+            let order = order.unwrap_or(conf.elimination);
+            let count = count.unwrap_or(explanation::DEFAULT_COUNT);
+            Ok(likely::compute_likely(net, obs_labels, conf, order, count).into())
+        }
+        command::Command::Cheapest((obs_labels, order, count)) => {
+            let order = order.unwrap_or(conf.elimination);
+            let count = count.unwrap_or(explanation::DEFAULT_COUNT);
+            Ok(cheapest::compute_cheapest(net, obs_labels, conf, order, count).into())
+        }
     }
 }
 
 fn run_linspace(
     net: &network::Network,
-    labels: &Vec<usize>,
+    labels: &[command::ObsStep],
     out_file: &Option<usize>,
-    file_names: &Vec<&str>,
+    file_names: &[&str],
     conf: &super::EngineConfig,
 ) -> NRes {
     let lin_space = linspace::compute_linear_space(net, labels, conf);
@@ -61,32 +91,106 @@ fn run_diagnosis(
     net: &network::Network,
     conf: &super::EngineConfig,
     cmd: &command::DiagnosisCommand,
-    file_names: &Vec<&str>,
+    file_names: &[&str],
 ) -> NRes {
+    Ok(compute_diagnosis(net, conf, cmd, file_names)?.into())
+}
+
+fn run_check(
+    net: &network::Network,
+    conf: &super::EngineConfig,
+    cmd: &command::DiagnosisCommand,
+    strings: &[Vec<usize>],
+    file_names: &[&str],
+) -> NRes {
+    let diagnosis = compute_diagnosis(net, conf, cmd, file_names)?;
+    Ok(dfa::check_strings(diagnosis, strings).into())
+}
+
+fn compute_diagnosis(
+    net: &network::Network,
+    conf: &super::EngineConfig,
+    cmd: &command::DiagnosisCommand,
+    file_names: &[&str],
+) -> DRes {
     match cmd {
-        command::DiagnosisCommand::Fresh(obs_labels) => run_fresh_diagnosis(net, conf, obs_labels),
-        command::DiagnosisCommand::Load(file) => run_load_diagnosis(*file, file_names, conf),
+        command::DiagnosisCommand::Fresh((obs_labels, order)) => {
+            let order = order.unwrap_or(conf.elimination);
+            Ok(run_fresh_diagnosis(net, conf, obs_labels, order))
+        }
+        command::DiagnosisCommand::Load((file, order)) => {
+            let order = order.unwrap_or(conf.elimination);
+            run_load_diagnosis(*file, file_names, conf, order)
+        }
     }
 }
 
 fn run_fresh_diagnosis(
     net: &network::Network,
     conf: &super::EngineConfig,
-    obs_labels: &Vec<usize>,
-) -> NRes {
+    obs_labels: &[command::ObsStep],
+    order: EliminationOrder,
+) -> diagnosis::DiagnosisResult {
     let tmp = linspace::compute_linear_space(net, obs_labels, conf);
-    let output = if tmp.complete {
-        diagnosis::diagnosis(&tmp.graph, conf)
-    } else {
-        diagnosis::fail_diagnosis()
-    }
-    .into();
-    Ok(output)
+    diagnosis::partial_diagnosis(&tmp.graph, &tmp.frontier, conf, order)
 }
 
-fn run_load_diagnosis(out_file: usize, file_names: &Vec<&str>, conf: &super::EngineConfig) -> NRes {
+fn run_load_diagnosis(
+    out_file: usize,
+    file_names: &[&str],
+    conf: &super::EngineConfig,
+    order: EliminationOrder,
+) -> DRes {
     let file_name = file_names[out_file];
     let data = load_str_from_file(file_name)?;
     let g: graph::Graph<Option<usize>> = graph::Graph::load(&data)?;
-    Ok(diagnosis::diagnosis(&g, conf).into())
+    Ok(diagnosis::diagnosis_with(&g, conf, order))
+}
+
+fn run_diagnoser(
+    net: &network::Network,
+    conf: &super::EngineConfig,
+    cmd: &command::DiagnoserCommand,
+    file_names: &[&str],
+) -> NRes {
+    match cmd {
+        command::DiagnoserCommand::Build((obs_labels, out_file)) => {
+            run_build_diagnoser(net, conf, obs_labels, out_file, file_names)
+        }
+        command::DiagnoserCommand::Load((file, obs_labels)) => {
+            run_load_diagnoser(*file, obs_labels, file_names)
+        }
+    }
+}
+
+fn run_build_diagnoser(
+    net: &network::Network,
+    conf: &super::EngineConfig,
+    obs_labels: &[usize],
+    out_file: &Option<usize>,
+    file_names: &[&str],
+) -> NRes {
+    let result = diagnoser::build_diagnoser(net, conf);
+    if let (Some(diagnoser), Some(file_index)) = (&result.diagnoser, out_file) {
+        save_str_to_file(&diagnoser.save(), file_names[*file_index])?;
+    }
+
+    if obs_labels.is_empty() {
+        Ok(result.into())
+    } else if let Some(diagnoser) = result.diagnoser {
+        Ok(diagnoser.diagnosis(obs_labels).into())
+    } else {
+        let result = diagnosis::DiagnosisResult {
+            complete: false,
+            timeout: true,
+            ..diagnosis::fail_diagnosis()
+        };
+        Ok(result.into())
+    }
+}
+
+fn run_load_diagnoser(in_file: usize, obs_labels: &[usize], file_names: &[&str]) -> NRes {
+    let data = load_str_from_file(file_names[in_file])?;
+    let diagnoser = diagnoser::Diagnoser::load(&data)?;
+    Ok(diagnoser.diagnosis(obs_labels).into())
 }
@@ -0,0 +1,366 @@
+use super::Regex;
+
+/*
+    Rewrite the regex with the identities of Kleene
+    algebra until it does not change anymore:
+    - nested chains and alternatives are flattened
+    - adjacent values in a chain are joined and ε is dropped
+    - alternatives are deduplicated, a | a* is a*
+    - an ε alternative makes the whole alternative optional
+    - common prefixes and suffixes of the alternatives
+      are factored out
+    - (a*)* = (a?)* = a*, (a?)? = a? and a?, a* are
+      left unchanged when a is already nullable
+*/
+pub fn simplify(regex: Regex) -> Regex {
+    let mut curr = regex;
+    loop {
+        let next = simplify_once(curr.clone());
+        if next == curr {
+            return next;
+        }
+        curr = next;
+    }
+}
+
+fn simplify_once(regex: Regex) -> Regex {
+    match regex {
+        Regex::Value(val) => Regex::Value(val),
+        Regex::Chain(items) => chain(items.into_iter().map(simplify_once)),
+        Regex::Alternative(alts) => alternative(alts.into_iter().map(simplify_once)),
+        Regex::Optional(opt) => optional(simplify_once(*opt)),
+        Regex::ZeroMore(rep) => zero_more(simplify_once(*rep)),
+    }
+}
+
+fn chain<I>(items: I) -> Regex
+where
+    I: IntoIterator<Item = Regex>,
+{
+    let mut output = vec![];
+    for item in items {
+        push_chain(&mut output, item);
+    }
+    match output.len() {
+        0 => Regex::default(),
+        1 => output.pop().unwrap(),
+        _ => Regex::Chain(output),
+    }
+}
+
+fn push_chain(output: &mut Vec<Regex>, item: Regex) {
+    match item {
+        Regex::Chain(items) => {
+            for item in items {
+                push_chain(output, item);
+            }
+        }
+        Regex::Value(val) if val.is_empty() => {}
+        Regex::Value(val) => {
+            if let Some(Regex::Value(last)) = output.last_mut() {
+                last.extend(val);
+            } else {
+                output.push(Regex::Value(val));
+            }
+        }
+        Regex::ZeroMore(_) if output.last() == Some(&item) => {}
+        item => output.push(item),
+    }
+}
+
+fn alternative<I>(items: I) -> Regex
+where
+    I: IntoIterator<Item = Regex>,
+{
+    let mut alts = vec![];
+    let mut empty = false;
+    for item in items {
+        push_alternative(&mut alts, &mut empty, item);
+    }
+    let stars: Vec<Regex> = alts
+        .iter()
+        .filter_map(|alt| match alt {
+            Regex::ZeroMore(rep) => Some(rep.as_ref().clone()),
+            _ => None,
+        })
+        .collect();
+    alts.retain(|alt| !stars.contains(alt));
+
+    let output = if let Some(output) = factor(&alts) {
+        output
+    } else if alts.len() > 1 {
+        Regex::Alternative(alts)
+    } else if let Some(alt) = alts.pop() {
+        alt
+    } else {
+        return Regex::default();
+    };
+
+    if empty {
+        optional(output)
+    } else {
+        output
+    }
+}
+
+fn push_alternative(alts: &mut Vec<Regex>, empty: &mut bool, item: Regex) {
+    match item {
+        Regex::Alternative(items) => {
+            for item in items {
+                push_alternative(alts, empty, item);
+            }
+        }
+        Regex::Optional(opt) => {
+            *empty = true;
+            push_alternative(alts, empty, *opt);
+        }
+        Regex::Value(val) if val.is_empty() => *empty = true,
+        item => {
+            if !alts.contains(&item) {
+                alts.push(item)
+            }
+        }
+    }
+}
+
+/*
+    Factor the longest common prefix, or suffix,
+    of the alternatives: None if there is none
+*/
+fn factor(alts: &[Regex]) -> Option<Regex> {
+    if alts.len() < 2 {
+        return None;
+    }
+    let items: Vec<Vec<Regex>> = alts.iter().map(chain_items).collect();
+    let shortest = items.iter().map(|i| i.len()).min().unwrap();
+
+    let prefix = (0..shortest)
+        .take_while(|k| items.iter().all(|i| i[*k] == items[0][*k]))
+        .count();
+    if prefix > 0 {
+        let rest = items.iter().map(|i| chain(i[prefix..].to_vec()));
+        let mut output = items[0][..prefix].to_vec();
+        output.push(alternative(rest));
+        return Some(chain(output));
+    }
+
+    let suffix = (1..=shortest)
+        .take_while(|k| {
+            let last = &items[0][items[0].len() - k];
+            items.iter().all(|i| &i[i.len() - k] == last)
+        })
+        .count();
+    if suffix > 0 {
+        let rest = items.iter().map(|i| chain(i[..i.len() - suffix].to_vec()));
+        let mut output = vec![alternative(rest)];
+        let first = &items[0];
+        output.extend_from_slice(&first[first.len() - suffix..]);
+        return Some(chain(output));
+    }
+    None
+}
+
+/*
+    Split a regex into the items of a chain,
+    values are split into single labels
+*/
+fn chain_items(regex: &Regex) -> Vec<Regex> {
+    let items = match regex {
+        Regex::Chain(items) => items.as_slice(),
+        other => std::slice::from_ref(other),
+    };
+    items
+        .iter()
+        .flat_map(|item| match item {
+            Regex::Value(val) => val.iter().map(|v| Regex::Value(vec![*v])).collect(),
+            other => vec![other.clone()],
+        })
+        .collect()
+}
+
+fn optional(regex: Regex) -> Regex {
+    if is_nullable(&regex) {
+        regex
+    } else {
+        Regex::Optional(Box::new(regex))
+    }
+}
+
+fn zero_more(regex: Regex) -> Regex {
+    match regex {
+        Regex::ZeroMore(rep) | Regex::Optional(rep) => zero_more(*rep),
+        Regex::Value(val) if val.is_empty() => Regex::default(),
+        other => Regex::ZeroMore(Box::new(other)),
+    }
+}
+
+fn is_nullable(regex: &Regex) -> bool {
+    match regex {
+        Regex::Value(val) => val.is_empty(),
+        Regex::Chain(items) => items.iter().all(is_nullable),
+        Regex::Alternative(alts) => alts.iter().any(is_nullable),
+        Regex::Optional(_) | Regex::ZeroMore(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use proptest::prelude::*;
+
+    fn val(v: &[usize]) -> Regex {
+        Regex::Value(v.to_vec())
+    }
+
+    fn star(r: Regex) -> Regex {
+        Regex::ZeroMore(Box::new(r))
+    }
+
+    fn opt(r: Regex) -> Regex {
+        Regex::Optional(Box::new(r))
+    }
+
+    #[test]
+    fn test_simplify() {
+        // ((r)|(r)) = r
+        let regex = Regex::Alternative(vec![val(&[1]), val(&[1])]);
+        assert_eq!(simplify(regex), val(&[1]));
+
+        // ((f)*)* = f*
+        let regex = star(Regex::Chain(vec![star(val(&[0]))]));
+        assert_eq!(simplify(regex), star(val(&[0])));
+
+        // (f?)* = f*
+        assert_eq!(simplify(star(opt(val(&[0])))), star(val(&[0])));
+
+        // fr | fff = f(r | ff)
+        let regex = Regex::Alternative(vec![val(&[0, 1]), val(&[0, 0, 0])]);
+        let expected = Regex::Chain(vec![
+            val(&[0]),
+            Regex::Alternative(vec![val(&[1]), val(&[0, 0])]),
+        ]);
+        assert_eq!(simplify(regex), expected);
+
+        // rf | f = r?f
+        let regex = Regex::Alternative(vec![val(&[1, 0]), val(&[0])]);
+        assert_eq!(
+            simplify(regex),
+            Regex::Chain(vec![opt(val(&[1])), val(&[0])])
+        );
+
+        // (f | ε | (r | f)) = (f | r)?
+        let regex = Regex::Alternative(vec![
+            val(&[0]),
+            val(&[]),
+            Regex::Alternative(vec![val(&[1]), val(&[0])]),
+        ]);
+        let expected = opt(Regex::Alternative(vec![val(&[0]), val(&[1])]));
+        assert_eq!(simplify(regex), expected);
+
+        // ε f (ε r) = fr
+        let regex = Regex::Chain(vec![
+            val(&[]),
+            val(&[0]),
+            Regex::Chain(vec![val(&[]), val(&[1])]),
+        ]);
+        assert_eq!(simplify(regex), val(&[0, 1]));
+
+        // (f* | f)? = f*
+        let regex = opt(Regex::Alternative(vec![star(val(&[0])), val(&[0])]));
+        assert_eq!(simplify(regex), star(val(&[0])));
+    }
+
+    /*
+        Positions of the word reachable
+        after matching the regex from `begin`
+    */
+    fn match_ends(regex: &Regex, word: &[usize], begin: Vec<usize>) -> Vec<usize> {
+        let mut output = match regex {
+            Regex::Value(val) => begin
+                .into_iter()
+                .filter(|b| word[*b..].starts_with(val))
+                .map(|b| b + val.len())
+                .collect(),
+            Regex::Chain(items) => items
+                .iter()
+                .fold(begin, |curr, item| match_ends(item, word, curr)),
+            Regex::Alternative(alts) => alts
+                .iter()
+                .flat_map(|alt| match_ends(alt, word, begin.clone()))
+                .collect(),
+            Regex::Optional(opt) => {
+                let mut output = match_ends(opt, word, begin.clone());
+                output.extend(begin);
+                output
+            }
+            Regex::ZeroMore(rep) => {
+                let mut output = begin.clone();
+                let mut curr = begin;
+                while !curr.is_empty() {
+                    curr = match_ends(rep, word, curr);
+                    curr.retain(|c| !output.contains(c));
+                    curr.sort_unstable();
+                    curr.dedup();
+                    output.extend(&curr);
+                }
+                output
+            }
+        };
+        output.sort_unstable();
+        output.dedup();
+        output
+    }
+
+    fn accepts(regex: &Regex, word: &[usize]) -> bool {
+        match_ends(regex, word, vec![0]).contains(&word.len())
+    }
+
+    fn all_words(alphabet: usize, max_len: usize) -> Vec<Vec<usize>> {
+        let mut output = vec![vec![]];
+        let mut last = vec![vec![]];
+        for _ in 0..max_len {
+            last = last
+                .iter()
+                .flat_map(|w: &Vec<usize>| {
+                    (0..alphabet).map(move |l| {
+                        let mut w = w.clone();
+                        w.push(l);
+                        w
+                    })
+                })
+                .collect();
+            output.extend(last.iter().cloned());
+        }
+        output
+    }
+
+    fn regex_strategy() -> impl Strategy<Value = Regex> {
+        let leaf = prop::collection::vec(0..3usize, 0..3).prop_map(Regex::Value);
+        leaf.prop_recursive(4, 24, 4, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4).prop_map(Regex::Chain),
+                prop::collection::vec(inner.clone(), 1..4).prop_map(Regex::Alternative),
+                inner.clone().prop_map(|r| Regex::Optional(Box::new(r))),
+                inner.prop_map(|r| Regex::ZeroMore(Box::new(r))),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_same_language(regex in regex_strategy()) {
+            let simple = simplify(regex.clone());
+            for word in all_words(3, 5) {
+                prop_assert_eq!(
+                    accepts(&regex, &word),
+                    accepts(&simple, &word),
+                    "word {:?} simplified to {:?}",
+                    word,
+                    simple
+                );
+            }
+            prop_assert_eq!(simplify(simple.clone()), simple);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use super::diagnosis::{partial_diagnosis, AsLabel, DiagnosisResult};
+use super::linspace::{compute_linear_space, LinSpaceResult};
+use super::EliminationOrder;
+use super::EngineConfig;
+use crate::command::ObsStep;
+use crate::graph;
+use crate::network;
+use crate::timer::Timer;
+use std::collections::{HashMap, VecDeque};
+
+pub struct WitnessResult {
+    pub diagnosis: DiagnosisResult,
+    pub linspace: LinSpaceResult,
+    // maximum length of the strings, None for the sets of labels
+    pub length: Option<usize>,
+    pub witnesses: Vec<Witness>,
+    pub timeout: bool,
+}
+
+impl From<WitnessResult> for super::NetworkResult {
+    fn from(result: WitnessResult) -> Self {
+        Self::Witness(result)
+    }
+}
+
+/*
+    Shortest trajectory in the linspace from the initial
+    node to a final one producing `labels`: event i fires
+    moving from nodes[i] to nodes[i + 1]
+*/
+pub struct Witness {
+    pub labels: Vec<usize>,
+    pub nodes: Vec<usize>,
+    pub events: Vec<network::TransEvent>,
+}
+
+pub fn compute_witnesses(
+    net: &network::Network,
+    obs_labels: &[ObsStep],
+    conf: &EngineConfig,
+    order: EliminationOrder,
+    length: Option<usize>,
+) -> WitnessResult {
+    let linspace = compute_linear_space(net, obs_labels, conf);
+    let diagnosis = partial_diagnosis(&linspace.graph, &linspace.frontier, conf, order);
+    let timer = conf.timer_factory.new_timer();
+    let (paths, timeout) = find_paths(&linspace.graph, length, &timer);
+    let adj = linspace.graph.get_adjacent_list();
+    let witnesses = paths
+        .into_iter()
+        .map(|path| Witness {
+            labels: path.labels,
+            nodes: path.nodes,
+            events: path
+                .arcs
+                .iter()
+                .map(|(src, arc)| adj[*src][*arc].label.clone())
+                .collect(),
+        })
+        .collect();
+    WitnessResult {
+        diagnosis,
+        linspace,
+        length,
+        witnesses,
+        timeout,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Path {
+    labels: Vec<usize>,
+    nodes: Vec<usize>,
+    // (source node, index in its adjacent list)
+    arcs: Vec<(usize, usize)>,
+}
+
+struct Visit {
+    node: usize,
+    labels: Vec<usize>,
+    // (previous visit, arc index)
+    parent: Option<(usize, usize)>,
+}
+
+/*
+    Breadth first search on the product of the graph with
+    the labels produced so far: the first visit of a final
+    node with some labels ends a shortest path producing them.
+    Strings longer than `length` are not explored.
+*/
+fn find_paths<T: AsLabel>(
+    g: &graph::Graph<T>,
+    length: Option<usize>,
+    timer: &Timer,
+) -> (Vec<Path>, bool) {
+    let adj = g.get_adjacent_list();
+    if adj.is_empty() {
+        return (vec![], false);
+    }
+    let kinds = g.get_node_kind_list();
+
+    let mut visits = vec![Visit {
+        node: 0,
+        labels: vec![],
+        parent: None,
+    }];
+    let mut index = HashMap::new();
+    index.insert((0, vec![]), 0);
+    let mut found = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(0);
+    let mut timeout = false;
+    while let Some(curr) = queue.pop_front() {
+        if timer.timeout() {
+            timeout = true;
+            break;
+        }
+        let node = visits[curr].node;
+        if kinds[node] == graph::NodeKind::Final {
+            found.entry(visits[curr].labels.clone()).or_insert(curr);
+        }
+        for (i, arc) in adj[node].iter().enumerate() {
+            let labels = match next_labels(&visits[curr].labels, arc.label.get_label(), length) {
+                Some(labels) => labels,
+                None => continue,
+            };
+            let key = (arc.next, labels);
+            if !index.contains_key(&key) {
+                index.insert(key.clone(), visits.len());
+                queue.push_back(visits.len());
+                visits.push(Visit {
+                    node: key.0,
+                    labels: key.1,
+                    parent: Some((curr, i)),
+                });
+            }
+        }
+    }
+
+    let mut paths: Vec<Path> = found
+        .into_values()
+        .map(|visit| build_path(&visits, visit))
+        .collect();
+    paths.sort_by(|a, b| (a.labels.len(), &a.labels).cmp(&(b.labels.len(), &b.labels)));
+    (paths, timeout)
+}
+
+// None when the labels exceed the maximum length
+fn next_labels(
+    labels: &[usize],
+    label: Option<usize>,
+    length: Option<usize>,
+) -> Option<Vec<usize>> {
+    let mut labels = labels.to_vec();
+    if let Some(label) = label {
+        match length {
+            Some(length) if labels.len() == length => return None,
+            Some(_) => labels.push(label),
+            None => {
+                if let Err(i) = labels.binary_search(&label) {
+                    labels.insert(i, label);
+                }
+            }
+        }
+    }
+    Some(labels)
+}
+
+fn build_path(visits: &[Visit], last: usize) -> Path {
+    let mut nodes = vec![visits[last].node];
+    let mut arcs = vec![];
+    let mut curr = last;
+    while let Some((prev, arc)) = visits[curr].parent {
+        arcs.push((visits[prev].node, arc));
+        nodes.push(visits[prev].node);
+        curr = prev;
+    }
+    nodes.reverse();
+    arcs.reverse();
+    Path {
+        labels: visits[last].labels.clone(),
+        nodes,
+        arcs,
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::timer::TimerFactory;
+
+    fn build_graph() -> graph::Graph<Option<usize>> {
+        // 0 -f-> 1 -r-> 2, 0 -> 2, 2 -f-> 2, node 2 is final
+        let mut builder = graph::GraphBuilder::new();
+        builder.add_simple_node(0);
+        builder.add_simple_node(1);
+        builder.add_final_node(2);
+        builder.add_arc(0, 1, Some(0));
+        builder.add_arc(1, 2, Some(1));
+        builder.add_arc(0, 2, None);
+        builder.add_arc(2, 2, Some(0));
+        builder.build_graph()
+    }
+
+    #[test]
+    fn test_witness_strings() {
+        let timer = TimerFactory::from_value(None).new_timer();
+        let (paths, timeout) = find_paths(&build_graph(), Some(2), &timer);
+        assert!(!timeout);
+        let labels: Vec<Vec<usize>> = paths.iter().map(|p| p.labels.clone()).collect();
+        assert_eq!(labels, vec![vec![], vec![0], vec![0, 0], vec![0, 1]]);
+
+        let expected = Path {
+            labels: vec![0, 1],
+            nodes: vec![0, 1, 2],
+            arcs: vec![(0, 0), (1, 0)],
+        };
+        assert_eq!(paths[3], expected);
+        assert_eq!(paths[2].nodes, vec![0, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_witness_sets() {
+        let timer = TimerFactory::from_value(None).new_timer();
+        let (paths, _) = find_paths(&build_graph(), None, &timer);
+        let labels: Vec<Vec<usize>> = paths.iter().map(|p| p.labels.clone()).collect();
+        assert_eq!(labels, vec![vec![], vec![0], vec![0, 1]]);
+        assert_eq!(paths[1].nodes, vec![0, 2, 2]);
+    }
+}
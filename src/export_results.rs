@@ -1,5 +1,9 @@
 use crate::compiler::{NetNames, NetworkIndexTable};
-use crate::engine::{DiagnosisResult, FullSpaceResult, LinSpaceResult, NetworkResult, Regex};
+use crate::engine::{
+    CheapestResult, CheckResult, ClosureSpaceResult, Dfa, DiagnosabilityResult, DiagnoserResult,
+    DiagnosisResult, DiagnosisStats, FaultClass, FaultOccurrence, FullSpaceResult, Lasso,
+    LikelyResult, LinSpaceResult, Monitor, NetworkResult, Regex, WitnessResult, FRONTIER,
+};
 use crate::graph;
 use crate::network;
 use crate::utils::zip;
@@ -7,6 +11,9 @@ use serde::Serialize;
 
 type NRes = Result<NetworkResult, Box<dyn std::error::Error>>;
 
+// how the FRONTIER label appears in the exported regex
+const UNEXPLORED: &str = "<unexplored>";
+
 pub enum JsonFormat {
     Compact,
     Pretty,
@@ -40,12 +47,55 @@ pub struct FullResult<'a> {
     exports: Vec<ExportResult<'a>>,
 }
 
+/**
+ * Export the belief of the monitor after the given
+ * observation: an inconsistent one leaves the belief
+ * unchanged, so the previous diagnosis is exported again
+ */
+pub fn export_monitor_step<'a>(
+    observation: &'a str,
+    consistent: bool,
+    monitor: &Monitor,
+    table: &'a NetworkIndexTable<'a>,
+) -> MonitorStep<'a> {
+    let diagnosis = monitor
+        .diagnosis()
+        .matrix
+        .map(|regex| export_regex(&regex, table.get_network_names()));
+    let layout = monitor.get_layout();
+    let states = monitor
+        .get_states()
+        .map(|(s, k)| State::convert(&layout.decode(s), table, k))
+        .collect();
+    MonitorStep {
+        observation,
+        consistent,
+        diagnosis,
+        states,
+    }
+}
+
+#[derive(Serialize)]
+pub struct MonitorStep<'a> {
+    observation: &'a str,
+    consistent: bool,
+    diagnosis: Option<String>,
+    states: Vec<State<'a>>,
+}
+
 fn export_result<'a>(result: &'a NRes, table: &'a NetworkIndexTable<'a>) -> ExportResult<'a> {
     match result {
         Ok(result) => ExportResult::Success(match result {
             NetworkResult::FullSpace(full_space) => export_full_space(&full_space, table).into(),
             NetworkResult::Linspace(lin_space) => export_lin_space(&lin_space, table).into(),
             NetworkResult::Diagnosis(diagnosis) => export_diagnosis(diagnosis, table).into(),
+            NetworkResult::Diagnoser(diagnoser) => export_diagnoser(diagnoser).into(),
+            NetworkResult::Closures(closures) => export_closures(closures, table).into(),
+            NetworkResult::Check(check) => export_check(check, table).into(),
+            NetworkResult::Witness(witness) => export_witness(witness, table).into(),
+            NetworkResult::Diagnosability(result) => export_diagnosability(result, table).into(),
+            NetworkResult::Likely(likely) => export_likely(likely, table).into(),
+            NetworkResult::Cheapest(cheapest) => export_cheapest(cheapest, table).into(),
         }),
         Err(err) => {
             let msg = format!("{}", err);
@@ -54,29 +104,331 @@ fn export_result<'a>(result: &'a NRes, table: &'a NetworkIndexTable<'a>) -> Expo
     }
 }
 
-fn export_diagnosis(diag: &DiagnosisResult, table: &NetworkIndexTable) -> ExportDiagnosis {
+fn export_diagnosis<'a>(
+    diag: &DiagnosisResult,
+    table: &'a NetworkIndexTable<'a>,
+) -> ExportDiagnosis<'a> {
+    let strategy = diag.strategy.map(|s| s.name());
+    let dfa = if diag.timeout {
+        None
+    } else {
+        let dfa = Dfa::new(diag.matrix.as_ref());
+        Some(export_dfa(&dfa, table.get_network_names()))
+    };
+    let faults = diag
+        .faults
+        .as_ref()
+        .map(|faults| export_faults(faults, table.get_network_names()));
+    let stats = diag.stats.map(ExportStats::from);
     if let Some(regex) = &diag.matrix {
+        let size = regex.size();
         let regex = export_regex(regex, table.get_network_names());
         ExportDiagnosis {
             regex: Some(regex),
+            size: Some(size),
+            strategy,
+            dfa,
+            faults,
+            stats,
             complete: diag.complete,
             timeout: diag.timeout,
         }
     } else {
         ExportDiagnosis {
             regex: None,
+            size: None,
+            strategy,
+            dfa,
+            faults,
+            stats,
             complete: diag.complete,
             timeout: diag.timeout,
         }
     }
 }
 
+fn export_faults<'a>(faults: &[FaultOccurrence], table: &'a NetNames) -> Vec<ExportFault<'a>> {
+    let excluded = FaultOccurrence::excluded();
+    (0..table.rel_count())
+        .map(|lbl| {
+            let fault = faults.get(lbl).unwrap_or(&excluded);
+            ExportFault {
+                label: table.get_rel_name(lbl),
+                class: fault.class().into(),
+                min: fault.min,
+                max: fault.max,
+            }
+        })
+        .collect()
+}
+
+fn export_dfa<'a>(dfa: &Dfa, table: &'a NetNames) -> ExportDfa<'a> {
+    let adjacent = dfa
+        .get_adjacent_list()
+        .iter()
+        .map(|adj| {
+            adj.iter()
+                .map(|(lbl, next)| DfaArc {
+                    label: table.get_rel_name(*lbl),
+                    next: *next,
+                })
+                .collect()
+        })
+        .collect();
+    let finals = (0..dfa.state_count())
+        .filter(|s| dfa.is_final(*s))
+        .collect();
+    ExportDfa { adjacent, finals }
+}
+
+fn export_check<'a>(check: &CheckResult, table: &'a NetworkIndexTable<'a>) -> ExportCheck<'a> {
+    let net_table = table.get_network_names();
+    let checks = check
+        .strings
+        .iter()
+        .enumerate()
+        .map(|(i, string)| CheckedString {
+            string: string.iter().map(|l| net_table.get_rel_name(*l)).collect(),
+            accepted: check.accepted.as_ref().map(|acc| acc[i]),
+        })
+        .collect();
+    ExportCheck {
+        diagnosis: export_diagnosis(&check.diagnosis, table),
+        checks,
+    }
+}
+
+fn export_witness<'a>(
+    result: &WitnessResult,
+    table: &'a NetworkIndexTable<'a>,
+) -> ExportWitness<'a> {
+    let witnesses = result
+        .witnesses
+        .iter()
+        .map(|witness| {
+            export_trajectory(
+                &witness.labels,
+                &witness.nodes,
+                &witness.events,
+                &result.linspace,
+                table,
+            )
+        })
+        .collect();
+    ExportWitness {
+        diagnosis: export_diagnosis(&result.diagnosis, table),
+        sets: result.length.is_none(),
+        witnesses,
+        complete: !result.timeout,
+    }
+}
+
+fn export_likely<'a>(result: &LikelyResult, table: &'a NetworkIndexTable<'a>) -> ExportLikely<'a> {
+    let explanations = result
+        .explanations
+        .iter()
+        .map(|explanation| ExportExplanation {
+            prob: explanation.score.0,
+            trajectory: export_trajectory(
+                &explanation.labels,
+                &explanation.nodes,
+                &explanation.events,
+                &result.linspace,
+                table,
+            ),
+        })
+        .collect();
+    ExportLikely {
+        diagnosis: export_diagnosis(&result.diagnosis, table),
+        explanations,
+        complete: !result.timeout,
+    }
+}
+
+fn export_cheapest<'a>(
+    result: &CheapestResult,
+    table: &'a NetworkIndexTable<'a>,
+) -> ExportCheapest<'a> {
+    let explanations = result
+        .explanations
+        .iter()
+        .map(|explanation| ExportCostExplanation {
+            cost: explanation.score.0,
+            trajectory: export_trajectory(
+                &explanation.labels,
+                &explanation.nodes,
+                &explanation.events,
+                &result.linspace,
+                table,
+            ),
+        })
+        .collect();
+    ExportCheapest {
+        diagnosis: export_diagnosis(&result.diagnosis, table),
+        explanations,
+        complete: !result.timeout,
+    }
+}
+
+fn export_trajectory<'a>(
+    labels: &[usize],
+    nodes: &[usize],
+    events: &[network::TransEvent],
+    linspace: &LinSpaceResult,
+    table: &'a NetworkIndexTable<'a>,
+) -> ExportTrajectory<'a> {
+    let net_table = table.get_network_names();
+    let kinds = linspace.graph.get_node_kind_list();
+    ExportTrajectory {
+        labels: labels.iter().map(|l| net_table.get_rel_name(*l)).collect(),
+        states: nodes
+            .iter()
+            .map(|n| {
+                let state = linspace.layout.decode(&linspace.states[*n]);
+                State::convert(&state, table, &kinds[*n])
+            })
+            .collect(),
+        transitions: events.iter().map(|ev| TransEvent::new(ev, table)).collect(),
+    }
+}
+
+fn export_diagnosability<'a>(
+    result: &DiagnosabilityResult,
+    table: &'a NetworkIndexTable<'a>,
+) -> ExportDiagnosability<'a> {
+    let net_table = table.get_network_names();
+    let labels = (0..net_table.rel_count())
+        .map(|lbl| match result.labels.get(lbl) {
+            Some(label) => ExportDiagnosable {
+                label: net_table.get_rel_name(lbl),
+                diagnosable: label.diagnosable,
+                counterexample: label.counterexample.as_ref().map(|c| ExportCounterexample {
+                    faulty: export_lasso(&c.faulty, &result.space, table),
+                    correct: export_lasso(&c.correct, &result.space, table),
+                }),
+            },
+            // the label never occurs in the space
+            None => ExportDiagnosable {
+                label: net_table.get_rel_name(lbl),
+                diagnosable: if result.complete { Some(true) } else { None },
+                counterexample: None,
+            },
+        })
+        .collect();
+    ExportDiagnosability {
+        labels,
+        complete: result.complete,
+    }
+}
+
+fn export_lasso<'a>(
+    lasso: &Lasso,
+    space: &FullSpaceResult,
+    table: &'a NetworkIndexTable<'a>,
+) -> ExportLasso<'a> {
+    let kinds = space.graph.get_node_kind_list();
+    let states = lasso
+        .nodes
+        .iter()
+        .map(|n| State::convert(&space.layout.decode(&space.states[*n]), table, &kinds[*n]))
+        .collect();
+    let transitions = lasso
+        .events
+        .iter()
+        .map(|ev| TransEvent::new(ev, table))
+        .collect();
+    ExportLasso {
+        states,
+        transitions,
+        cycle: lasso.cycle,
+    }
+}
+
+fn export_diagnoser(diagnoser: &DiagnoserResult) -> ExportDiagnoser {
+    let (states, transitions) = if let Some(diagnoser) = &diagnoser.diagnoser {
+        (diagnoser.state_count(), diagnoser.transition_count())
+    } else {
+        (0, 0)
+    };
+    ExportDiagnoser {
+        states,
+        transitions,
+        complete: diagnoser.complete,
+    }
+}
+
+fn export_closures<'a>(
+    result: &'a ClosureSpaceResult,
+    table: &'a NetworkIndexTable<'a>,
+) -> ExportClosureSpace<'a> {
+    let space = &result.space;
+    let states = export_state_list(
+        &space.states,
+        &space.layout,
+        space.graph.get_node_kind_list(),
+        table,
+    );
+    let closures = result
+        .closures
+        .iter()
+        .map(|closure| {
+            let arcs = closure
+                .arcs
+                .iter()
+                .map(|(src, next, ev)| InnerArc {
+                    src: closure.states[*src],
+                    next: closure.states[*next],
+                    ev: TransEvent::new(ev, table),
+                })
+                .collect();
+            let exits = closure
+                .exits
+                .iter()
+                .map(|exit| ClosureExit {
+                    state: closure.states[exit.state],
+                    regex: export_regex(&exit.regex, table.get_network_names()),
+                })
+                .collect();
+            ExportClosure {
+                entry: closure.get_entry(),
+                states: closure.states.clone(),
+                arcs,
+                exits,
+            }
+        })
+        .collect();
+    let adjacent = result
+        .closures
+        .iter()
+        .map(|closure| {
+            closure
+                .exits
+                .iter()
+                .flat_map(|exit| {
+                    exit.arcs.iter().map(move |(ev, next)| ClosureArc {
+                        exit: closure.states[exit.state],
+                        next: *next,
+                        ev: TransEvent::new(ev, table),
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    ExportClosureSpace {
+        states,
+        closures,
+        adjacent,
+        complete: result.complete,
+    }
+}
+
 fn export_full_space<'a>(
     full_space: &'a FullSpaceResult,
     table: &'a NetworkIndexTable<'a>,
 ) -> ExportFullSpace<'a> {
     let states = export_state_list(
         &full_space.states,
+        &full_space.layout,
         &full_space.graph.get_node_kind_list(),
         table,
     );
@@ -90,6 +442,7 @@ fn export_lin_space<'a>(
 ) -> ExportLinSpace<'a> {
     let states = export_state_list(
         &lin_space.states,
+        &lin_space.layout,
         &lin_space.graph.get_node_kind_list(),
         table,
     );
@@ -99,16 +452,248 @@ fn export_lin_space<'a>(
         adjacent,
         states,
         complete: lin_space.complete,
+        frontier: &lin_space.frontier,
     }
 }
 
 #[derive(Serialize)]
-struct ExportDiagnosis {
+struct ExportDiagnosis<'a> {
     regex: Option<String>,
+    // number of labels and operators in the regex
+    size: Option<usize>,
+    strategy: Option<&'static str>,
+    // minimal automaton of the regex, missing on timeout
+    dfa: Option<ExportDfa<'a>>,
+    faults: Option<Vec<ExportFault<'a>>>,
+    stats: Option<ExportStats>,
     complete: bool,
     timeout: bool,
 }
 
+/*
+    Size of the explored space and how much of it
+    was removed by the state elimination
+*/
+#[derive(Serialize)]
+struct ExportStats {
+    nodes: usize,
+    frontier: usize,
+    eliminated: usize,
+}
+
+impl From<DiagnosisStats> for ExportStats {
+    fn from(stats: DiagnosisStats) -> Self {
+        Self {
+            nodes: stats.nodes,
+            frontier: stats.frontier,
+            eliminated: stats.eliminated,
+        }
+    }
+}
+
+/*
+    Occurrences of a relevance label on the trajectories
+    consistent with the observation, max is null if unbounded
+*/
+#[derive(Serialize)]
+struct ExportFault<'a> {
+    label: &'a str,
+    class: ExportFaultClass,
+    min: usize,
+    max: Option<usize>,
+}
+
+#[derive(Serialize)]
+enum ExportFaultClass {
+    Certain,
+    Possible,
+    Excluded,
+}
+
+impl From<FaultClass> for ExportFaultClass {
+    fn from(class: FaultClass) -> Self {
+        match class {
+            FaultClass::Certain => Self::Certain,
+            FaultClass::Possible => Self::Possible,
+            FaultClass::Excluded => Self::Excluded,
+        }
+    }
+}
+
+/*
+    State 0 is the initial one, strings
+    with a missing arc are rejected
+*/
+#[derive(Serialize)]
+struct ExportDfa<'a> {
+    adjacent: Vec<Vec<DfaArc<'a>>>,
+    finals: Vec<usize>,
+}
+
+#[derive(Serialize)]
+struct DfaArc<'a> {
+    label: &'a str,
+    next: usize,
+}
+
+#[derive(Serialize)]
+struct ExportCheck<'a> {
+    diagnosis: ExportDiagnosis<'a>,
+    checks: Vec<CheckedString<'a>>,
+}
+
+#[derive(Serialize)]
+struct CheckedString<'a> {
+    string: Vec<&'a str>,
+    // unknown when the diagnosis timed out
+    accepted: Option<bool>,
+}
+
+/*
+    Labels are a string of relevance labels or, when
+    sets is true, the set of labels of the trajectory
+*/
+#[derive(Serialize)]
+struct ExportWitness<'a> {
+    diagnosis: ExportDiagnosis<'a>,
+    sets: bool,
+    witnesses: Vec<ExportTrajectory<'a>>,
+    // false if the search timed out
+    complete: bool,
+}
+
+/*
+    Explanations are sorted from the most likely,
+    prob is the one of the most likely trajectory
+*/
+#[derive(Serialize)]
+struct ExportLikely<'a> {
+    diagnosis: ExportDiagnosis<'a>,
+    explanations: Vec<ExportExplanation<'a>>,
+    // false if the search timed out
+    complete: bool,
+}
+
+#[derive(Serialize)]
+struct ExportExplanation<'a> {
+    prob: f64,
+    trajectory: ExportTrajectory<'a>,
+}
+
+/*
+    Explanations are sorted from the cheapest, cost
+    is the total one of the cheapest trajectory
+*/
+#[derive(Serialize)]
+struct ExportCheapest<'a> {
+    diagnosis: ExportDiagnosis<'a>,
+    explanations: Vec<ExportCostExplanation<'a>>,
+    // false if the search timed out
+    complete: bool,
+}
+
+#[derive(Serialize)]
+struct ExportCostExplanation<'a> {
+    cost: usize,
+    trajectory: ExportTrajectory<'a>,
+}
+
+/*
+    Transition i moves from state i to state i + 1
+*/
+#[derive(Serialize)]
+struct ExportTrajectory<'a> {
+    labels: Vec<&'a str>,
+    states: Vec<State<'a>>,
+    transitions: Vec<TransEvent<'a>>,
+}
+
+/*
+    diagnosable is unknown when the analysis timed out
+    before finding a counterexample
+*/
+#[derive(Serialize)]
+struct ExportDiagnosability<'a> {
+    labels: Vec<ExportDiagnosable<'a>>,
+    complete: bool,
+}
+
+#[derive(Serialize)]
+struct ExportDiagnosable<'a> {
+    label: &'a str,
+    diagnosable: Option<bool>,
+    counterexample: Option<ExportCounterexample<'a>>,
+}
+
+/*
+    Two trajectories with the same observation,
+    only the faulty one contains the label
+*/
+#[derive(Serialize)]
+struct ExportCounterexample<'a> {
+    faulty: ExportLasso<'a>,
+    correct: ExportLasso<'a>,
+}
+
+/*
+    Transition i moves from state i to state i + 1,
+    the transitions from cycle on repeat forever
+*/
+#[derive(Serialize)]
+struct ExportLasso<'a> {
+    states: Vec<State<'a>>,
+    transitions: Vec<TransEvent<'a>>,
+    cycle: usize,
+}
+
+/*
+    States are indexes in the list of the states of
+    the behavioral space, the closure level graph
+    connects closures through observable transitions.
+*/
+#[derive(Serialize)]
+struct ExportClosureSpace<'a> {
+    states: Vec<State<'a>>,
+    closures: Vec<ExportClosure<'a>>,
+    adjacent: Vec<Vec<ClosureArc<'a>>>,
+    complete: bool,
+}
+
+#[derive(Serialize)]
+struct ExportClosure<'a> {
+    entry: usize,
+    states: Vec<usize>,
+    arcs: Vec<InnerArc<'a>>,
+    exits: Vec<ClosureExit>,
+}
+
+#[derive(Serialize)]
+struct InnerArc<'a> {
+    src: usize,
+    next: usize,
+    ev: TransEvent<'a>,
+}
+
+#[derive(Serialize)]
+struct ClosureExit {
+    state: usize,
+    regex: String,
+}
+
+#[derive(Serialize)]
+struct ClosureArc<'a> {
+    exit: usize,
+    next: usize,
+    ev: TransEvent<'a>,
+}
+
+#[derive(Serialize)]
+struct ExportDiagnoser {
+    states: usize,
+    transitions: usize,
+    complete: bool,
+}
+
 #[derive(Serialize)]
 enum ExportResult<'a> {
     Success(Export<'a>),
@@ -119,7 +704,14 @@ enum ExportResult<'a> {
 enum Export<'a> {
     FullSpace(ExportFullSpace<'a>),
     LinSpace(ExportLinSpace<'a>),
-    Diagnosis(ExportDiagnosis),
+    Diagnosis(ExportDiagnosis<'a>),
+    Diagnoser(ExportDiagnoser),
+    Closures(ExportClosureSpace<'a>),
+    Check(ExportCheck<'a>),
+    Witness(ExportWitness<'a>),
+    Diagnosability(ExportDiagnosability<'a>),
+    Likely(ExportLikely<'a>),
+    Cheapest(ExportCheapest<'a>),
 }
 
 impl<'a> From<ExportFullSpace<'a>> for Export<'a> {
@@ -134,12 +726,54 @@ impl<'a> From<ExportLinSpace<'a>> for Export<'a> {
     }
 }
 
-impl<'a> From<ExportDiagnosis> for Export<'a> {
-    fn from(res: ExportDiagnosis) -> Self {
+impl<'a> From<ExportDiagnosis<'a>> for Export<'a> {
+    fn from(res: ExportDiagnosis<'a>) -> Self {
         Self::Diagnosis(res)
     }
 }
 
+impl<'a> From<ExportCheck<'a>> for Export<'a> {
+    fn from(res: ExportCheck<'a>) -> Self {
+        Self::Check(res)
+    }
+}
+
+impl<'a> From<ExportWitness<'a>> for Export<'a> {
+    fn from(res: ExportWitness<'a>) -> Self {
+        Self::Witness(res)
+    }
+}
+
+impl<'a> From<ExportLikely<'a>> for Export<'a> {
+    fn from(res: ExportLikely<'a>) -> Self {
+        Self::Likely(res)
+    }
+}
+
+impl<'a> From<ExportCheapest<'a>> for Export<'a> {
+    fn from(res: ExportCheapest<'a>) -> Self {
+        Self::Cheapest(res)
+    }
+}
+
+impl<'a> From<ExportDiagnosability<'a>> for Export<'a> {
+    fn from(res: ExportDiagnosability<'a>) -> Self {
+        Self::Diagnosability(res)
+    }
+}
+
+impl<'a> From<ExportClosureSpace<'a>> for Export<'a> {
+    fn from(res: ExportClosureSpace<'a>) -> Self {
+        Self::Closures(res)
+    }
+}
+
+impl<'a> From<ExportDiagnoser> for Export<'a> {
+    fn from(res: ExportDiagnoser) -> Self {
+        Self::Diagnoser(res)
+    }
+}
+
 #[derive(Serialize)]
 struct ExportFullSpace<'a> {
     adjacent: Vec<Vec<Arc<'a>>>,
@@ -152,6 +786,8 @@ struct ExportLinSpace<'a> {
     adjacent: Vec<Vec<Arc<'a>>>,
     states: Vec<IndexedState<'a>>,
     complete: bool,
+    // states not expanded before the timeout
+    frontier: &'a [usize],
 }
 
 impl<'a> ExportFullSpace<'a> {
@@ -166,6 +802,7 @@ impl<'a> ExportFullSpace<'a> {
 
 fn export_state_list<'a, T>(
     states: &[network::State],
+    layout: &network::StateLayout,
     state_kinds: &[graph::NodeKind],
     table: &'a NetworkIndexTable,
 ) -> Vec<T>
@@ -173,29 +810,24 @@ where
     T: Convert<'a>,
 {
     zip(states, state_kinds)
-        .map(|(s, k)| T::convert(s, table, k))
+        .map(|(s, k)| T::convert(&layout.decode(s), table, k))
         .collect()
 }
 
-fn export_content<'a>(content: Option<usize>, table: &'a NetNames) -> Option<&'a str> {
-    if let Some(content) = content {
-        let content = table.get_ev_name(content);
-        Some(content)
-    } else {
-        None
-    }
+fn export_content<'a>(content: &[usize], table: &'a NetNames) -> Vec<&'a str> {
+    content.iter().map(|ev| table.get_ev_name(*ev)).collect()
 }
 
 #[derive(Serialize)]
 struct State<'a> {
     states: Vec<&'a str>,
-    links: Vec<(&'a str, Option<&'a str>)>,
+    links: Vec<(&'a str, Vec<&'a str>)>,
     kind: StateKind,
 }
 
 impl<'a> Convert<'a> for State<'a> {
     fn convert(
-        state: &network::State,
+        state: &network::DecodedState,
         index: &'a NetworkIndexTable<'a>,
         kind: &graph::NodeKind,
     ) -> Self {
@@ -230,7 +862,7 @@ struct IndexedState<'a> {
 
 impl<'a> Convert<'a> for IndexedState<'a> {
     fn convert(
-        state: &network::State,
+        state: &network::DecodedState,
         index: &'a NetworkIndexTable<'a>,
         kind: &graph::NodeKind,
     ) -> Self {
@@ -327,7 +959,7 @@ fn convert_arc<'a>(
 
 trait Convert<'a> {
     fn convert(
-        state: &network::State,
+        state: &network::DecodedState,
         index: &'a NetworkIndexTable<'a>,
         kind: &graph::NodeKind,
     ) -> Self;
@@ -356,5 +988,13 @@ fn export_regex(regex: &Regex, table: &NetNames) -> String {
 }
 
 fn join_values(vals: &[usize], table: &NetNames) -> String {
-    vals.iter().map(|r| table.get_rel_name(*r)).collect()
+    vals.iter()
+        .map(|r| {
+            if *r == FRONTIER {
+                UNEXPLORED
+            } else {
+                table.get_rel_name(*r)
+            }
+        })
+        .collect()
 }
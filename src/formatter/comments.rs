@@ -0,0 +1,68 @@
+/*
+    The parser drops comments, so they are
+    collected directly from the source code.
+    Quoted names may contain comment markers:
+    they are skipped as a whole.
+*/
+
+#[derive(Debug, PartialEq)]
+pub struct Comment<'a> {
+    pub text: &'a str,
+    pub begin: usize,
+    pub end: usize,
+}
+
+pub fn collect_comments(code: &str) -> Vec<Comment<'_>> {
+    let bytes = code.as_bytes();
+    let mut output = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ b'\'' | quote @ b'"' => i = skip_after(code, i + 1, &[quote]),
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = code[i..].find('\n').map(|e| i + e).unwrap_or(code.len());
+                output.push(new_comment(code, i, end));
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = skip_after(code, i + 2, b"*/");
+                output.push(new_comment(code, i, end));
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    output
+}
+
+fn new_comment(code: &str, begin: usize, end: usize) -> Comment<'_> {
+    Comment {
+        text: code[begin..end].trim_end(),
+        begin,
+        end,
+    }
+}
+
+fn skip_after(code: &str, begin: usize, pattern: &[u8]) -> usize {
+    let pattern = std::str::from_utf8(pattern).unwrap();
+    code[begin..]
+        .find(pattern)
+        .map(|e| begin + e + pattern.len())
+        .unwrap_or(code.len())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_collect_comments() {
+        let code = "// first\nnetwork 'a//b' {\n    /* block\n    comment */ link L A B // last\n}";
+        let comments: Vec<&str> = collect_comments(code).iter().map(|c| c.text).collect();
+        assert_eq!(
+            comments,
+            vec!["// first", "/* block\n    comment */", "// last"]
+        );
+    }
+}
@@ -0,0 +1,151 @@
+mod comments;
+mod printer;
+
+use fsa_net_parser::{parse, SyntaxError};
+
+/**
+ * Return the canonical form of the given source code.
+ * Comments are preserved, imported files are not followed.
+ */
+pub fn format_code(code: &str) -> Result<String, SyntaxError<'_>> {
+    let ast = parse(code)?;
+    let comments = comments::collect_comments(code);
+    let printer = printer::Printer::new(code, &comments);
+    Ok(printer.print_code(&ast))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use std::path::PathBuf;
+
+    /*
+        Printing without comments is a complete
+        description of the syntax tree: if the
+        output is the same the two trees are equal.
+    */
+    fn print_ast(code: &str) -> String {
+        let ast = parse(code).expect("code should be syntactically correct");
+        printer::Printer::new(code, &[]).print_code(&ast)
+    }
+
+    fn check_format(code: &str) {
+        let formatted = format_code(code).unwrap();
+        assert_eq!(print_ast(code), print_ast(&formatted));
+        assert_eq!(format_code(&formatted).unwrap(), formatted);
+
+        let comments: Vec<&str> = comments::collect_comments(code)
+            .iter()
+            .map(|c| c.text)
+            .collect();
+        let formatted_comments: Vec<&str> = comments::collect_comments(&formatted)
+            .iter()
+            .map(|c| c.text)
+            .collect();
+        assert_eq!(comments, formatted_comments);
+    }
+
+    #[test]
+    fn test_format() {
+        let code = "import \"a.fnl\"\nimport \"b.fnl\"\n\
+                    network N { link L1 A B capacity 2 init  e1 events e1, 'e 2' final  both\n\
+                    automata A { begin s0 trans t s0 s0 trans 'state' {\n\
+                    obs o dst s0 src s0 prob .50 cost 4 output e1(L1)}} automata B { begin s0 state s1 final s0,s1 }}\n\
+                    request N { linspace o save f diagnosis o, {o|'o 2'},? linspace (o|o,o)+,o* diagnosis load f strategy  size diagnosis o check [r,'r 2'],[] diagnosis o witness   3 diagnosability diagnosis o likely 2 diagnosis o cheapest }";
+        let expected = "import \"a.fnl\"\n\
+                        import \"b.fnl\"\n\
+                        \n\
+                        network N {\n    \
+                            link L1 A B capacity 2 init e1\n    \
+                            events e1, 'e 2'\n    \
+                            final both\n\
+                        \n    \
+                            automata A {\n        \
+                                begin s0\n        \
+                                trans t s0 s0\n\
+                        \n        \
+                                trans 'state' {\n            \
+                                    src s0\n            \
+                                    dst s0\n            \
+                                    output e1(L1)\n            \
+                                    obs o\n            \
+                                    prob 0.5\n            \
+                                    cost 4\n        \
+                                }\n    \
+                            }\n\
+                        \n    \
+                            automata B {\n        \
+                                begin s0\n        \
+                                state s1\n        \
+                                final s0, s1\n    \
+                            }\n\
+                        }\n\
+                        \n\
+                        request N {\n    \
+                            linspace o save f\n    \
+                            diagnosis o, {o | 'o 2'}, ?\n    \
+                            linspace (o | o, o)+, o*\n    \
+                            diagnosis load f strategy size\n    \
+                            diagnosis o check [r, 'r 2'], []\n    \
+                            diagnosis o witness 3\n    \
+                            diagnosability\n    \
+                            diagnosis o likely 2\n    \
+                            diagnosis o cheapest\n\
+                        }\n";
+        assert_eq!(format_code(code).unwrap(), expected);
+        check_format(code);
+    }
+
+    #[test]
+    fn test_format_comments() {
+        let code = "// header\nnetwork N {\n    link L A B // link\n  /* before\n automata */\n    automata A {\n    begin s0\n    // end of automata\n    }\n}\n// end of file\n";
+        let expected = "// header\n\
+                        network N {\n    \
+                            link L A B // link\n\
+                        \n    \
+                            /* before\n automata */\n    \
+                            automata A {\n        \
+                                begin s0\n        \
+                                // end of automata\n    \
+                            }\n\
+                        }\n\
+                        \n\
+                        // end of file\n";
+        assert_eq!(format_code(code).unwrap(), expected);
+        check_format(code);
+    }
+
+    #[test]
+    fn test_format_transition_comments() {
+        let code = "network N {\n    automata A {\n    begin s0\n    trans t {\n    // destination\n    dst s0\n    obs o // label\n    src s0\n    // end of transition\n    }\n    }\n}\n";
+        let expected = "network N {\n    \
+                            automata A {\n        \
+                                begin s0\n\
+                        \n        \
+                                trans t {\n            \
+                                    src s0\n            \
+                                    // destination\n            \
+                                    dst s0\n            \
+                                    obs o // label\n            \
+                                    // end of transition\n        \
+                                }\n    \
+                            }\n\
+                        }\n";
+        assert_eq!(format_code(code).unwrap(), expected);
+        check_format(code);
+    }
+
+    #[test]
+    fn test_format_test_code() {
+        for dir in &["fnl-test-code", "fsa-net-parser/fnl-test-code"] {
+            for entry in PathBuf::from(dir).read_dir().unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().map(|ext| ext == "fnl").unwrap_or(false) {
+                    let code = std::fs::read_to_string(&path).unwrap();
+                    check_format(&code);
+                }
+            }
+        }
+    }
+}
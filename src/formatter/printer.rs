@@ -0,0 +1,513 @@
+use super::comments::Comment;
+use fsa_net_parser::syntax_tree::*;
+use fsa_net_parser::KEYWORDS;
+
+type Loc = (usize, usize);
+
+const INDENT: &str = "    ";
+
+/*
+    Print the syntax tree in canonical form.
+    Each comment is printed before the first item
+    that begins after it, unless it is on the
+    same line of the previous item: in that case
+    it is kept at the end of that line.
+    Multi line items are surrounded by blank lines.
+*/
+pub struct Printer<'a, 'b> {
+    code: &'a str,
+    comments: &'b [Comment<'a>],
+    next: usize,
+    indent: usize,
+    output: String,
+}
+
+impl<'a, 'b> Printer<'a, 'b> {
+    pub fn new(code: &'a str, comments: &'b [Comment<'a>]) -> Self {
+        Self {
+            code,
+            comments,
+            next: 0,
+            indent: 0,
+            output: String::with_capacity(code.len()),
+        }
+    }
+
+    pub fn print_code(mut self, code: &[Block]) -> String {
+        let mut prev: Option<&Block> = None;
+        for blk in code {
+            let loc = block_location(blk);
+            if let Some(prev) = prev {
+                let imports = matches!((prev, blk), (Block::Import(_), Block::Import(_)));
+                if !imports || self.has_comment_before(loc.0) {
+                    self.output.push('\n');
+                }
+            }
+            self.leading_comments(loc.0);
+            match blk {
+                Block::Network(net) => self.print_network(net),
+                Block::Request(req) => self.print_request(req),
+                Block::Import(import) => self.line(format!("import \"{}\"", import.path)),
+                Block::Template(template) => self.print_template(template),
+            }
+            self.trailing_comment(loc.1);
+            prev = Some(blk);
+        }
+        if self.has_comment_before(usize::MAX) {
+            if prev.is_some() {
+                self.output.push('\n');
+            }
+            self.leading_comments(usize::MAX);
+        }
+        self.output
+    }
+
+    fn print_network(&mut self, net: &Network) {
+        self.line(format!("network {} {{", name(net.name)));
+        self.print_body(
+            &net.params,
+            net.get_location().1,
+            |param| param.get_location(),
+            |param| is_multiline_network_param(&param.param),
+            |this, param| this.print_network_param(&param.param),
+        );
+        self.line("}");
+    }
+
+    fn print_network_param(&mut self, param: &NetworkParameter) {
+        match param {
+            NetworkParameter::Automata(automata) => {
+                self.line(format!("automata {} {{", name(automata.name)));
+                self.print_automata_body(&automata.params, automata.get_location().1);
+                self.line("}");
+            }
+            NetworkParameter::Template(template) => self.print_template(template),
+            NetworkParameter::Instance(instance) => self.line(format!(
+                "automata {} = {}({})",
+                name(instance.name),
+                name(instance.template),
+                name_list(&instance.args)
+            )),
+            NetworkParameter::Link(link) => {
+                let mut text = format!(
+                    "link {} {} {}",
+                    name(link.name),
+                    name(link.source),
+                    name(link.destination)
+                );
+                if let Some(capacity) = link.capacity {
+                    text.push_str(&format!(" capacity {}", capacity));
+                }
+                if let Some(init) = link.init {
+                    text.push_str(&format!(" init {}", name(init)));
+                }
+                self.line(text)
+            }
+            NetworkParameter::Events(list) => self.line(format!("events {}", name_list(list))),
+            NetworkParameter::ObserveLabels(list) => self.line(format!("obs {}", name_list(list))),
+            NetworkParameter::RelevanceLabels(list) => {
+                self.line(format!("rel {}", name_list(list)))
+            }
+            NetworkParameter::Final(criterion) => self.line(format!("final {}", criterion.name())),
+        }
+    }
+
+    fn print_template(&mut self, template: &AutomataTemplate) {
+        self.line(format!(
+            "automata template {}({}) {{",
+            name(template.name),
+            name_list(&template.args)
+        ));
+        self.print_automata_body(&template.params, template.get_location().1);
+        self.line("}");
+    }
+
+    fn print_automata_body(&mut self, params: &[AutomataParameterDecl], end: usize) {
+        self.print_body(
+            params,
+            end,
+            |param| param.get_location(),
+            |param| is_multiline_automata_param(&param.param),
+            |this, param| this.print_automata_param(&param.param),
+        );
+    }
+
+    fn print_automata_param(&mut self, param: &AutomataParameter) {
+        match param {
+            AutomataParameter::StateDecl(StateDeclaration::State(state)) => {
+                self.line(format!("state {}", name(state)))
+            }
+            AutomataParameter::StateDecl(StateDeclaration::Begin(state)) => {
+                self.line(format!("begin {}", name(state)))
+            }
+            AutomataParameter::Transition(trans) => self.print_transition(trans),
+            AutomataParameter::Final(states) => self.line(format!("final {}", name_list(states))),
+        }
+    }
+
+    fn print_transition(&mut self, trans: &TransitionDeclaration) {
+        if !is_complex_transition(trans) {
+            self.line(format!(
+                "trans {} {} {}",
+                name(trans.name),
+                name(trans.source),
+                name(trans.destination)
+            ));
+            return;
+        }
+
+        self.line(format!("trans {} {{", name(trans.name)));
+        self.indent += 1;
+        let mut keys = vec![
+            (
+                TransitionKeyName::Src,
+                format!("src {}", name(trans.source)),
+            ),
+            (
+                TransitionKeyName::Dst,
+                format!("dst {}", name(trans.destination)),
+            ),
+        ];
+        if let Some(input) = &trans.input {
+            keys.push((TransitionKeyName::Input, format!("input {}", event(input))));
+        }
+        if let Some(output) = &trans.output {
+            let output: Vec<String> = output.iter().map(event).collect();
+            keys.push((
+                TransitionKeyName::Output,
+                format!("output {}", output.join(", ")),
+            ));
+        }
+        if let Some(rel) = trans.rel_label {
+            keys.push((TransitionKeyName::Rel, format!("rel {}", name(rel))));
+        }
+        if let Some(obs) = trans.obs_label {
+            keys.push((TransitionKeyName::Obs, format!("obs {}", name(obs))));
+        }
+        if let Some(prob) = trans.prob {
+            keys.push((TransitionKeyName::Prob, format!("prob {}", prob)));
+        }
+        if let Some(cost) = trans.cost {
+            keys.push((TransitionKeyName::Cost, format!("cost {}", cost)));
+        }
+
+        let comments = self.key_comments(trans);
+        for (key, text) in keys {
+            for (_, _, comment) in comments.iter().filter(|c| c.0 == Some(key) && !c.1) {
+                self.line(comment);
+            }
+            match comments.iter().find(|c| c.0 == Some(key) && c.1) {
+                Some((_, _, comment)) => self.line(format!("{} {}", text, comment)),
+                None => self.line(text),
+            }
+        }
+        for (_, _, comment) in comments.iter().filter(|c| c.0.is_none()) {
+            self.line(comment);
+        }
+        self.indent -= 1;
+        self.line("}");
+    }
+
+    /*
+        Keys are printed in canonical order, so the comments
+        in the body move with their key: the one on their line,
+        flagged as trailing, or else the one that follows them.
+        Comments after the last key have no key.
+    */
+    fn key_comments(
+        &mut self,
+        trans: &TransitionDeclaration,
+    ) -> Vec<(Option<TransitionKeyName>, bool, &'a str)> {
+        let end = trans.get_location().1;
+        let mut output = vec![];
+        while self.has_comment_before(end) {
+            let comment = &self.comments[self.next];
+            let prev = trans
+                .keys
+                .iter()
+                .rev()
+                .find(|(_, (_, key_end))| *key_end <= comment.begin);
+            let attached = match prev {
+                Some((key, (_, key_end))) if !self.code[*key_end..comment.begin].contains('\n') => {
+                    (Some(*key), true)
+                }
+                _ => {
+                    let next = trans
+                        .keys
+                        .iter()
+                        .find(|(_, (begin, _))| *begin > comment.begin);
+                    (next.map(|(key, _)| *key), false)
+                }
+            };
+            output.push((attached.0, attached.1, comment.text));
+            self.next += 1;
+        }
+        output
+    }
+
+    fn print_request(&mut self, req: &Request) {
+        self.line(format!("request {} {{", name(req.name)));
+        self.print_body(
+            &req.list,
+            req.get_location().1,
+            |cmd| cmd.get_location(),
+            |_| false,
+            |this, cmd| this.print_command(&cmd.cmd),
+        );
+        self.line("}");
+    }
+
+    fn print_command(&mut self, cmd: &Command) {
+        let text = match cmd {
+            Command::Space => "space".to_owned(),
+            Command::Closures => "closures".to_owned(),
+            Command::Diagnosability => "diagnosability".to_owned(),
+            Command::Linspace(linspace) => {
+                let mut text = format!("linspace {}", obs_list(&linspace.obs_list));
+                if let Some(file) = linspace.save_file {
+                    text.push_str(&format!(" save {}", name(file)));
+                }
+                text
+            }
+            Command::Diagnosis(diagnosis) => diagnosis_command(diagnosis),
+            Command::Check(check) => {
+                let strings: Vec<String> = check
+                    .strings
+                    .iter()
+                    .map(|string| format!("[{}]", name_list(string)))
+                    .collect();
+                format!(
+                    "{} check {}",
+                    diagnosis_command(&check.diagnosis),
+                    strings.join(", ")
+                )
+            }
+            Command::Witness(witness) => {
+                let mut text = format!("{} witness", fresh_diagnosis(&witness.diagnosis));
+                if let Some(length) = witness.length {
+                    text.push_str(&format!(" {}", length));
+                }
+                text
+            }
+            Command::Likely(likely) => {
+                let mut text = format!("{} likely", fresh_diagnosis(&likely.diagnosis));
+                if let Some(count) = likely.count {
+                    text.push_str(&format!(" {}", count));
+                }
+                text
+            }
+            Command::Cheapest(cheapest) => {
+                let mut text = format!("{} cheapest", fresh_diagnosis(&cheapest.diagnosis));
+                if let Some(count) = cheapest.count {
+                    text.push_str(&format!(" {}", count));
+                }
+                text
+            }
+            Command::Diagnoser(DiagnoserCommand::Build(diagnoser)) => {
+                let mut text = "diagnoser".to_owned();
+                if !diagnoser.name_list.is_empty() {
+                    text.push_str(&format!(" {}", name_list(&diagnoser.name_list)));
+                }
+                if let Some(file) = diagnoser.save_file {
+                    text.push_str(&format!(" save {}", name(file)));
+                }
+                text
+            }
+            Command::Diagnoser(DiagnoserCommand::Load(diagnoser)) => format!(
+                "diagnoser load {} {}",
+                name(diagnoser.file),
+                name_list(&diagnoser.name_list)
+            ),
+        };
+        self.line(text);
+    }
+
+    fn print_body<T>(
+        &mut self,
+        items: &[T],
+        end: usize,
+        location: impl Fn(&T) -> Loc,
+        multiline: impl Fn(&T) -> bool,
+        print: impl Fn(&mut Self, &T),
+    ) {
+        self.indent += 1;
+        let mut prev_multiline = None;
+        for item in items {
+            let (begin, end) = location(item);
+            let curr_multiline = multiline(item);
+            if let Some(prev_multiline) = prev_multiline {
+                if prev_multiline || curr_multiline {
+                    self.output.push('\n');
+                }
+            }
+            self.leading_comments(begin);
+            print(self, item);
+            self.trailing_comment(end);
+            prev_multiline = Some(curr_multiline);
+        }
+        if self.has_comment_before(end) {
+            if prev_multiline == Some(true) {
+                self.output.push('\n');
+            }
+            self.leading_comments(end);
+        }
+        self.indent -= 1;
+    }
+
+    fn has_comment_before(&self, pos: usize) -> bool {
+        self.comments
+            .get(self.next)
+            .map(|c| c.begin < pos)
+            .unwrap_or(false)
+    }
+
+    fn leading_comments(&mut self, pos: usize) {
+        while self.has_comment_before(pos) {
+            let text = self.comments[self.next].text;
+            self.line(text);
+            self.next += 1;
+        }
+    }
+
+    /*
+        The comment is on the same line of the
+        item when no line break separates them
+    */
+    fn trailing_comment(&mut self, end: usize) {
+        if let Some(comment) = self.comments.get(self.next) {
+            if comment.begin >= end && !self.code[end..comment.begin].contains('\n') {
+                self.output.pop();
+                self.output.push(' ');
+                self.output.push_str(comment.text);
+                self.output.push('\n');
+                self.next += 1;
+            }
+        }
+    }
+
+    fn line<S: AsRef<str>>(&mut self, text: S) {
+        for _ in 0..self.indent {
+            self.output.push_str(INDENT);
+        }
+        self.output.push_str(text.as_ref());
+        self.output.push('\n');
+    }
+}
+
+fn block_location(blk: &Block) -> Loc {
+    match blk {
+        Block::Network(net) => net.get_location(),
+        Block::Request(req) => req.get_location(),
+        Block::Import(import) => import.get_location(),
+        Block::Template(template) => template.get_location(),
+    }
+}
+
+fn is_multiline_network_param(param: &NetworkParameter) -> bool {
+    matches!(
+        param,
+        NetworkParameter::Automata(_) | NetworkParameter::Template(_)
+    )
+}
+
+fn is_multiline_automata_param(param: &AutomataParameter) -> bool {
+    match param {
+        AutomataParameter::Transition(trans) => is_complex_transition(trans),
+        AutomataParameter::StateDecl(_) | AutomataParameter::Final(_) => false,
+    }
+}
+
+fn is_complex_transition(trans: &TransitionDeclaration) -> bool {
+    trans.input.is_some()
+        || trans.output.is_some()
+        || trans.rel_label.is_some()
+        || trans.obs_label.is_some()
+        || trans.prob.is_some()
+        || trans.cost.is_some()
+}
+
+fn event(ev: &Event) -> String {
+    format!("{}({})", name(ev.name), name(ev.link))
+}
+
+fn name_list(list: &[&str]) -> String {
+    let list: Vec<String> = list.iter().map(|n| name(n)).collect();
+    list.join(", ")
+}
+
+fn diagnosis_command(diagnosis: &DiagnosisCommand) -> String {
+    match diagnosis {
+        DiagnosisCommand::Fresh(diagnosis) => fresh_diagnosis(diagnosis),
+        DiagnosisCommand::Load(diagnosis) => format!(
+            "diagnosis load {}{}",
+            name(diagnosis.file),
+            strategy(&diagnosis.order)
+        ),
+    }
+}
+
+fn fresh_diagnosis(diagnosis: &FreshDiagnosisCommand) -> String {
+    format!(
+        "diagnosis {}{}",
+        obs_list(&diagnosis.obs_list),
+        strategy(&diagnosis.order)
+    )
+}
+
+fn strategy(order: &Option<EliminationOrder>) -> String {
+    match order {
+        Some(order) => format!(" strategy {}", order.name()),
+        None => String::new(),
+    }
+}
+
+fn obs_list(list: &[ObsStep]) -> String {
+    let list: Vec<String> = list.iter().map(obs_step).collect();
+    list.join(", ")
+}
+
+fn obs_step(step: &ObsStep) -> String {
+    match step {
+        ObsStep::Label(label) => name(label),
+        ObsStep::Set(set) => {
+            let set: Vec<String> = set.iter().map(|n| name(n)).collect();
+            format!("{{{}}}", set.join(" | "))
+        }
+        ObsStep::Any => "?".to_owned(),
+        ObsStep::Group(alts) => {
+            let alts: Vec<String> = alts.iter().map(|alt| obs_list(alt)).collect();
+            format!("({})", alts.join(" | "))
+        }
+        ObsStep::Star(step) => format!("{}*", obs_step(step)),
+        ObsStep::Plus(step) => format!("{}+", obs_step(step)),
+    }
+}
+
+/*
+    Names are quoted only when they
+    cannot be written as simple names
+*/
+fn name(name: &str) -> String {
+    let simple = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric());
+    if simple && !KEYWORDS.contains(&name) {
+        name.to_owned()
+    } else if !name.contains('\'') {
+        format!("'{}'", name)
+    } else {
+        format!("\"{}\"", name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_name() {
+        assert_eq!(name("A1"), "A1");
+        assert_eq!(name("state"), "'state'");
+        assert_eq!(name("Test Name"), "'Test Name'");
+        assert_eq!(name("it's"), "\"it's\"");
+    }
+}
@@ -42,10 +42,24 @@ impl<'a, T> Graph<T> {
     }
 
     pub fn prune<K>(self, states: Vec<K>) -> (Self, Vec<K>) {
-        let prune = prune_list(&self.adjacent, &self.nodes);
+        let (output_graph, states, _) = self.prune_keeping(states, &[]);
+        (output_graph, states)
+    }
+
+    /**
+     * Like prune, the nodes in `keep` are never removed,
+     * as they were final ones: their new indexes are returned
+     */
+    pub fn prune_keeping<K>(self, states: Vec<K>, keep: &[usize]) -> (Self, Vec<K>, Vec<usize>) {
+        let prune = prune_list(&self.adjacent, &self.nodes, keep);
+        let remapper = IndexRemap::new(self.adjacent.len(), &prune);
+        let keep = keep
+            .iter()
+            .map(|k| remapper.remap_node(*k).unwrap())
+            .collect();
         let output_graph = self.prune_nodes(&prune).0;
         let states = filter_by_index(states, &prune);
-        (output_graph, states)
+        (output_graph, states, keep)
     }
 
     pub fn chain_transaction(&mut self, nodes: &[usize]) -> Vec<T> {
@@ -329,7 +343,7 @@ pub enum NodeKind {
     Final,
 }
 
-fn prune_list<T>(adj: &AdjList<T>, kind_list: &[NodeKind]) -> Vec<usize> {
+fn prune_list<T>(adj: &AdjList<T>, kind_list: &[NodeKind], keep: &[usize]) -> Vec<usize> {
     let mut reach: Vec<bool> = kind_list
         .iter()
         .map(|k| match k {
@@ -337,6 +351,9 @@ fn prune_list<T>(adj: &AdjList<T>, kind_list: &[NodeKind]) -> Vec<usize> {
             NodeKind::Simple => false,
         })
         .collect();
+    for k in keep {
+        reach[*k] = true;
+    }
     let mut seen = reach.clone();
 
     for node in 0..adj.len() {
@@ -437,7 +454,7 @@ mod test {
     #[test]
     fn test_prune_list() {
         let graph = build_test_graph();
-        let prune = prune_list(&graph.adjacent, &graph.nodes);
+        let prune = prune_list(&graph.adjacent, &graph.nodes, &[]);
         assert_eq!(prune, vec![1, 7, 10, 12, 13, 14, 15]);
     }
     #[test]
@@ -484,8 +501,17 @@ mod test {
         }
 
         let graph = builder.build_graph();
-        let prune = prune_list(&graph.adjacent, &graph.nodes);
+        let prune = prune_list(&graph.adjacent, &graph.nodes, &[]);
         assert_eq!(prune, vec![13, 14], "{:?}", prune);
+
+        let prune = prune_list(&graph.adjacent, &graph.nodes, &[14]);
+        assert_eq!(prune.len(), 0, "{:?}", prune);
+
+        let states: Vec<usize> = (0..15).collect();
+        let (graph, states, keep) = graph.prune_keeping(states, &[13]);
+        assert_eq!(graph.nodes.len(), 14);
+        assert_eq!(states.last(), Some(&13));
+        assert_eq!(keep, vec![13]);
     }
 
     #[test]
@@ -517,7 +543,7 @@ mod test {
         ];
         assert_eq!(graph.adjacent, expected_adjacent);
 
-        let prune = prune_list(&graph.adjacent, &graph.nodes);
+        let prune = prune_list(&graph.adjacent, &graph.nodes, &[]);
         assert_eq!(prune.len(), 0);
     }
 
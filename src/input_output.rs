@@ -24,6 +24,14 @@ pub fn get_fsa_code(file_path: &Option<path::PathBuf>) -> io::Result<String> {
     }
 }
 
+pub fn get_input_name(file_path: &Option<path::PathBuf>) -> String {
+    if let Some(file_path) = file_path {
+        file_path.display().to_string()
+    } else {
+        "<stdin>".to_owned()
+    }
+}
+
 pub fn save_str_to_file<'a, P>(s: &str, file: P) -> io::Result<()>
 where
     P: AsRef<path::Path>,
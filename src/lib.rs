@@ -0,0 +1,15 @@
+pub mod compiler;
+pub mod diagnostics;
+pub mod engine;
+pub mod export_results;
+pub mod formatter;
+pub mod input_output;
+pub mod lsp;
+pub mod source_loader;
+pub mod timer;
+
+mod command;
+mod graph;
+mod network;
+mod state_table;
+mod utils;
@@ -0,0 +1,127 @@
+use super::position::to_range;
+use super::symbols::SymbolIndex;
+use crate::compiler::compile;
+use crate::diagnostics::{compile_error_reports, Report, SourceMap};
+use crate::source_loader::{load_sources_from_code, parse_sources};
+
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Url};
+use std::path::PathBuf;
+
+/*
+    An open document. The symbol index is kept
+    from the last syntactically correct version so
+    that completion works while the user is typing.
+*/
+pub struct Document {
+    uri: Url,
+    code: String,
+    index: Option<SymbolIndex>,
+}
+
+impl Document {
+    pub fn new(uri: Url, code: String) -> Self {
+        let mut output = Self {
+            uri,
+            code: String::new(),
+            index: None,
+        };
+        output.update(code);
+        output
+    }
+
+    pub fn update(&mut self, code: String) {
+        if let Some(index) = SymbolIndex::build(&code) {
+            self.index = Some(index);
+        }
+        self.code = code;
+    }
+
+    pub fn get_code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn get_uri(&self) -> &Url {
+        &self.uri
+    }
+
+    pub fn get_index(&self) -> Option<&SymbolIndex> {
+        self.index.as_ref()
+    }
+
+    /**
+     * Load, parse and compile the document
+     * and its imports. Only the errors located
+     * in this document are returned.
+     */
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let path = self.uri.to_file_path().ok();
+        let name = path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| self.uri.to_string());
+        let (sources, reports) = load_sources_from_code(name, self.code.clone(), path.as_deref());
+
+        let reports = if !reports.is_empty() {
+            reports
+        } else {
+            match parse_sources(&sources) {
+                Ok(code) => match compile(&code) {
+                    Ok(_) => vec![],
+                    Err(errors) => compile_error_reports(&errors),
+                },
+                Err(reports) => reports,
+            }
+        };
+
+        reports
+            .iter()
+            .filter_map(|report| self.to_diagnostic(report, &sources))
+            .collect()
+    }
+
+    fn to_diagnostic(&self, report: &Report, sources: &SourceMap) -> Option<Diagnostic> {
+        let loc = report.get_location()?;
+        // the document is always the first file
+        if sources.find_file(loc.0).get_offset() != 0 {
+            return None;
+        }
+
+        let mut message = report.get_message().to_owned();
+        for note in report.get_notes() {
+            message.push('\n');
+            message.push_str(note);
+        }
+        let related = report
+            .get_labels()
+            .filter(|(label, _, primary)| !primary || *label != loc)
+            .filter_map(|(loc, msg, _)| {
+                let location = self.to_location(loc, sources)?;
+                Some(DiagnosticRelatedInformation {
+                    location,
+                    message: msg.unwrap_or_default().to_owned(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut output = Diagnostic::new_simple(to_range(&self.code, loc), message);
+        output.severity = Some(DiagnosticSeverity::ERROR);
+        output.source = Some("fsa-net".to_owned());
+        if !related.is_empty() {
+            output.related_information = Some(related);
+        }
+        Some(output)
+    }
+
+    fn to_location(&self, loc: (usize, usize), sources: &SourceMap) -> Option<Location> {
+        let file = sources.find_file(loc.0);
+        let offset = file.get_offset();
+        let uri = if offset == 0 {
+            self.uri.clone()
+        } else {
+            let path = PathBuf::from(file.get_name()).canonicalize().ok()?;
+            Url::from_file_path(path).ok()?
+        };
+        let range = to_range(file.get_code(), (loc.0 - offset, loc.1 - offset));
+        Some(Location::new(uri, range))
+    }
+}
@@ -0,0 +1,348 @@
+/*
+    Language server for FNL files.
+    Documents are synchronized in full: each change
+    sends the whole text, that is analyzed again.
+*/
+
+mod document;
+mod position;
+mod symbols;
+mod tokens;
+
+use document::Document;
+use position::{to_offset, to_range};
+
+use ahash::AHashMap;
+use fsa_net_parser::{CONTEXTUAL_KEYWORDS, KEYWORDS};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, GotoDefinition, HoverRequest, References, Request as RequestTrait,
+};
+use lsp_types::*;
+use serde::de::DeserializeOwned;
+
+type LspError = Box<dyn std::error::Error + Sync + Send>;
+
+/**
+ * Run the server on stdin and stdout
+ * until the client asks to exit
+ */
+pub fn run() -> Result<(), LspError> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = serde_json::to_value(server_capabilities())?;
+    connection.initialize(capabilities)?;
+
+    let mut server = Server::default();
+    for msg in &connection.receiver {
+        match msg {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    break;
+                }
+                let resp = server.handle_request(req);
+                connection.sender.send(Message::Response(resp))?;
+            }
+            Message::Notification(not) => {
+                for not in server.handle_notification(not) {
+                    connection.sender.send(Message::Notification(not))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    // the writer thread stops when the connection is dropped
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions::default()),
+        ..Default::default()
+    }
+}
+
+#[derive(Default)]
+struct Server {
+    documents: AHashMap<Url, Document>,
+}
+
+impl Server {
+    fn handle_request(&self, req: Request) -> Response {
+        let id = req.id.clone();
+        let result = match req.method.as_str() {
+            GotoDefinition::METHOD => parse_params(req).map(|p| to_json(self.definition(p))),
+            References::METHOD => parse_params(req).map(|p| to_json(self.references(p))),
+            HoverRequest::METHOD => parse_params(req).map(|p| to_json(self.hover(p))),
+            Completion::METHOD => parse_params(req).map(|p| to_json(self.completion(p))),
+            method => Err((
+                ErrorCode::MethodNotFound,
+                format!("unsupported method `{}`", method),
+            )),
+        };
+        match result {
+            Ok(result) => Response::new_ok(id, result),
+            Err((code, msg)) => Response::new_err(id, code as i32, msg),
+        }
+    }
+
+    /**
+     * Return the notifications to send
+     * back to the client, if any
+     */
+    fn handle_notification(&mut self, not: Notification) -> Vec<Notification> {
+        let uri = match not.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams = match extract(not.params) {
+                    Some(params) => params,
+                    None => return vec![],
+                };
+                let uri = params.text_document.uri;
+                let doc = Document::new(uri.clone(), params.text_document.text);
+                self.documents.insert(uri.clone(), doc);
+                uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams = match extract(not.params) {
+                    Some(params) => params,
+                    None => return vec![],
+                };
+                let uri = params.text_document.uri;
+                let text = params.content_changes.into_iter().last();
+                match (self.documents.get_mut(&uri), text) {
+                    (Some(doc), Some(text)) => doc.update(text.text),
+                    _ => return vec![],
+                }
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Some(params) = extract::<DidCloseTextDocumentParams>(not.params) {
+                    let uri = params.text_document.uri;
+                    self.documents.remove(&uri);
+                    return vec![publish_diagnostics(uri, vec![])];
+                }
+                return vec![];
+            }
+            _ => return vec![],
+        };
+
+        let diagnostics = self.documents[&uri].diagnostics();
+        vec![publish_diagnostics(uri, diagnostics)]
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let (doc, offset) = self.locate(&params.text_document_position_params)?;
+        let symbol = doc.get_index()?.definition_at(offset)?;
+        let location = Location::new(doc.get_uri().clone(), to_range(doc.get_code(), symbol.loc));
+        Some(GotoDefinitionResponse::Scalar(location))
+    }
+
+    fn references(&self, params: ReferenceParams) -> Option<Vec<Location>> {
+        let (doc, offset) = self.locate(&params.text_document_position)?;
+        let index = doc.get_index()?;
+        let symbol = index.definition_at(offset)?;
+        let mut locations = index.references(symbol);
+        if params.context.include_declaration {
+            locations.insert(0, symbol.loc);
+        }
+        let output = locations
+            .into_iter()
+            .map(|loc| Location::new(doc.get_uri().clone(), to_range(doc.get_code(), loc)))
+            .collect();
+        Some(output)
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let (doc, offset) = self.locate(&params.text_document_position_params)?;
+        let index = doc.get_index()?;
+        let symbol = index.definition_at(offset)?;
+        let range = index
+            .name_location(offset)
+            .map(|loc| to_range(doc.get_code(), loc));
+        Some(Hover {
+            contents: HoverContents::Scalar(MarkedString::String(symbol.detail.clone())),
+            range,
+        })
+    }
+
+    /*
+        Keywords are always proposed, names only
+        when they are visible from the cursor position
+    */
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let (doc, offset) = self.locate(&params.text_document_position)?;
+        let keywords = KEYWORDS
+            .iter()
+            .chain(CONTEXTUAL_KEYWORDS)
+            .map(|kw| CompletionItem {
+                label: kw.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                ..Default::default()
+            });
+        let mut items: Vec<CompletionItem> = keywords.collect();
+        if let Some(index) = doc.get_index() {
+            for symbol in index.visible_symbols(offset) {
+                if !items.iter().any(|item| item.label == symbol.name) {
+                    items.push(CompletionItem {
+                        label: symbol.name.clone(),
+                        kind: Some(CompletionItemKind::VARIABLE),
+                        detail: Some(symbol.detail.clone()),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn locate(&self, position: &TextDocumentPositionParams) -> Option<(&Document, usize)> {
+        let doc = self.documents.get(&position.text_document.uri)?;
+        let offset = to_offset(doc.get_code(), position.position);
+        Some((doc, offset))
+    }
+}
+
+fn parse_params<P: DeserializeOwned>(req: Request) -> Result<P, (ErrorCode, String)> {
+    serde_json::from_value(req.params).map_err(|err| (ErrorCode::InvalidParams, err.to_string()))
+}
+
+fn extract<P: DeserializeOwned>(params: serde_json::Value) -> Option<P> {
+    serde_json::from_value(params).ok()
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+fn publish_diagnostics(uri: Url, diagnostics: Vec<Diagnostic>) -> Notification {
+    let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+    Notification::new(PublishDiagnostics::METHOD.to_owned(), params)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use test_utils::load_code_from_file;
+
+    fn open(server: &mut Server, uri: &Url, code: &str) -> Vec<Diagnostic> {
+        let params = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "fnl".to_owned(), 0, code.to_owned()),
+        };
+        let not = Notification::new(DidOpenTextDocument::METHOD.to_owned(), params);
+        let mut output = server.handle_notification(not);
+        assert_eq!(output.len(), 1);
+        let params: PublishDiagnosticsParams = extract(output.remove(0).params).unwrap();
+        params.diagnostics
+    }
+
+    fn position(uri: &Url, code: &str, pattern: &str, shift: usize) -> TextDocumentPositionParams {
+        let offset = code.find(pattern).unwrap() + shift;
+        TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(uri.clone()),
+            position::to_position(code, offset),
+        )
+    }
+
+    fn request<P: serde::Serialize>(server: &Server, method: &str, params: P) -> serde_json::Value {
+        let req = Request::new(1.into(), method.to_owned(), params);
+        let resp = server.handle_request(req);
+        assert!(resp.error.is_none());
+        resp.result.unwrap()
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut server = Server::default();
+        let uri = Url::parse("untitled:test.fnl").unwrap();
+        let code = "network N {\n    automata A {\n        begin s0\n        state s0\n    }\n}";
+        let diagnostics = open(&mut server, &uri, code);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "state `s0` is defined multiple times"
+        );
+        assert_eq!(diagnostics[0].range.start, Position::new(3, 8));
+        assert_eq!(
+            diagnostics[0].related_information.as_ref().unwrap().len(),
+            1
+        );
+
+        let diagnostics = open(&mut server, &uri, "network N {");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("unexpected end of file\nexpected one of"));
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut server = Server::default();
+        let uri = Url::parse("untitled:simple-network.fnl").unwrap();
+        let code = load_code_from_file("simple-network");
+        assert!(open(&mut server, &uri, &code).is_empty());
+
+        let params = GotoDefinitionParams {
+            text_document_position_params: position(&uri, &code, "e3(L3)", 3),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let result = request(&server, GotoDefinition::METHOD, params);
+        let result: Location = serde_json::from_value(result).unwrap();
+        assert_eq!(result.range.start, Position::new(4, 9));
+
+        let params = ReferenceParams {
+            text_document_position: position(&uri, &code, "L3 TestA", 0),
+            context: ReferenceContext {
+                include_declaration: true,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let result = request(&server, References::METHOD, params);
+        let result: Vec<Location> = serde_json::from_value(result).unwrap();
+        assert_eq!(result.len(), 5);
+
+        let params = HoverParams {
+            text_document_position_params: position(&uri, &code, "e3(L3)", 3),
+            work_done_progress_params: Default::default(),
+        };
+        let result = request(&server, HoverRequest::METHOD, params);
+        let result: Hover = serde_json::from_value(result).unwrap();
+        assert_eq!(
+            result.contents,
+            HoverContents::Scalar(MarkedString::String("link L3: TestA -> TestB".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut server = Server::default();
+        let uri = Url::parse("untitled:simple-network.fnl").unwrap();
+        let code = load_code_from_file("simple-network");
+        open(&mut server, &uri, &code);
+
+        let params = CompletionParams {
+            text_document_position: position(&uri, &code, "src b", 4),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        };
+        let result = request(&server, Completion::METHOD, params);
+        let result: Vec<CompletionItem> = serde_json::from_value(result).unwrap();
+        let labels: Vec<&str> = result.iter().map(|item| item.label.as_str()).collect();
+        assert!(labels.contains(&"trans"));
+        assert!(labels.contains(&"TestB"));
+        assert!(labels.contains(&"L2"));
+        assert!(labels.contains(&"b"));
+    }
+}
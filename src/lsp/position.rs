@@ -0,0 +1,61 @@
+/*
+    LSP positions are zero based (line, column) pairs
+    where columns count UTF-16 code units.
+*/
+
+use lsp_types::{Position, Range};
+
+type Loc = (usize, usize);
+
+pub fn to_position(code: &str, offset: usize) -> Position {
+    let offset = offset.min(code.len());
+    let line_begin = code[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = code[..line_begin].matches('\n').count();
+    let character = code[line_begin..offset].encode_utf16().count();
+    Position::new(line as u32, character as u32)
+}
+
+pub fn to_range(code: &str, loc: Loc) -> Range {
+    Range::new(to_position(code, loc.0), to_position(code, loc.1))
+}
+
+/**
+ * Convert a position into a byte offset,
+ * positions past the end of a line are
+ * moved to the end of that line.
+ */
+pub fn to_offset(code: &str, position: Position) -> usize {
+    let line_begin = if position.line == 0 {
+        0
+    } else {
+        code.match_indices('\n')
+            .nth(position.line as usize - 1)
+            .map(|(i, _)| i + 1)
+            .unwrap_or(code.len())
+    };
+    let mut units = 0;
+    for (i, c) in code[line_begin..].char_indices() {
+        if c == '\n' || units >= position.character as usize {
+            return line_begin + i;
+        }
+        units += c.len_utf16();
+    }
+    code.len()
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let code = "network A {\n    link 'è' A B\n}";
+        let offset = code.find(" A B").unwrap();
+        let position = to_position(code, offset);
+        assert_eq!(position, Position::new(1, 12));
+        assert_eq!(to_offset(code, position), offset);
+        assert_eq!(to_offset(code, Position::new(0, 100)), 11);
+        assert_eq!(to_offset(code, Position::new(2, 1)), code.len());
+    }
+}
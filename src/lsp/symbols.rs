@@ -0,0 +1,602 @@
+use super::tokens::{tokenize, Token};
+use fsa_net_parser::syntax_tree::*;
+use fsa_net_parser::{parse, Code};
+
+type Loc = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Network,
+    Template,
+    Automata,
+    State,
+    Transition,
+    Link,
+    Event,
+    ObserveLabel,
+    RelevanceLabel,
+}
+
+impl std::fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Network => "network",
+            Self::Template => "template",
+            Self::Automata => "automata",
+            Self::State => "state",
+            Self::Transition => "transition",
+            Self::Link => "link",
+            Self::Event => "event",
+            Self::ObserveLabel => "observation label",
+            Self::RelevanceLabel => "relevance label",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/*
+    The scope is the path of names enclosing
+    a definition: networks and global templates are in
+    the empty scope, automata, links, events and labels
+    are in the scope of their network, states and transitions
+    in the scope of their automata. Global templates use an
+    empty network name.
+*/
+type Scope = Vec<String>;
+
+#[derive(Debug)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    pub name: String,
+    pub loc: Loc,
+    pub detail: String,
+    scope: Scope,
+}
+
+struct Reference {
+    loc: Loc,
+    name: String,
+    kind: SymbolKind,
+    scopes: Vec<Scope>,
+}
+
+/**
+ * All the names defined and used in a file,
+ * with the definition each use refers to
+ */
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    symbols: Vec<Symbol>,
+    uses: Vec<(Loc, usize)>,
+    scopes: Vec<(Scope, Loc)>,
+}
+
+impl SymbolIndex {
+    /**
+     * Build the index of the given code,
+     * None if the code is not syntactically correct
+     */
+    pub fn build(code: &str) -> Option<Self> {
+        let ast = parse(code).ok()?;
+        let mut builder = IndexBuilder {
+            code,
+            symbols: vec![],
+            references: vec![],
+            scopes: vec![],
+        };
+        builder.collect_code(&ast);
+        Some(builder.build())
+    }
+
+    /**
+     * Return the definition of the name at the given offset,
+     * either the name is defined or used there.
+     */
+    pub fn definition_at(&self, offset: usize) -> Option<&Symbol> {
+        let contains = |loc: &Loc| loc.0 <= offset && offset <= loc.1;
+        if let Some(sym) = self.symbols.iter().find(|s| contains(&s.loc)) {
+            Some(sym)
+        } else {
+            self.uses
+                .iter()
+                .find(|(loc, _)| contains(loc))
+                .map(|(_, def)| &self.symbols[*def])
+        }
+    }
+
+    /**
+     * Return the location of the name at the given offset
+     */
+    pub fn name_location(&self, offset: usize) -> Option<Loc> {
+        let contains = |loc: &Loc| loc.0 <= offset && offset <= loc.1;
+        self.symbols
+            .iter()
+            .map(|s| s.loc)
+            .chain(self.uses.iter().map(|(loc, _)| *loc))
+            .find(contains)
+    }
+
+    /**
+     * Return all the uses of the given symbol
+     */
+    pub fn references(&self, symbol: &Symbol) -> Vec<Loc> {
+        let index = self.symbols.iter().position(|s| s.loc == symbol.loc);
+        self.uses
+            .iter()
+            .filter(|(_, def)| Some(*def) == index)
+            .map(|(loc, _)| *loc)
+            .collect()
+    }
+
+    /**
+     * Return the symbols visible at the given offset:
+     * those defined in the innermost enclosing
+     * scope and in all its parents.
+     */
+    pub fn visible_symbols(&self, offset: usize) -> impl Iterator<Item = &Symbol> {
+        let scope = self
+            .scopes
+            .iter()
+            .filter(|(_, loc)| loc.0 <= offset && offset <= loc.1)
+            .max_by_key(|(scope, _)| scope.len())
+            .map(|(scope, _)| scope.clone())
+            .unwrap_or_default();
+        self.symbols
+            .iter()
+            .filter(move |s| scope.starts_with(&s.scope))
+    }
+}
+
+struct IndexBuilder<'a> {
+    code: &'a str,
+    symbols: Vec<Symbol>,
+    references: Vec<Reference>,
+    scopes: Vec<(Scope, Loc)>,
+}
+
+impl<'a> IndexBuilder<'a> {
+    fn build(self) -> SymbolIndex {
+        let symbols = self.symbols;
+        let uses = self
+            .references
+            .into_iter()
+            .filter_map(|r| {
+                let def = r.scopes.iter().find_map(|scope| {
+                    symbols
+                        .iter()
+                        .position(|s| s.kind == r.kind && s.name == r.name && s.scope == *scope)
+                })?;
+                Some((r.loc, def))
+            })
+            .collect();
+        SymbolIndex {
+            symbols,
+            uses,
+            scopes: self.scopes,
+        }
+    }
+
+    fn collect_code(&mut self, code: &Code) {
+        for blk in code {
+            match blk {
+                Block::Network(net) => self.collect_network(net),
+                Block::Request(req) => self.collect_request(req),
+                Block::Template(template) => {
+                    self.collect_template(template, vec![String::new()], vec![])
+                }
+                Block::Import(_) => {}
+            }
+        }
+    }
+
+    fn collect_network(&mut self, net: &Network) {
+        let tokens = tokenize(self.code, net.get_location());
+        let detail = format!("network {}", net.name);
+        self.define(SymbolKind::Network, &tokens[1], vec![], detail);
+
+        let scope = vec![net.name.to_owned()];
+        self.scopes.push((scope.clone(), net.get_location()));
+        for param in &net.params {
+            self.collect_network_param(param, &scope);
+        }
+    }
+
+    fn collect_network_param(&mut self, decl: &NetworkParameterDecl, scope: &Scope) {
+        let tokens = tokenize(self.code, decl.get_location());
+        match &decl.param {
+            NetworkParameter::Automata(automata) => {
+                let detail = format!("automata {}", automata.name);
+                self.define(SymbolKind::Automata, &tokens[1], scope.clone(), detail);
+                let inner = child_scope(scope, automata.name);
+                self.scopes.push((inner.clone(), automata.get_location()));
+                let links = vec![scope.clone()];
+                self.collect_automata_body(&automata.params, &inner, &links, scope);
+            }
+            NetworkParameter::Template(template) => {
+                self.collect_template(template, scope.clone(), vec![scope.clone()])
+            }
+            NetworkParameter::Instance(instance) => {
+                let detail = format!(
+                    "automata {} = {}({})",
+                    instance.name,
+                    instance.template,
+                    instance.args.join(", ")
+                );
+                self.define(SymbolKind::Automata, &tokens[1], scope.clone(), detail);
+                let template_scopes = vec![scope.clone(), vec![]];
+                self.refer(SymbolKind::Template, &tokens[3], template_scopes);
+                for arg in names(&tokens[4..]) {
+                    self.refer(SymbolKind::Link, arg, vec![scope.clone()]);
+                }
+            }
+            NetworkParameter::Link(link) => {
+                let mut detail = format!(
+                    "link {}: {} -> {}",
+                    link.name, link.source, link.destination
+                );
+                if let Some(capacity) = link.capacity {
+                    detail.push_str(&format!(", capacity {}", capacity));
+                }
+                if let Some(init) = link.init {
+                    detail.push_str(&format!(", init {}", init));
+                }
+                self.define(SymbolKind::Link, &tokens[1], scope.clone(), detail);
+                self.refer(SymbolKind::Automata, &tokens[2], vec![scope.clone()]);
+                self.refer(SymbolKind::Automata, &tokens[3], vec![scope.clone()]);
+                if link.init.is_some() {
+                    let event = &tokens[tokens.len() - 1];
+                    self.refer(SymbolKind::Event, event, vec![scope.clone()]);
+                }
+            }
+            NetworkParameter::Events(_) => self.define_list(SymbolKind::Event, &tokens[1..], scope),
+            NetworkParameter::ObserveLabels(_) => {
+                self.define_list(SymbolKind::ObserveLabel, &tokens[1..], scope)
+            }
+            NetworkParameter::RelevanceLabels(_) => {
+                self.define_list(SymbolKind::RelevanceLabel, &tokens[1..], scope)
+            }
+            NetworkParameter::Final(_) => {}
+        }
+    }
+
+    /*
+        Links used inside a template are either its
+        parameters or, for templates declared in a network,
+        the links of that network
+    */
+    fn collect_template(&mut self, template: &AutomataTemplate, scope: Scope, links: Vec<Scope>) {
+        let tokens = tokenize(self.code, template.get_location());
+        let detail = format!(
+            "automata template {}({})",
+            template.name,
+            template.args.join(", ")
+        );
+        let template_scope = if scope.len() == 1 && scope[0].is_empty() {
+            vec![]
+        } else {
+            scope.clone()
+        };
+        self.define(SymbolKind::Template, &tokens[2], template_scope, detail);
+
+        let inner = child_scope(&scope, template.name);
+        self.scopes.push((inner.clone(), template.get_location()));
+        let args = tokens[3..].iter().take_while(|t| !t.is_keyword(")"));
+        for arg in names(args) {
+            let detail = format!("link parameter {} of template {}", arg.text, template.name);
+            self.define(SymbolKind::Link, arg, inner.clone(), detail);
+        }
+
+        let links: Vec<Scope> = std::iter::once(inner.clone()).chain(links).collect();
+        self.collect_automata_body(&template.params, &inner, &links, &scope);
+    }
+
+    fn collect_automata_body(
+        &mut self,
+        params: &[AutomataParameterDecl],
+        scope: &Scope,
+        links: &[Scope],
+        events: &Scope,
+    ) {
+        let automata = scope.last().unwrap();
+        for decl in params {
+            let tokens = tokenize(self.code, decl.get_location());
+            match &decl.param {
+                AutomataParameter::StateDecl(StateDeclaration::State(name)) => {
+                    let detail = format!("state {} of {}", name, automata);
+                    self.define(SymbolKind::State, &tokens[1], scope.clone(), detail);
+                }
+                AutomataParameter::StateDecl(StateDeclaration::Begin(name)) => {
+                    let detail = format!("begin state {} of {}", name, automata);
+                    self.define(SymbolKind::State, &tokens[1], scope.clone(), detail);
+                }
+                AutomataParameter::Transition(trans) => {
+                    let detail = format!(
+                        "transition {}: {} -> {}",
+                        trans.name, trans.source, trans.destination
+                    );
+                    self.define(SymbolKind::Transition, &tokens[1], scope.clone(), detail);
+                    self.collect_transition_body(&tokens[2..], scope, links, events);
+                }
+                AutomataParameter::Final(_) => {
+                    for state in names(&tokens[1..]) {
+                        self.refer(SymbolKind::State, state, vec![scope.clone()]);
+                    }
+                }
+            }
+        }
+    }
+
+    /*
+        Simple transitions are made of two state names,
+        complex ones of key-value pairs.
+    */
+    fn collect_transition_body(
+        &mut self,
+        tokens: &[Token],
+        scope: &Scope,
+        links: &[Scope],
+        events: &Scope,
+    ) {
+        if !tokens[0].is_keyword("{") {
+            self.refer(SymbolKind::State, &tokens[0], vec![scope.clone()]);
+            self.refer(SymbolKind::State, &tokens[1], vec![scope.clone()]);
+            return;
+        }
+
+        let mut kind = None;
+        let mut in_event = false;
+        // after a key or a comma a key name is a value
+        let mut is_value = false;
+        for token in tokens {
+            let is_key = |key| !is_value && !in_event && token.is_keyword(key);
+            if is_key("src") || is_key("dst") {
+                kind = Some(SymbolKind::State);
+                is_value = true;
+            } else if is_key("input") || is_key("output") {
+                kind = Some(SymbolKind::Event);
+                is_value = true;
+            } else if is_key("rel") {
+                kind = Some(SymbolKind::RelevanceLabel);
+                is_value = true;
+            } else if is_key("obs") {
+                kind = Some(SymbolKind::ObserveLabel);
+                is_value = true;
+            } else if is_key("prob") || is_key("cost") {
+                // a number, not a name
+                kind = None;
+                is_value = true;
+            } else if token.is_keyword("(") {
+                in_event = true;
+            } else if token.is_keyword(")") {
+                in_event = false;
+            } else if token.is_keyword(",") {
+                is_value = true;
+            } else if token.is_punct() {
+                continue;
+            } else if in_event {
+                self.refer(SymbolKind::Link, token, links.to_vec());
+            } else {
+                is_value = false;
+                if let Some(kind) = kind {
+                    let scope = if kind == SymbolKind::State {
+                        scope
+                    } else {
+                        events
+                    };
+                    self.refer(kind, token, vec![scope.clone()]);
+                }
+            }
+        }
+    }
+
+    fn collect_request(&mut self, req: &Request) {
+        let tokens = tokenize(self.code, req.get_location());
+        self.refer(SymbolKind::Network, &tokens[1], vec![vec![]]);
+
+        let scope = vec![req.name.to_owned()];
+        self.scopes.push((scope.clone(), req.get_location()));
+        for cmd in &req.list {
+            let tokens = tokenize(self.code, cmd.get_location());
+            let labels = match &cmd.cmd {
+                Command::Linspace(_) | Command::Diagnoser(DiagnoserCommand::Build(_)) => tokens
+                    [1..]
+                    .iter()
+                    .take_while(|t| !t.is_keyword("save"))
+                    .collect(),
+                Command::Diagnoser(DiagnoserCommand::Load(_)) => tokens[3..].iter().collect(),
+                Command::Diagnosis(DiagnosisCommand::Fresh(_))
+                | Command::Check(CheckCommand {
+                    diagnosis: DiagnosisCommand::Fresh(_),
+                    ..
+                })
+                | Command::Witness(_)
+                | Command::Likely(_)
+                | Command::Cheapest(_) => obs_steps(&tokens),
+                _ => vec![],
+            };
+            for label in names(labels) {
+                self.refer(SymbolKind::ObserveLabel, label, vec![scope.clone()]);
+            }
+            if let Command::Check(_) = &cmd.cmd {
+                // the strings are the only lists in brackets
+                let begin = tokens
+                    .windows(2)
+                    .position(|t| t[0].is_keyword("check") && t[1].is_keyword("["))
+                    .map_or(tokens.len(), |i| i + 1);
+                for label in names(&tokens[begin..]) {
+                    self.refer(SymbolKind::RelevanceLabel, label, vec![scope.clone()]);
+                }
+            }
+        }
+    }
+
+    fn define_list(&mut self, kind: SymbolKind, tokens: &[Token], scope: &Scope) {
+        for token in names(tokens) {
+            let detail = format!("{} {}", kind, token.text);
+            self.define(kind, token, scope.clone(), detail);
+        }
+    }
+
+    fn define(&mut self, kind: SymbolKind, token: &Token, scope: Scope, detail: String) {
+        self.symbols.push(Symbol {
+            kind,
+            name: token.text.to_owned(),
+            loc: token.loc,
+            detail,
+            scope,
+        });
+    }
+
+    fn refer(&mut self, kind: SymbolKind, token: &Token, scopes: Vec<Scope>) {
+        self.references.push(Reference {
+            loc: token.loc,
+            name: token.text.to_owned(),
+            kind,
+            scopes,
+        });
+    }
+}
+
+fn child_scope(scope: &Scope, name: &str) -> Scope {
+    let mut output = scope.clone();
+    output.push(name.to_owned());
+    output
+}
+
+/*
+    Tokens of the observation of a fresh diagnosis, the
+    command keyword excluded. A clause keyword ends the
+    observation only after a complete step: after the
+    command, a comma, a bar or an open bracket it is a label.
+*/
+fn obs_steps<'a, 'b>(tokens: &'b [Token<'a>]) -> Vec<&'b Token<'a>> {
+    let clauses = ["strategy", "check", "witness", "likely", "cheapest"];
+    let mut output = vec![];
+    for (i, token) in tokens.iter().enumerate().skip(1) {
+        let prev = &tokens[i - 1];
+        let step_end = i > 1 && !["{", "(", ",", "|"].iter().any(|p| prev.is_keyword(p));
+        if step_end && clauses.iter().any(|c| token.is_keyword(c)) {
+            break;
+        }
+        output.push(token);
+    }
+    output
+}
+
+fn names<'a, 'b, I>(tokens: I) -> impl Iterator<Item = &'b Token<'a>>
+where
+    I: IntoIterator<Item = &'b Token<'a>>,
+    'a: 'b,
+{
+    tokens.into_iter().filter(|t| !t.is_punct())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use test_utils::load_code_from_file;
+
+    fn offset_of(code: &str, pattern: &str) -> usize {
+        code.find(pattern).unwrap()
+    }
+
+    #[test]
+    fn test_definition() {
+        let code = load_code_from_file("simple-network");
+        let index = SymbolIndex::build(&code).unwrap();
+
+        // `input e2(L2)` in TestA
+        let offset = offset_of(&code, "e2(L2)") + 3;
+        let link = index.definition_at(offset).unwrap();
+        assert_eq!(link.kind, SymbolKind::Link);
+        assert_eq!(link.detail, "link L2: TestB -> TestA");
+        assert_eq!(link.loc.0, offset_of(&code, "L2 TestB"));
+
+        let refs = index.references(link);
+        assert_eq!(refs.len(), 2);
+
+        let offset = offset_of(&code, "src b") + 4;
+        let state = index.definition_at(offset).unwrap();
+        assert_eq!(state.kind, SymbolKind::State);
+        assert_eq!(state.detail, "state b of TestA");
+    }
+
+    #[test]
+    fn test_request_labels() {
+        let code = load_code_from_file("simple-network");
+        let index = SymbolIndex::build(&code).unwrap();
+        let offset = offset_of(&code, "linspace o3") + 9;
+        let label = index.definition_at(offset).unwrap();
+        assert_eq!(label.kind, SymbolKind::ObserveLabel);
+        assert_eq!(label.loc.0, offset_of(&code, "o3\n"));
+        assert_eq!(index.references(label).len(), 4);
+    }
+
+    #[test]
+    fn test_contextual_labels() {
+        let code = "network N {\n    obs strategy, check, witness, likely, cheapest\n    rel check\n}\n\
+                    request N {\n    diagnosis strategy strategy size\n    diagnosis check, strategy\n    \
+                    diagnosis check check [check]\n    diagnosis witness witness\n    diagnosis likely likely 2\n    diagnosis cheapest cheapest\n}\n";
+        let index = SymbolIndex::build(code).unwrap();
+        let count = |kind, name| {
+            let label = index
+                .symbols
+                .iter()
+                .find(|s| s.kind == kind && s.name == name)
+                .unwrap();
+            index.references(label).len()
+        };
+        assert_eq!(count(SymbolKind::ObserveLabel, "strategy"), 2);
+        assert_eq!(count(SymbolKind::ObserveLabel, "check"), 2);
+        assert_eq!(count(SymbolKind::RelevanceLabel, "check"), 1);
+        assert_eq!(count(SymbolKind::ObserveLabel, "witness"), 1);
+        assert_eq!(count(SymbolKind::ObserveLabel, "likely"), 1);
+        assert_eq!(count(SymbolKind::ObserveLabel, "cheapest"), 1);
+    }
+
+    #[test]
+    fn test_contextual_transition_keys() {
+        let code = "network N {\n    events e\n    automata A {\n        begin prob\n        \
+                    trans t {\n            src prob\n            dst prob\n            prob 0.5\n        }\n    }\n}\n";
+        let index = SymbolIndex::build(code).unwrap();
+        let state = index
+            .symbols
+            .iter()
+            .find(|s| s.kind == SymbolKind::State && s.name == "prob")
+            .unwrap();
+        assert_eq!(index.references(state).len(), 2);
+    }
+
+    #[test]
+    fn test_templates() {
+        let code = load_code_from_file("template-network");
+        let index = SymbolIndex::build(&code).unwrap();
+        assert!(index
+            .symbols
+            .iter()
+            .any(|s| s.kind == SymbolKind::Template && s.scope.is_empty()));
+        for (_, def) in &index.uses {
+            let sym = &index.symbols[*def];
+            if sym.kind == SymbolKind::Template {
+                assert!(sym.scope.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_visible_symbols() {
+        let code = load_code_from_file("simple-network");
+        let index = SymbolIndex::build(&code).unwrap();
+        let offset = offset_of(&code, "trans tb {");
+        let visible: Vec<&str> = index
+            .visible_symbols(offset)
+            .filter(|s| s.kind == SymbolKind::State)
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(visible, vec!["b", "a"]);
+        assert!(index
+            .visible_symbols(0)
+            .all(|s| s.kind == SymbolKind::Network));
+    }
+}
@@ -0,0 +1,117 @@
+/*
+    The syntax tree records the location of
+    each item but not of the names inside it:
+    the source code of an item is split again
+    into tokens to find where each name is.
+*/
+
+type Loc = (usize, usize);
+
+#[derive(Debug, PartialEq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub loc: Loc,
+    pub quoted: bool,
+}
+
+impl<'a> Token<'a> {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        !self.quoted && self.text == keyword
+    }
+
+    pub fn is_punct(&self) -> bool {
+        !self.quoted && self.text.len() == 1 && is_punct(self.text.as_bytes()[0])
+    }
+}
+
+/**
+ * Split the code in loc into tokens, skipping comments
+ */
+pub fn tokenize(code: &str, loc: Loc) -> Vec<Token<'_>> {
+    let (begin, end) = loc;
+    let bytes = code.as_bytes();
+    let mut output = vec![];
+    let mut i = begin;
+    while i < end {
+        let curr = bytes[i];
+        if curr.is_ascii_whitespace() {
+            i += 1;
+        } else if code[i..end].starts_with("//") {
+            i = code[i..end].find('\n').map(|e| i + e).unwrap_or(end);
+        } else if code[i..end].starts_with("/*") {
+            i = code[i + 2..end]
+                .find("*/")
+                .map(|e| i + e + 4)
+                .unwrap_or(end);
+        } else if curr == b'\'' || curr == b'"' {
+            let close = code[i + 1..end]
+                .find(curr as char)
+                .map(|e| i + e + 1)
+                .unwrap_or(end - 1);
+            output.push(Token {
+                text: &code[i + 1..close],
+                loc: (i, close + 1),
+                quoted: true,
+            });
+            i = close + 1;
+        } else if is_punct(curr) {
+            output.push(new_token(code, i, i + 1));
+            i += 1;
+        } else {
+            let len = code[i..end]
+                .find(|c: char| c.is_whitespace() || (c.is_ascii() && is_punct(c as u8)))
+                .unwrap_or(end - i);
+            output.push(new_token(code, i, i + len));
+            i += len;
+        }
+    }
+    output
+}
+
+fn new_token(code: &str, begin: usize, end: usize) -> Token<'_> {
+    Token {
+        text: &code[begin..end],
+        loc: (begin, end),
+        quoted: false,
+    }
+}
+
+fn is_punct(c: u8) -> bool {
+    matches!(
+        c,
+        b'{' | b'}'
+            | b'('
+            | b')'
+            | b'['
+            | b']'
+            | b','
+            | b'='
+            | b'|'
+            | b'?'
+            | b'*'
+            | b'+'
+            | b'\''
+            | b'"'
+    )
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let code = "link L1 /* comment */ 'Test Name' B\n";
+        let tokens = tokenize(code, (0, code.len()));
+        let text: Vec<&str> = tokens.iter().map(|t| t.text).collect();
+        assert_eq!(text, vec!["link", "L1", "Test Name", "B"]);
+        assert_eq!(tokens[2].loc, (22, 33));
+        assert!(tokens[2].quoted);
+
+        let code = "output e1(L1), e2(L2)";
+        let tokens = tokenize(code, (7, code.len()));
+        let text: Vec<&str> = tokens.iter().map(|t| t.text).collect();
+        assert_eq!(text, vec!["e1", "(", "L1", ")", ",", "e2", "(", "L2", ")"]);
+    }
+}
@@ -1,25 +1,47 @@
-use fsa_net_parser;
+use fsa_net::{
+    compiler, diagnostics, engine, export_results, formatter, input_output, source_loader, timer,
+};
+use std::io::{self, BufRead, Write};
 use std::path;
+use std::process;
 use structopt::StructOpt;
 
-
-mod command;
-mod compiler;
-mod engine;
-mod export_results;
-mod graph;
-mod input_output;
-mod network;
-mod state_table;
-mod timer;
-mod utils;
-
 #[derive(StructOpt)]
 struct Arguments {
     input: Option<path::PathBuf>,
     output: Option<path::PathBuf>,
     #[structopt(flatten)]
     conf: EngineConfig,
+    #[structopt(subcommand)]
+    cmd: Option<SubCommand>,
+}
+
+#[derive(StructOpt)]
+enum SubCommand {
+    /// Rewrite the given files in canonical form,
+    /// read from stdin and write to stdout when no file is given
+    Fmt(FormatConfig),
+    /// Diagnose the observations read from stdin one at a time,
+    /// printing the updated diagnosis after each one
+    Monitor(MonitorConfig),
+}
+
+#[derive(StructOpt)]
+struct MonitorConfig {
+    input: path::PathBuf,
+    /// Name of the monitored network, the first one when not given
+    #[structopt(short = "-n", long = "--network")]
+    network: Option<String>,
+    #[structopt(flatten)]
+    conf: EngineConfig,
+}
+
+#[derive(StructOpt)]
+struct FormatConfig {
+    /// Do not write any file, exit with an error if some file is not formatted
+    #[structopt(long = "--check")]
+    check: bool,
+    files: Vec<path::PathBuf>,
 }
 
 #[derive(StructOpt)]
@@ -30,11 +52,19 @@ struct EngineConfig {
     prune: engine::GraphMode,
     #[structopt(short="-t", long="--time-limit",parse(try_from_str = timer::parse_time_spec))]
     time_limit: Option<u64>,
+    /// Number of threads used to explore the behavioral space
+    #[structopt(short = "-j", long = "--jobs", default_value = "1")]
+    jobs: usize,
+    /// Elimination order of the diagnosis: degree, product, size, loops or topological
+    #[structopt(short = "-e", long = "--elimination", default_value = "degree")]
+    elimination: engine::EliminationOrder,
 }
 
 fn run_request(comp_res: compiler::CompileResult, conf: EngineConfig) -> String {
     let timer_factory = timer::TimerFactory::from_value(conf.time_limit);
-    let engine_config = engine::EngineConfig::new(conf.prune, timer_factory);
+    let engine_config = engine::EngineConfig::new(conf.prune, timer_factory)
+        .set_threads(conf.jobs)
+        .set_elimination(conf.elimination);
     let mut results = Vec::with_capacity(comp_res.compile_network.len());
     for (i, cmd) in comp_res.compile_network.iter().enumerate() {
         let net_table = comp_res.index_table.get_network_table(i);
@@ -59,11 +89,182 @@ fn run_request(comp_res: compiler::CompileResult, conf: EngineConfig) -> String
     .unwrap()
 }
 
+fn print_reports(reports: &[diagnostics::Report], sources: &diagnostics::SourceMap) -> ! {
+    for report in reports {
+        eprintln!("{}", report.render(sources));
+    }
+    process::exit(1);
+}
+
+fn format_file(file: Option<path::PathBuf>, check: bool) -> bool {
+    let code = input_output::get_fsa_code(&file).unwrap();
+    let formatted = match formatter::format_code(&code) {
+        Ok(formatted) => formatted,
+        Err(err) => {
+            let mut sources = diagnostics::SourceMap::default();
+            sources.add_file(input_output::get_input_name(&file), code.as_str());
+            print_reports(&[diagnostics::syntax_error_report(&err)], &sources)
+        }
+    };
+
+    let ok = !check || formatted == code;
+    if !ok {
+        eprintln!("{} is not formatted", input_output::get_input_name(&file));
+    }
+    if let Some(output) = format_output(&code, formatted, file.is_some(), check) {
+        input_output::write_results(output, file).unwrap();
+    }
+    ok
+}
+
+/*
+    Text to write after formatting, None when there is nothing
+    to write: in check mode and for files already formatted.
+    Stdin is always copied to stdout, otherwise a pipeline
+    would lose the code that needs no change.
+*/
+fn format_output(code: &str, formatted: String, is_file: bool, check: bool) -> Option<String> {
+    if check || (is_file && formatted == code) {
+        None
+    } else {
+        Some(formatted)
+    }
+}
+
+fn run_format(conf: FormatConfig) {
+    let check = conf.check;
+    let formatted = if conf.files.is_empty() {
+        format_file(None, check)
+    } else {
+        // check all the files, do not stop at the first error
+        let results: Vec<bool> = conf.files
+            .into_iter()
+            .map(|file| format_file(Some(file), check))
+            .collect();
+        results.iter().all(|ok| *ok)
+    };
+    if !formatted {
+        process::exit(1);
+    }
+}
+
+/*
+    Each line is either an observation name, a json string
+    or a json array of names. Empty lines are skipped.
+*/
+fn parse_observations(line: &str) -> Result<Vec<String>, serde_json::Error> {
+    let line = line.trim();
+    if line.is_empty() {
+        Ok(vec![])
+    } else if line.starts_with('[') {
+        serde_json::from_str(line)
+    } else if line.starts_with('"') {
+        serde_json::from_str(line).map(|obs| vec![obs])
+    } else {
+        Ok(vec![line.to_owned()])
+    }
+}
+
+fn run_monitor(comp_res: compiler::CompileResult, conf: MonitorConfig) {
+    let index = match &conf.network {
+        Some(name) => (0..comp_res.compile_network.len())
+            .find(|i| comp_res.index_table.get_network_table(*i).get_name() == name),
+        None if comp_res.compile_network.is_empty() => None,
+        None => Some(0),
+    };
+    let index = index.unwrap_or_else(|| {
+        eprintln!("no network to monitor");
+        process::exit(1);
+    });
+    let net_table = comp_res.index_table.get_network_table(index);
+    let names = net_table.get_network_names();
+
+    let timer_factory = timer::TimerFactory::from_value(conf.conf.time_limit);
+    let engine_config = engine::EngineConfig::new(conf.conf.prune, timer_factory)
+        .set_threads(conf.conf.jobs)
+        .set_elimination(conf.conf.elimination);
+    let net = &comp_res.compile_network[index].net;
+    let mut monitor = engine::Monitor::new(net, &engine_config).unwrap_or_else(|| {
+        eprintln!("timeout while building the diagnoser of {}", net_table.get_name());
+        process::exit(1);
+    });
+
+    let stdout = io::stdout();
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap();
+        let observations = parse_observations(&line).unwrap_or_else(|err| {
+            eprintln!("invalid observation `{}`: {}", line.trim(), err);
+            vec![]
+        });
+        for obs in observations {
+            let consistent = match names.get_obs_index(&obs) {
+                Some(index) => monitor.observe(index),
+                None => {
+                    eprintln!("unknown observation `{}`", obs);
+                    false
+                }
+            };
+            let step = export_results::export_monitor_step(&obs, consistent, &monitor, net_table);
+            let output = match conf.conf.format {
+                export_results::JsonFormat::Pretty => serde_json::to_string_pretty(&step),
+                export_results::JsonFormat::Compact => serde_json::to_string(&step),
+            }
+            .unwrap();
+            let mut out = stdout.lock();
+            writeln!(out, "{}", output).unwrap();
+            out.flush().unwrap();
+        }
+    }
+}
+
 fn main() {
     let args = Arguments::from_args();
-    let src_code = input_output::get_fsa_code(&args.input).unwrap();
-    let code = fsa_net_parser::parse(&src_code).unwrap();
-    let compile_result = compiler::compile(&code).unwrap();
+    let (input, monitor) = match args.cmd {
+        Some(SubCommand::Fmt(conf)) => {
+            run_format(conf);
+            return;
+        }
+        Some(SubCommand::Monitor(conf)) => (Some(conf.input.clone()), Some(conf)),
+        None => (args.input, None),
+    };
+    let (sources, errors) = source_loader::load_sources(&input).unwrap();
+    if !errors.is_empty() {
+        print_reports(&errors, &sources);
+    }
+    let code = match source_loader::parse_sources(&sources) {
+        Ok(code) => code,
+        Err(errors) => print_reports(&errors, &sources),
+    };
+    let compile_result = match compiler::compile(&code) {
+        Ok(res) => res,
+        Err(errors) => print_reports(&diagnostics::compile_error_reports(&errors), &sources),
+    };
+    if let Some(conf) = monitor {
+        run_monitor(compile_result, conf);
+        return;
+    }
     let result = run_request(compile_result, args.conf);
     input_output::write_results(result, args.output).unwrap();
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use test_utils::load_code_from_file;
+
+    #[test]
+    fn test_format_output() {
+        let code = formatter::format_code(&load_code_from_file("simple-network")).unwrap();
+        let formatted = formatter::format_code(&code).unwrap();
+        assert_eq!(formatted, code);
+
+        // formatted code read from stdin is written back unchanged
+        assert_eq!(
+            format_output(&code, formatted.clone(), false, false),
+            Some(code.clone())
+        );
+        assert_eq!(format_output(&code, formatted.clone(), true, false), None);
+        assert_eq!(format_output(&code, formatted, false, true), None);
+    }
+}
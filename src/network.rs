@@ -1,99 +1,312 @@
-use crate::utils::zeros;
-
+/*
+    States are packed into an array of words: the current
+    state of each automaton, the length of each link and
+    every slot of the link queue take a fixed number of bits,
+    computed by StateLayout from the compiled network.
+    Unused queue slots are always zero, so two states
+    are equal only when their content is equal.
+*/
 #[derive(PartialEq, Eq, Clone, Debug, Hash)]
 pub struct State {
-    states: Vec<usize>,
-    links: Vec<Option<usize>>,
+    words: Box<[u64]>,
     index: usize,
 }
 
 impl State {
-    pub fn initial(states: Vec<usize>, link_count: usize) -> Self {
+    pub fn set_index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    fn get(&self, field: Field) -> usize {
+        ((self.words[field.word] >> field.shift) & field.mask()) as usize
+    }
+
+    fn set(&mut self, field: Field, value: usize) {
+        let mask = field.mask() << field.shift;
+        let word = &mut self.words[field.word];
+        *word = (*word & !mask) | (((value as u64) << field.shift) & mask);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Field {
+    word: usize,
+    shift: u32,
+    width: u32,
+}
+
+impl Field {
+    fn mask(&self) -> u64 {
+        if self.width == u64::BITS {
+            u64::MAX
+        } else {
+            (1 << self.width) - 1
+        }
+    }
+}
+
+/*
+    Each link is a bounded FIFO queue:
+    output events are enqueued at the back,
+    input events are consumed from the front.
+*/
+#[derive(Debug, PartialEq, Clone)]
+struct LinkLayout {
+    len: Field,
+    slots: Vec<Field>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StateLayout {
+    automata: Vec<Field>,
+    links: Vec<LinkLayout>,
+    words: usize,
+}
+
+impl StateLayout {
+    fn new(automata: &[Automata], links: &[Link]) -> Self {
+        let state_counts: Vec<usize> = automata.iter().map(|a| a.adjacent_list.len()).collect();
+        let capacities: Vec<usize> = links.iter().map(|l| l.capacity).collect();
+        let event_count = automata
+            .iter()
+            .flat_map(|a| a.adjacent_list.iter().flatten())
+            .filter_map(|adj| adj.trans.max_event())
+            .chain(links.iter().filter_map(|l| l.init))
+            .max()
+            .map_or(0, |ev| ev + 1);
+        Self::from_sizes(&state_counts, &capacities, event_count)
+    }
+
+    fn from_sizes(state_counts: &[usize], capacities: &[usize], event_count: usize) -> Self {
+        let mut builder = LayoutBuilder::default();
+        let automata = state_counts
+            .iter()
+            .map(|count| builder.next_field(*count))
+            .collect();
+        let links = capacities
+            .iter()
+            .map(|capacity| LinkLayout {
+                len: builder.next_field(capacity + 1),
+                slots: (0..*capacity)
+                    .map(|_| builder.next_field(event_count))
+                    .collect(),
+            })
+            .collect();
         Self {
-            states,
-            links: zeros(link_count),
-            index: 0,
+            automata,
+            links,
+            words: builder.word + 1,
         }
     }
 
-    pub fn is_final(&self) -> bool {
-        for l in &self.links {
-            if l.is_some() {
-                return false;
-            }
+    /**
+     * Convert a packed state into plain lists,
+     * used only to export the results
+     */
+    pub fn decode(&self, state: &State) -> DecodedState {
+        let states = self.automata.iter().map(|f| state.get(*f)).collect();
+        let links = self
+            .links
+            .iter()
+            .map(|link| {
+                let len = state.get(link.len);
+                link.slots[..len].iter().map(|f| state.get(*f)).collect()
+            })
+            .collect();
+        DecodedState {
+            states,
+            links,
+            index: state.index,
         }
-        true
     }
 
-    pub fn set_index(mut self, index: usize) -> Self {
-        self.index = index;
-        self
+    fn initial(&self, states: &[usize]) -> State {
+        let mut output = State {
+            words: vec![0; self.words].into_boxed_slice(),
+            index: 0,
+        };
+        for (field, value) in self.automata.iter().zip(states) {
+            output.set(*field, *value);
+        }
+        output
     }
 
-    pub fn get_index(&self) -> usize {
-        self.index
+    fn links_empty(&self, state: &State) -> bool {
+        self.links.iter().all(|l| state.get(l.len) == 0)
     }
 
-    pub fn get_states<'a>(&'a self) -> impl Iterator<Item = (usize, usize)> + 'a {
-        self.states.iter().enumerate().map(|(i, s)| (i, *s))
+    fn get_state(&self, state: &State, automata: usize) -> usize {
+        state.get(self.automata[automata])
     }
 
-    pub fn get_links<'a>(&'a self) -> impl Iterator<Item = (usize, Option<usize>)> + 'a {
-        self.links.iter().enumerate().map(|(i, l)| (i, *l))
+    fn set_state(&self, state: &State, automata: usize, next: usize) -> State {
+        let mut out = state.clone();
+        out.set(self.automata[automata], next);
+        out
     }
 
-    fn get_state(&self, automata: usize) -> usize {
-        self.states[automata]
+    fn fill_link(&self, mut state: State, link: usize, val: usize) -> State {
+        let link = &self.links[link];
+        let len = state.get(link.len);
+        state.set(link.slots[len], val);
+        state.set(link.len, len + 1);
+        state
     }
 
-    fn set_state(&self, automata: usize, next: usize) -> Self {
-        let mut out = self.clone();
-        out.states[automata] = next;
-        out
+    fn drain_link(&self, mut state: State, link: usize) -> State {
+        let link = &self.links[link];
+        let len = state.get(link.len);
+        for i in 1..len {
+            let next = state.get(link.slots[i]);
+            state.set(link.slots[i - 1], next);
+        }
+        state.set(link.slots[len - 1], 0);
+        state.set(link.len, len - 1);
+        state
     }
 
-    fn fill_link(mut self, link: usize, val: usize) -> Self {
-        self.links[link] = Some(val);
-        self
+    fn link_len(&self, state: &State, link: usize) -> usize {
+        state.get(self.links[link].len)
     }
 
-    fn drain_link(mut self, link: usize) -> Self {
-        self.links[link] = None;
-        self
+    fn link_capacity(&self, link: usize) -> usize {
+        self.links[link].slots.len()
     }
 
-    fn is_empty_link(&self, link: usize) -> bool {
-        self.links[link].is_none()
+    fn has_event_link(&self, state: &State, link: usize, ev: usize) -> bool {
+        let link = &self.links[link];
+        state.get(link.len) > 0 && state.get(link.slots[0]) == ev
     }
+}
 
-    fn has_event_link(&self, link: usize, ev: usize) -> bool {
-        let link = self.links[link];
-        if let Some(event) = link {
-            event == ev
-        } else {
-            false
+/*
+    Fields never cross a word boundary,
+    a field holding a single value takes no bits.
+*/
+#[derive(Default)]
+struct LayoutBuilder {
+    word: usize,
+    bit: u32,
+}
+
+impl LayoutBuilder {
+    fn next_field(&mut self, values: usize) -> Field {
+        let width = usize::BITS - values.saturating_sub(1).leading_zeros();
+        if self.bit + width > u64::BITS {
+            self.word += 1;
+            self.bit = 0;
         }
+        let output = Field {
+            word: self.word,
+            shift: self.bit,
+            width,
+        };
+        self.bit += width;
+        output
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DecodedState {
+    states: Vec<usize>,
+    links: Vec<Vec<usize>>,
+    index: usize,
+}
+
+impl DecodedState {
+    pub fn get_index(&self) -> usize {
+        self.index
     }
+
+    pub fn get_states<'a>(&'a self) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.states.iter().enumerate().map(|(i, s)| (i, *s))
+    }
+
+    /**
+     * Return the content of each link,
+     * from the oldest to the newest event
+     */
+    pub fn get_links<'a>(&'a self) -> impl Iterator<Item = (usize, &'a [usize])> + 'a {
+        self.links.iter().map(|l| l.as_slice()).enumerate()
+    }
+}
+
+/*
+    When a state of the network is final: the automata
+    without final states accept any of their states
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FinalCriterion {
+    #[default]
+    EmptyLinks,
+    FinalStates,
+    Both,
+    Any,
 }
+
 #[derive(Debug, PartialEq)]
 pub struct Network {
     automata: Vec<Automata>,
     links: Vec<Link>,
+    layout: StateLayout,
+    criterion: FinalCriterion,
 }
 
 impl Network {
     pub fn new(automata: Vec<Automata>, links: Vec<Link>) -> Self {
-        Self { automata, links }
+        let layout = StateLayout::new(&automata, &links);
+        Self {
+            automata,
+            links,
+            layout,
+            criterion: FinalCriterion::default(),
+        }
+    }
+
+    pub fn set_criterion(mut self, criterion: FinalCriterion) -> Self {
+        self.criterion = criterion;
+        self
     }
 
     pub fn get_initial_state(&self) -> State {
-        State::initial(self.get_automata_initial_state(), self.links.len())
+        let state = self.layout.initial(&self.get_automata_initial_state());
+        self.links
+            .iter()
+            .enumerate()
+            .filter_map(|(index, link)| link.init.map(|ev| (index, ev)))
+            .fold(state, |state, (index, ev)| {
+                self.layout.fill_link(state, index, ev)
+            })
+    }
+
+    pub fn get_layout(&self) -> &StateLayout {
+        &self.layout
+    }
+
+    pub fn is_final(&self, state: &State) -> bool {
+        let links_empty = || self.layout.links_empty(state);
+        let states_final = || {
+            self.automata
+                .iter()
+                .all(|a| a.is_final(state, &self.layout))
+        };
+        match self.criterion {
+            FinalCriterion::EmptyLinks => links_empty(),
+            FinalCriterion::FinalStates => states_final(),
+            FinalCriterion::Both => links_empty() && states_final(),
+            FinalCriterion::Any => links_empty() || states_final(),
+        }
     }
 
     pub fn step_one(&self, state: &State) -> Vec<(TransEvent, State)> {
         let mut output = Vec::new();
         for auto in &self.automata {
-            let mut next = auto.step_one(state);
+            let mut next = auto.step_one(state, &self.layout);
             output.append(&mut next)
         }
         output
@@ -112,6 +325,8 @@ pub struct Automata {
     adjacent_list: Vec<Vec<Adjacent>>,
     index: usize,
     begin: usize,
+    // empty when every state is final
+    finals: Vec<usize>,
 }
 
 impl Automata {
@@ -120,17 +335,32 @@ impl Automata {
             adjacent_list,
             index,
             begin,
+            finals: vec![],
         }
     }
 
-    fn step_one(&self, net_state: &State) -> Vec<(TransEvent, State)> {
-        let curr_state = net_state.get_state(self.index);
+    pub fn add_final(mut self, state: usize) -> Self {
+        if !self.finals.contains(&state) {
+            self.finals.push(state);
+        }
+        self
+    }
+
+    fn is_final(&self, net_state: &State, layout: &StateLayout) -> bool {
+        self.finals.is_empty()
+            || self
+                .finals
+                .contains(&layout.get_state(net_state, self.index))
+    }
+
+    fn step_one(&self, net_state: &State, layout: &StateLayout) -> Vec<(TransEvent, State)> {
+        let curr_state = layout.get_state(net_state, self.index);
         let next_states = &self.adjacent_list[curr_state];
         let mut output = Vec::new();
         for trans in next_states.iter() {
-            if trans.trans.is_enabled(net_state) {
-                let next = net_state.set_state(self.index, trans.state);
-                let next = trans.trans.apply_transition(next);
+            if trans.trans.is_enabled(net_state, layout) {
+                let next = layout.set_state(net_state, self.index, trans.state);
+                let next = trans.trans.apply_transition(next, layout);
                 output.push(next);
             }
         }
@@ -162,6 +392,8 @@ pub struct Transition {
     output: Option<Vec<Event>>,
     rel: Option<usize>,
     obs: Option<usize>,
+    prob: Option<f64>,
+    cost: Option<usize>,
 }
 
 impl Transition {
@@ -197,16 +429,32 @@ impl Transition {
         self
     }
 
-    fn is_enabled(&self, state: &State) -> bool {
+    pub fn set_probability(mut self, prob: f64) -> Self {
+        self.prob = Some(prob);
+        self
+    }
+
+    pub fn set_cost(mut self, cost: usize) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+
+    fn is_enabled(&self, state: &State, layout: &StateLayout) -> bool {
         if let Some(input) = &self.input {
-            if !state.has_event_link(input.link, input.event) {
+            if !layout.has_event_link(state, input.link, input.event) {
                 return false;
             }
         }
 
         if let Some(output) = &self.output {
             for out in output {
-                if !state.is_empty_link(out.link) {
+                // the input event is consumed before
+                // the output events are enqueued
+                let consumed = match &self.input {
+                    Some(input) if input.link == out.link => 1,
+                    _ => 0,
+                };
+                if layout.link_len(state, out.link) - consumed >= layout.link_capacity(out.link) {
                     return false;
                 }
             }
@@ -215,27 +463,49 @@ impl Transition {
         true
     }
 
-    fn apply_transition(&self, mut state: State) -> (TransEvent, State) {
+    fn apply_transition(&self, mut state: State, layout: &StateLayout) -> (TransEvent, State) {
         if let Some(input) = &self.input {
-            state = state.drain_link(input.link);
+            state = layout.drain_link(state, input.link);
         }
 
         if let Some(output) = &self.output {
             for out in output {
-                state = state.fill_link(out.link, out.event);
+                state = layout.fill_link(state, out.link, out.event);
             }
         }
 
         (self.into(), state)
     }
+
+    fn max_event(&self) -> Option<usize> {
+        let output = self.output.iter().flatten();
+        self.input.iter().chain(output).map(|ev| ev.event).max()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TransEvent {
     pub auto: usize,
     pub trans: usize,
     pub obs: Option<usize>,
     pub rel: Option<usize>,
+    pub prob: Option<f64>,
+    pub cost: Option<usize>,
+}
+
+impl TransEvent {
+    /*
+        Weight of the transition among the ones leaving
+        the same state: DEFAULT_TRANS_PROB when not declared
+    */
+    pub fn weight(&self) -> f64 {
+        self.prob.unwrap_or(DEFAULT_TRANS_PROB)
+    }
+
+    // DEFAULT_TRANS_COST when not declared
+    pub fn get_cost(&self) -> usize {
+        self.cost.unwrap_or(DEFAULT_TRANS_COST)
+    }
 }
 
 impl From<&Transition> for TransEvent {
@@ -245,6 +515,8 @@ impl From<&Transition> for TransEvent {
             trans: trans.index,
             obs: trans.obs,
             rel: trans.rel,
+            prob: trans.prob,
+            cost: trans.cost,
         }
     }
 }
@@ -261,15 +533,32 @@ impl Event {
     }
 }
 
+pub const DEFAULT_LINK_CAPACITY: usize = 1;
+pub const DEFAULT_TRANS_PROB: f64 = 1.0;
+pub const DEFAULT_TRANS_COST: usize = 0;
+
 #[derive(Debug, PartialEq)]
 pub struct Link {
     src: usize,
     dst: usize,
+    capacity: usize,
+    // event on the link in the initial state
+    init: Option<usize>,
 }
 
 impl Link {
-    pub fn new(src: usize, dst: usize) -> Self {
-        Self { src, dst }
+    pub fn new(src: usize, dst: usize, capacity: usize) -> Self {
+        Self {
+            src,
+            dst,
+            capacity,
+            init: None,
+        }
+    }
+
+    pub fn set_init(mut self, event: usize) -> Self {
+        self.init = Some(event);
+        self
     }
 }
 
@@ -286,6 +575,7 @@ mod test {
 
     use super::*;
     use crate::compiler::compile;
+    use crate::utils::zeros;
     use fsa_net_parser::parse;
     use test_utils::load_code_from_file;
 
@@ -330,20 +620,57 @@ mod test {
             ],
         );
 
-        let expect_net = Network::new(vec![auto_a, auto_b], vec![Link::new(1, 0), Link::new(0, 1)]);
+        let expect_net = Network::new(
+            vec![auto_a, auto_b],
+            vec![Link::new(1, 0, 1), Link::new(0, 1, 1)],
+        );
 
         assert_eq!(&expect_net, net);
     }
 
+    fn decode_links(layout: &StateLayout, state: &State) -> Vec<Vec<usize>> {
+        layout.decode(state).links
+    }
+
     #[test]
     fn test_initial_state() {
-        let initial = State::initial(zeros(4), 1);
-        assert!(initial.is_final());
+        let layout = StateLayout::from_sizes(&[2, 2, 2, 2], &[1], 4);
+        let initial = layout.initial(&zeros(4));
+        assert!(layout.links_empty(&initial));
+    }
+
+    #[test]
+    fn test_packed_state() {
+        // 33 bits for each automaton: the second
+        // and the third state start on a new word
+        let layout = StateLayout::from_sizes(&[1 << 33, 1 << 33, 5, 1], &[3, 0], 300);
+        assert_eq!(layout.words, 3);
+        assert_eq!(layout.automata[3].width, 0);
+
+        let state = layout.initial(&[(1 << 33) - 1, 7, 4, 0]);
+        let state = layout.fill_link(state, 0, 299);
+        let state = layout.fill_link(state, 0, 0);
+        let state = layout.fill_link(state, 0, 17);
+        let decoded = layout.decode(&state);
+        assert_eq!(decoded.states, vec![(1 << 33) - 1, 7, 4, 0]);
+        assert_eq!(decoded.links, vec![vec![299, 0, 17], vec![]]);
+
+        let state = layout.drain_link(state, 0);
+        assert_eq!(decode_links(&layout, &state), vec![vec![0, 17], vec![]]);
+        let state = layout.set_state(&state, 1, 3);
+        assert_eq!(layout.get_state(&state, 1), 3);
+        assert_eq!(layout.get_state(&state, 0), (1 << 33) - 1);
+
+        let other = layout.initial(&[(1 << 33) - 1, 3, 4, 0]);
+        let other = layout.fill_link(other, 0, 0);
+        let other = layout.fill_link(other, 0, 17);
+        assert_eq!(state, other);
     }
 
     #[test]
     fn test_enabled_transition() {
-        let state = State::initial(zeros(3), 2).fill_link(1, 3);
+        let layout = StateLayout::from_sizes(&[1, 1, 1], &[1, 1], 4);
+        let state = layout.fill_link(layout.initial(&zeros(3)), 1, 3);
         let trans = Transition {
             owner: 0,
             index: 0,
@@ -351,8 +678,10 @@ mod test {
             output: None,
             rel: None,
             obs: None,
+            prob: None,
+            cost: None,
         };
-        assert!(trans.is_enabled(&state));
+        assert!(trans.is_enabled(&state, &layout));
 
         let trans = Transition {
             owner: 0,
@@ -361,8 +690,10 @@ mod test {
             output: Some(vec![Event { event: 3, link: 1 }]),
             rel: None,
             obs: None,
+            prob: None,
+            cost: None,
         };
-        assert!(!trans.is_enabled(&state));
+        assert!(!trans.is_enabled(&state, &layout));
 
         let trans = Transition {
             owner: 0,
@@ -371,13 +702,16 @@ mod test {
             output: Some(vec![Event { event: 2, link: 0 }]),
             rel: None,
             obs: None,
+            prob: None,
+            cost: None,
         };
-        assert!(trans.is_enabled(&state));
+        assert!(trans.is_enabled(&state, &layout));
     }
 
     #[test]
     fn test_apply_transition() {
-        let state = State::initial(zeros(3), 2).fill_link(1, 3);
+        let layout = StateLayout::from_sizes(&[1, 1, 1], &[1, 1], 4);
+        let state = layout.fill_link(layout.initial(&zeros(3)), 1, 3);
 
         let in_link = 1;
         let out_link = 0;
@@ -397,13 +731,16 @@ mod test {
             }]),
             rel: Some(31),
             obs: Some(12),
+            prob: None,
+            cost: None,
         };
-        assert!(trans.is_enabled(&state));
+        assert!(trans.is_enabled(&state, &layout));
 
-        let (event, state) = trans.apply_transition(state);
+        let (event, state) = trans.apply_transition(state, &layout);
 
-        assert!(state.links[in_link].is_none());
-        assert_eq!(state.links[out_link].unwrap(), out_ev);
+        let links = decode_links(&layout, &state);
+        assert!(links[in_link].is_empty());
+        assert_eq!(links[out_link], vec![out_ev]);
 
         assert_eq!(event.obs.unwrap(), 12);
         assert_eq!(event.rel.unwrap(), 31);
@@ -428,30 +765,116 @@ mod test {
             }]),
             rel: Some(31),
             obs: Some(12),
+            prob: None,
+            cost: None,
         };
 
         let automata = Automata::new(
             0,
             0,
-            vec![vec![Adjacent {
-                state: 1,
-                trans: trans,
-            }]],
+            vec![
+                vec![Adjacent {
+                    state: 1,
+                    trans: trans,
+                }],
+                vec![],
+            ],
         );
 
-        let state = State::initial(zeros(1), 2).fill_link(1, 3);
+        let links = vec![Link::new(0, 0, 1), Link::new(0, 0, 1)];
+        let layout = StateLayout::new(std::slice::from_ref(&automata), &links);
+        let state = layout.fill_link(layout.initial(&zeros(1)), 1, 3);
 
-        let next = automata.step_one(&state);
+        let next = automata.step_one(&state, &layout);
         assert_eq!(next.len(), 1);
 
         let (event, state) = &next[0];
 
-        assert!(state.links[in_link].is_none());
-        assert_eq!(state.links[out_link].unwrap(), out_ev);
+        let decoded = layout.decode(state);
+        assert!(decoded.links[in_link].is_empty());
+        assert_eq!(decoded.links[out_link], vec![out_ev]);
 
-        assert_eq!(state.states[0], 1);
+        assert_eq!(decoded.states[0], 1);
 
         assert_eq!(event.obs.unwrap(), 12);
         assert_eq!(event.rel.unwrap(), 31);
     }
+
+    #[test]
+    fn test_fifo_link() {
+        let layout = StateLayout::from_sizes(&[1, 1], &[2], 6);
+        let produce = |event| Transition::new(0, 0).add_output(Event::new(event, 0));
+        let consume = |event| Transition::new(1, 0).set_input(Event::new(event, 0));
+
+        let state = layout.initial(&zeros(2));
+        let (_, state) = produce(3).apply_transition(state, &layout);
+        assert!(produce(4).is_enabled(&state, &layout));
+        let (_, state) = produce(4).apply_transition(state, &layout);
+        assert!(!produce(5).is_enabled(&state, &layout));
+        assert_eq!(decode_links(&layout, &state), vec![vec![3, 4]]);
+
+        assert!(!consume(4).is_enabled(&state, &layout));
+        assert!(consume(3).is_enabled(&state, &layout));
+        let (_, state) = consume(3).apply_transition(state, &layout);
+        assert_eq!(decode_links(&layout, &state), vec![vec![4]]);
+        assert!(!layout.links_empty(&state));
+    }
+
+    #[test]
+    fn test_full_self_link() {
+        let layout = StateLayout::from_sizes(&[1], &[1], 5);
+        let state = layout.fill_link(layout.initial(&zeros(1)), 0, 3);
+        let trans = Transition::new(0, 0)
+            .set_input(Event::new(3, 0))
+            .add_output(Event::new(4, 0));
+        assert!(trans.is_enabled(&state, &layout));
+        let (_, state) = trans.apply_transition(state, &layout);
+        assert_eq!(decode_links(&layout, &state), vec![vec![4]]);
+    }
+
+    #[test]
+    fn test_final_criterion() {
+        // a -> b sending on L, b is the only final state
+        let automata = || {
+            let trans = Transition::new(0, 0).add_output(Event::new(0, 0));
+            Automata::new(0, 0, vec![vec![Adjacent::new(1, trans)], vec![]]).add_final(1)
+        };
+        let links = || vec![Link::new(0, 0, 1)];
+        let network = |criterion| Network::new(vec![automata()], links()).set_criterion(criterion);
+
+        let initial = network(FinalCriterion::EmptyLinks).get_initial_state();
+        let (_, next) = &network(FinalCriterion::EmptyLinks).step_one(&initial)[0];
+        let expected = [
+            (FinalCriterion::EmptyLinks, true, false),
+            (FinalCriterion::FinalStates, false, true),
+            (FinalCriterion::Both, false, false),
+            (FinalCriterion::Any, true, true),
+        ];
+        for (criterion, at_initial, at_next) in &expected {
+            let net = network(*criterion);
+            assert_eq!(net.is_final(&initial), *at_initial);
+            assert_eq!(net.is_final(next), *at_next);
+        }
+    }
+
+    #[test]
+    fn test_initial_link() {
+        // a -> b consuming event 2 from L, which starts holding it
+        let trans = Transition::new(0, 0).set_input(Event::new(2, 0));
+        let automata = Automata::new(0, 0, vec![vec![Adjacent::new(1, trans)], vec![]]);
+        let net = Network::new(vec![automata], vec![Link::new(0, 0, 1).set_init(2)]);
+
+        let initial = net.get_initial_state();
+        let decoded = net.get_layout().decode(&initial);
+        assert_eq!(decoded.states, vec![0]);
+        assert_eq!(decoded.links, vec![vec![2]]);
+        assert!(!net.is_final(&initial));
+
+        let next = net.step_one(&initial);
+        assert_eq!(next.len(), 1);
+        let decoded = net.get_layout().decode(&next[0].1);
+        assert_eq!(decoded.states, vec![1]);
+        assert_eq!(decoded.links, vec![Vec::<usize>::new()]);
+        assert!(net.is_final(&next[0].1));
+    }
 }
@@ -0,0 +1,216 @@
+/*
+    Load a specification split across multiple
+    files by `import` blocks. Loading happens in two phases:
+    first all the files are collected, following imports
+    depth first, then, once no more files are added, every
+    file is parsed with its own offset.
+*/
+
+use crate::diagnostics::{syntax_error_report, Report, SourceMap};
+use crate::input_output;
+
+use fsa_net_parser::syntax_tree::Block;
+use fsa_net_parser::{parse_with_offset, Code};
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+type Loc = (usize, usize);
+
+/**
+ * Load the input file and, recursively, all the imported files.
+ * Import paths are relative to the importing file.
+ * Errors in import resolution are returned as reports.
+ */
+pub fn load_sources(input: &Option<PathBuf>) -> io::Result<(SourceMap, Vec<Report>)> {
+    let code = input_output::get_fsa_code(input)?;
+    let name = input_output::get_input_name(input);
+    Ok(load_sources_from_code(name, code, input.as_deref()))
+}
+
+/**
+ * Same as `load_sources`, but the content of the input
+ * file is given: only imported files are read from disk.
+ */
+pub fn load_sources_from_code(
+    name: String,
+    code: String,
+    input: Option<&Path>,
+) -> (SourceMap, Vec<Report>) {
+    let (dir, path) = if let Some(input) = input {
+        let dir = input.parent().map(Path::to_path_buf).unwrap_or_default();
+        (dir, input.canonicalize().ok())
+    } else {
+        (PathBuf::new(), None)
+    };
+
+    let mut loader = SourceLoader::default();
+    let index = loader.sources.add_file(name, code);
+    loader.load_imports(index, &dir, path);
+    (loader.sources, loader.errors)
+}
+
+/**
+ * Parse all the files in the source map,
+ * collecting the syntax errors of every file.
+ * Files are parsed in reverse loading order: in this way
+ * imported files come before the files importing them.
+ */
+pub fn parse_sources(sources: &SourceMap) -> Result<Code<'_>, Vec<Report>> {
+    let mut code = vec![];
+    let mut errors = vec![];
+    for file in sources.files().rev() {
+        match parse_with_offset(file.get_code(), file.get_offset()) {
+            Ok(mut blocks) => code.append(&mut blocks),
+            Err(err) => errors.push(syntax_error_report(&err)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(code)
+    } else {
+        Err(errors)
+    }
+}
+
+#[derive(Default)]
+struct SourceLoader {
+    sources: SourceMap,
+    errors: Vec<Report>,
+    loaded: Vec<PathBuf>,
+    stack: Vec<(PathBuf, String)>,
+}
+
+impl SourceLoader {
+    fn load_imports(&mut self, index: usize, dir: &Path, path: Option<PathBuf>) {
+        if let Some(path) = &path {
+            self.loaded.push(path.clone());
+            let name = self.sources.get_file(index).get_name().to_owned();
+            self.stack.push((path.clone(), name));
+        }
+
+        for (import, loc) in self.collect_imports(index) {
+            self.load_import(dir.join(import), loc);
+        }
+
+        if path.is_some() {
+            self.stack.pop();
+        }
+    }
+
+    fn load_import(&mut self, path: PathBuf, loc: Loc) {
+        let canonical = match path.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(err) => return self.io_error(&path, loc, err),
+        };
+
+        if let Some(pos) = self.stack.iter().position(|(p, _)| *p == canonical) {
+            self.cycle_error(pos, loc);
+        } else if !self.loaded.contains(&canonical) {
+            let code = match input_output::load_str_from_file(&path) {
+                Ok(code) => code,
+                Err(err) => return self.io_error(&path, loc, err),
+            };
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            let index = self.sources.add_file(path.display().to_string(), code);
+            self.load_imports(index, &dir, Some(canonical));
+        }
+    }
+
+    /*
+        Syntax errors are ignored here: they
+        are reported when the files are parsed
+        in the second phase.
+    */
+    fn collect_imports(&self, index: usize) -> Vec<(String, Loc)> {
+        let file = self.sources.get_file(index);
+        if let Ok(code) = parse_with_offset(file.get_code(), file.get_offset()) {
+            code.iter()
+                .filter_map(|blk| match blk {
+                    Block::Import(import) => Some((import.path.to_owned(), import.get_location())),
+                    _ => None,
+                })
+                .collect()
+        } else {
+            vec![]
+        }
+    }
+
+    fn io_error(&mut self, path: &Path, loc: Loc, err: io::Error) {
+        let msg = format!("cannot import `{}`", path.display());
+        let report = Report::error(msg).with_label(loc, Some(err.to_string()));
+        self.errors.push(report);
+    }
+
+    fn cycle_error(&mut self, pos: usize, loc: Loc) {
+        let chain: Vec<&str> = self.stack[pos..]
+            .iter()
+            .chain(std::iter::once(&self.stack[pos]))
+            .map(|(_, name)| name.as_str())
+            .collect();
+        let report = Report::error("import cycle detected")
+            .with_label(loc, None)
+            .with_note(format!("cycle: {}", chain.join(" -> ")));
+        self.errors.push(report);
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn load(name: &str) -> (SourceMap, Vec<Report>) {
+        let path = PathBuf::from("fnl-test-code").join("imports").join(name);
+        load_sources(&Some(path)).unwrap()
+    }
+
+    #[test]
+    fn test_import() {
+        let (sources, errors) = load("request.fnl");
+        assert!(errors.is_empty());
+        let names: Vec<&str> = sources.files().map(|f| f.get_name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "fnl-test-code/imports/request.fnl",
+                "fnl-test-code/imports/networks/network.fnl",
+                "fnl-test-code/imports/networks/../common.fnl",
+            ]
+        );
+        let code = parse_sources(&sources).unwrap();
+        crate::compiler::compile(&code).expect("imported code should be correct");
+    }
+
+    #[test]
+    fn test_import_error_location() {
+        let (sources, errors) = load("ridefined.fnl");
+        assert!(errors.is_empty());
+        let code = parse_sources(&sources).unwrap();
+        let errors = crate::compiler::compile(&code).unwrap_err();
+        let reports = crate::diagnostics::compile_error_reports(&errors);
+        assert_eq!(reports.len(), 1);
+        let output = reports[0].render(&sources);
+        assert!(output.contains("--> fnl-test-code/imports/ridefined.fnl:3:1\n"));
+        assert!(output.contains("--> fnl-test-code/imports/networks/network.fnl:3:1\n"));
+    }
+
+    #[test]
+    fn test_import_cycle() {
+        let (sources, errors) = load("cycle-a.fnl");
+        assert_eq!(sources.files().count(), 2);
+        assert_eq!(errors.len(), 1);
+        let output = errors[0].render(&sources);
+        assert!(output.starts_with(
+            "error: import cycle detected\n --> fnl-test-code/imports/cycle-b.fnl:1:1\n"
+        ));
+        assert!(output.contains(
+            "cycle: fnl-test-code/imports/cycle-a.fnl -> fnl-test-code/imports/cycle-b.fnl -> fnl-test-code/imports/cycle-a.fnl"
+        ));
+    }
+
+    #[test]
+    fn test_missing_import() {
+        let (_, errors) = load("missing.fnl");
+        assert_eq!(errors.len(), 1);
+    }
+}
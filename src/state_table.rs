@@ -1,4 +1,8 @@
+use ahash::{AHashMap, RandomState};
 use indexmap::IndexMap;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 #[derive(Debug)]
 pub struct StateTable<T> {
@@ -49,3 +53,69 @@ where
         self.table.into_iter().map(|(s, _)| s).collect()
     }
 }
+
+/*
+    State table shared between threads. States are
+    split among shards by hash so that threads inserting
+    different states rarely wait on the same lock.
+    Indexes are unique but their order depends
+    on thread scheduling.
+*/
+pub struct ShardedStateTable<T> {
+    shards: Vec<Mutex<AHashMap<T, usize>>>,
+    hasher: RandomState,
+    count: AtomicUsize,
+}
+
+impl<T> ShardedStateTable<T>
+where
+    T: Eq + Hash + Clone,
+{
+    pub fn new(shard_count: usize) -> Self {
+        Self {
+            shards: (0..shard_count.max(1))
+                .map(|_| Mutex::new(AHashMap::new()))
+                .collect(),
+            hasher: RandomState::new(),
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    /**
+     * Return the index of the state and
+     * true if the state was not in the table
+     */
+    pub fn insert_state(&self, s: &T) -> (usize, bool) {
+        let mut shard = self.shards[self.shard_index(s)].lock().unwrap();
+        if let Some(i) = shard.get(s) {
+            (*i, false)
+        } else {
+            let index = self.count.fetch_add(1, Ordering::Relaxed);
+            shard.insert(s.clone(), index);
+            (index, true)
+        }
+    }
+
+    fn shard_index(&self, s: &T) -> usize {
+        self.hasher.hash_one(s) as usize % self.shards.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_sharded_table() {
+        let table = ShardedStateTable::new(4);
+        let indexes: Vec<(usize, bool)> = ["a", "b", "a", "c", "b"]
+            .iter()
+            .map(|s| table.insert_state(s))
+            .collect();
+        assert_eq!(
+            indexes,
+            vec![(0, true), (1, true), (0, false), (2, true), (1, false)]
+        );
+    }
+}